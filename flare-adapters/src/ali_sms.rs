@@ -1,40 +1,185 @@
-use flare_common::{SmsConfig, FlareError};
+use flare_common::{DeliveryStatus, SmsConfig, SmsSignMethod, FlareError};
 use reqwest::Client;
-use serde::Serialize;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
 use async_trait::async_trait;
 use flare_core::{Sender, Notification};
 use flare_common::FlareResult;
-use std::collections::HashMap;
-
-/// 阿里云短信API请求参数
-#[derive(Serialize)]
-struct SmsRequest<'a> {
-    #[serde(rename = "PhoneNumbers")]
-    phone_numbers: &'a str,
-    #[serde(rename = "SignName")]
-    sign_name: &'a str,
-    #[serde(rename = "TemplateCode")]
-    template_code: &'a str,
-    #[serde(rename = "TemplateParam")]
-    template_param: &'a str,
-    #[serde(rename = "Action")]
-    action: &'a str,
-    #[serde(rename = "Version")]
-    version: &'a str,
-    #[serde(rename = "RegionId")]
-    region_id: &'a str,
-    #[serde(rename = "AccessKeyId")]
-    access_key_id: &'a str,
-    #[serde(rename = "Signature")]
-    signature: String,
-    #[serde(rename = "SignatureMethod")]
-    signature_method: &'a str,
-    #[serde(rename = "SignatureVersion")]
-    signature_version: &'a str,
-    #[serde(rename = "SignatureNonce")]
-    signature_nonce: String,
-    #[serde(rename = "Timestamp")]
-    timestamp: String,
+use std::collections::BTreeMap;
+
+use crate::{aliyun_sign, signing};
+use crate::SmsGateway;
+
+/// 短信 API 版本，中国内地与国际地域相同
+const API_VERSION: &str = "2017-05-25";
+
+/// SendBatchSms 单次请求最多的号码数
+const BATCH_LIMIT: usize = 100;
+
+/// 可稍后重试的错误码：流控与服务端异常
+const RETRYABLE_CODES: [&str; 5] = [
+    "isv.BUSINESS_LIMIT_CONTROL",
+    "Throttling.User",
+    "isp.SYSTEM_ERROR",
+    "ServiceUnavailable",
+    "InternalError",
+];
+
+/// 阿里云 SendSms 响应，其 Code/Message/RequestId 也是各接口的公共响应头
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SendSmsResponse {
+    pub code: String,
+    #[serde(default)]
+    pub message: String,
+    /// 发送回执 ID，用于查询发送状态、对账
+    #[serde(default)]
+    pub biz_id: Option<String>,
+    #[serde(default)]
+    pub request_id: Option<String>,
+}
+
+impl SendSmsResponse {
+    fn into_result(self) -> FlareResult<Self> {
+        if self.code != "OK" {
+            return Err(FlareError::Sms {
+                provider: "aliyun",
+                retryable: RETRYABLE_CODES.contains(&self.code.as_str()),
+                code: self.code,
+                message: self.message,
+                request_id: self.request_id,
+            });
+        }
+        Ok(self)
+    }
+}
+
+/// 单条短信的模板与签名，未指定的字段使用配置中的默认值
+#[derive(Debug, Clone, Default)]
+pub struct SmsTemplate {
+    /// 模板别名（见 `SMS_TEMPLATES`）或服务商模板 ID（阿里云为 `SMS_` 开头的 Code）
+    pub template_code: Option<String>,
+    pub sign_name: Option<String>,
+}
+
+/// QuerySendDetails 返回的单条发送记录
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SmsSendDetail {
+    pub phone_num: String,
+    /// 1 等待回执，2 发送失败，3 发送成功
+    pub send_status: i32,
+    /// 运营商回执码，成功时为 DELIVERED
+    #[serde(default)]
+    pub err_code: Option<String>,
+    #[serde(default)]
+    pub template_code: Option<String>,
+    #[serde(default)]
+    pub content: Option<String>,
+    #[serde(default)]
+    pub send_date: Option<String>,
+    #[serde(default)]
+    pub receive_date: Option<String>,
+    #[serde(default)]
+    pub out_id: Option<String>,
+}
+
+impl SmsSendDetail {
+    pub fn delivery_status(&self) -> DeliveryStatus {
+        match self.send_status {
+            3 => DeliveryStatus::Delivered,
+            2 => DeliveryStatus::Failed,
+            _ => DeliveryStatus::Sent,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct QuerySendDetailsResponse {
+    #[serde(rename = "SmsSendDetailDTOs", default)]
+    details: SmsSendDetailList,
+}
+
+#[derive(Default, Deserialize)]
+struct SmsSendDetailList {
+    #[serde(rename = "SmsSendDetailDTO", default)]
+    items: Vec<SmsSendDetail>,
+}
+
+/// 阿里云短信状态报告（SmsReport），HTTP 批量推送时请求体为该结构的数组
+#[derive(Debug, Clone, Deserialize)]
+pub struct SmsReport {
+    pub phone_number: String,
+    pub success: bool,
+    pub biz_id: String,
+    #[serde(default)]
+    pub err_code: Option<String>,
+    #[serde(default)]
+    pub err_msg: Option<String>,
+    #[serde(default)]
+    pub send_time: Option<String>,
+    #[serde(default)]
+    pub report_time: Option<String>,
+    #[serde(default)]
+    pub out_id: Option<String>,
+}
+
+impl SmsReport {
+    pub fn delivery_status(&self) -> DeliveryStatus {
+        if self.success { DeliveryStatus::Delivered } else { DeliveryStatus::Failed }
+    }
+}
+
+impl SmsTemplate {
+    /// 通过 `Sender` 发送时以 `Notification.subject` 作为模板别名，为空则使用默认模板
+    pub fn from_subject(subject: &str) -> Self {
+        Self {
+            template_code: (!subject.is_empty()).then(|| subject.to_string()),
+            sign_name: None,
+        }
+    }
+}
+
+/// 批量短信中的一个号码
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct BatchSmsItem {
+    pub phone: String,
+    /// 覆盖本号码的签名，未指定时使用批次签名
+    #[serde(default)]
+    pub sign_name: Option<String>,
+    /// 本号码的模板参数 JSON 字符串
+    #[serde(default, alias = "param")]
+    pub template_param: Option<String>,
+}
+
+/// 批量短信中单个号码的发送结果
+#[derive(Debug, Clone)]
+pub struct BatchSmsResult {
+    pub phone: String,
+    /// 所在批次的回执 ID，失败时为 None
+    pub biz_id: Option<String>,
+    /// 失败时的错误码（非短信业务错误时为空）与说明，成功时为 None
+    pub error: Option<(String, String)>,
+    /// 失败是否可重试
+    pub retryable: bool,
+}
+
+impl BatchSmsResult {
+    fn new(phone: &str, outcome: &FlareResult<SendSmsResponse>) -> Self {
+        let (biz_id, error, retryable) = match outcome {
+            Ok(resp) => (resp.biz_id.clone(), None, false),
+            Err(FlareError::Sms { code, message, retryable, .. }) => {
+                (None, Some((code.clone(), message.clone())), *retryable)
+            }
+            Err(e) => (None, Some((String::new(), e.to_string())), e.is_retryable()),
+        };
+        Self { phone: phone.to_string(), biz_id, error, retryable }
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
 }
 
 /// 短信发送器
@@ -51,87 +196,228 @@ impl SmsSender {
         }
     }
 
-    async fn send_sms(&self, phone: &str, param_json: &str) -> FlareResult<()> {
-        use uuid::Uuid;
-        use hmac::{Hmac, Mac};
-        use sha1::Sha1;
-        use base64::Engine;
-        
+    /// 使用默认模板与签名发送短信，成功时返回包含 BizId 的响应
+    pub async fn send_sms(&self, phone: &str, param_json: &str) -> FlareResult<SendSmsResponse> {
+        self.send_sms_with(phone, param_json, &SmsTemplate::default()).await
+    }
+
+    /// 将模板别名解析为模板 Code；未知别名直接报错，避免发出无效模板
+    fn resolve_template<'a>(&'a self, template: &'a SmsTemplate) -> FlareResult<(&'a str, &'a str)> {
+        let template_code = match template.template_code.as_deref() {
+            None => self.config.template_code.as_str(),
+            Some(name) => match self.config.template_alias(name) {
+                Some(code) => code,
+                None if name.starts_with("SMS_") => name,
+                None => return Err(FlareError::Config(format!("未知短信模板: {}", name))),
+            },
+        };
+        let sign_name = template.sign_name.as_deref().unwrap_or(&self.config.sign_name);
+        Ok((template_code, sign_name))
+    }
+
+    /// 按指定模板与签名发送短信
+    pub async fn send_sms_with(
+        &self,
+        phone: &str,
+        param_json: &str,
+        template: &SmsTemplate,
+    ) -> FlareResult<SendSmsResponse> {
+        let (template_code, sign_name) = self.resolve_template(template)?;
+
+        let result: SendSmsResponse = self
+            .call("SendSms", &[
+                ("PhoneNumbers", phone),
+                ("SignName", sign_name),
+                ("TemplateCode", template_code),
+                ("TemplateParam", param_json),
+            ])
+            .await?;
+        tracing::info!(
+            "短信已提交 phone={} template={} biz_id={}",
+            phone,
+            template_code,
+            result.biz_id.as_deref().unwrap_or_default()
+        );
+        Ok(result)
+    }
+
+    /// 批量发送：同一模板，每个号码可单独指定签名与模板参数。
+    /// 超过阿里云单次上限时自动分批，返回结果与 `items` 一一对应；
+    /// 同一批次的号码共享该批次的发送结果
+    pub async fn send_batch_sms(
+        &self,
+        items: &[BatchSmsItem],
+        template: &SmsTemplate,
+    ) -> FlareResult<Vec<BatchSmsResult>> {
+        let (template_code, default_sign) = self.resolve_template(template)?;
+
+        // 先校验全部模板参数，避免部分批次已发出后才发现格式错误
+        let params = items
+            .iter()
+            .map(|item| match item.template_param.as_deref() {
+                Some(raw) => serde_json::from_str::<Value>(raw).map_err(|e| {
+                    FlareError::Config(format!("号码 {} 的模板参数不是合法 JSON: {}", item.phone, e))
+                }),
+                None => Ok(Value::Object(Default::default())),
+            })
+            .collect::<FlareResult<Vec<_>>>()?;
+
+        let mut results = Vec::with_capacity(items.len());
+        for (chunk, params) in items.chunks(BATCH_LIMIT).zip(params.chunks(BATCH_LIMIT)) {
+            let phones: Vec<&str> = chunk.iter().map(|item| item.phone.as_str()).collect();
+            let signs: Vec<&str> = chunk
+                .iter()
+                .map(|item| item.sign_name.as_deref().unwrap_or(default_sign))
+                .collect();
+            let phone_json = Value::from(phones).to_string();
+            let sign_json = Value::from(signs).to_string();
+            let param_json = Value::from(params.to_vec()).to_string();
+
+            let outcome = self
+                .call::<SendSmsResponse>("SendBatchSms", &[
+                    ("PhoneNumberJson", &phone_json),
+                    ("SignNameJson", &sign_json),
+                    ("TemplateCode", template_code),
+                    ("TemplateParamJson", &param_json),
+                ])
+                .await;
+            match &outcome {
+                Ok(resp) => tracing::info!(
+                    "批量短信已提交 count={} template={} biz_id={}",
+                    chunk.len(),
+                    template_code,
+                    resp.biz_id.as_deref().unwrap_or_default()
+                ),
+                Err(e) => tracing::warn!("批量短信发送失败 count={} error={}", chunk.len(), e),
+            }
+            results.extend(chunk.iter().map(|item| BatchSmsResult::new(&item.phone, &outcome)));
+        }
+        Ok(results)
+    }
+
+    /// 按回执 ID 查询号码的发送记录，`send_date` 为发送日期（北京时间，yyyyMMdd）。
+    /// 回执通常在几秒到几分钟内到达，记录状态为等待回执时稍后再查
+    pub async fn query_send_details(
+        &self,
+        phone: &str,
+        send_date: &str,
+        biz_id: Option<&str>,
+    ) -> FlareResult<Vec<SmsSendDetail>> {
+        let mut params = vec![
+            ("PhoneNumber", phone),
+            ("SendDate", send_date),
+            ("PageSize", "50"),
+            ("CurrentPage", "1"),
+        ];
+        if let Some(biz_id) = biz_id {
+            params.push(("BizId", biz_id));
+        }
+        let resp: QuerySendDetailsResponse = self.call("QuerySendDetails", &params).await?;
+        Ok(resp.details.items)
+    }
+
+    /// 调用短信 API：按配置的签名方式补齐公共参数、签名后以表单提交
+    async fn call<T: DeserializeOwned>(&self, action: &str, biz_params: &[(&str, &str)]) -> FlareResult<T> {
+        let params: BTreeMap<&str, &str> = biz_params.iter().copied().collect();
+        let request = match self.config.sign_method {
+            SmsSignMethod::HmacSha1 => self.rpc_v1_request(action, &params),
+            SmsSignMethod::Acs3HmacSha256 => self.acs3_request(action, &params)?,
+        };
+        let resp = request.send().await?;
+
+        let status = resp.status();
+        let text = resp.text().await?;
+        tracing::debug!("Sms response: {}", text);
+
+        // 业务错误（含签名错误）也以 JSON 返回 Code，优先解析响应体
+        match serde_json::from_str::<SendSmsResponse>(&text) {
+            Ok(envelope) => envelope.into_result()?,
+            Err(_) => return Err(FlareError::HttpStatus { status: status.as_u16(), body: text }),
+        };
+        serde_json::from_str(&text)
+            .map_err(|e| FlareError::String(format!("{} 响应解析失败: {}", action, e)))
+    }
+
+    /// RPC 风格 V1 签名：公共参数与签名都放在表单中
+    fn rpc_v1_request(&self, action: &str, biz_params: &BTreeMap<&str, &str>) -> reqwest::RequestBuilder {
         // 生成时间戳和随机数 (ISO 8601 格式)
         let timestamp = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
-        let nonce = Uuid::new_v4().to_string();
-        
-        // 构建请求参数
-        let mut params = HashMap::new();
-        params.insert("Action", "SendSms");
-        params.insert("Version", "2017-05-25");
-        params.insert("RegionId", "cn-hangzhou");
-        params.insert("PhoneNumbers", phone);
-        params.insert("SignName", &self.config.sign_name);
-        params.insert("TemplateCode", &self.config.template_code);
-        params.insert("TemplateParam", param_json);
+        let nonce = uuid::Uuid::new_v4().to_string();
+
+        let mut params: BTreeMap<&str, &str> = biz_params.iter().map(|(k, v)| (*k, *v)).collect();
+
+        params.insert("Action", action);
+        params.insert("Version", API_VERSION);
+        params.insert("RegionId", &self.config.region_id);
         params.insert("AccessKeyId", &self.config.access_key_id);
         params.insert("SignatureMethod", "HMAC-SHA1");
         params.insert("SignatureVersion", "1.0");
         params.insert("SignatureNonce", &nonce);
         params.insert("Timestamp", &timestamp);
-        
-        // 生成签名
-        let mut sorted_params: Vec<_> = params.iter().collect();
-        sorted_params.sort_by_key(|(k, _)| *k);
-        
-        let query_string = sorted_params
-            .iter()
-            .map(|(k, v)| format!("{}={}", k, urlencoding::encode(v)))
-            .collect::<Vec<_>>()
-            .join("&");
-        
-        let string_to_sign = format!("POST&{}&{}",
-            urlencoding::encode("/"),
-            urlencoding::encode(&query_string));
-        
-        let signing_key = format!("{}&", self.config.access_key_secret);
-        let mut mac = Hmac::<Sha1>::new_from_slice(signing_key.as_bytes())
-            .map_err(|e| FlareError::String(format!("HMAC key error: {}", e)))?;
-        mac.update(string_to_sign.as_bytes());
-        let signature = base64::engine::general_purpose::STANDARD.encode(mac.finalize().into_bytes());
-        
-        // 构建最终请求
-        let req = SmsRequest {
-            phone_numbers: phone,
-            sign_name: &self.config.sign_name,
-            template_code: &self.config.template_code,
-            template_param: param_json,
-            action: "SendSms",
-            version: "2017-05-25",
-            region_id: "cn-hangzhou",
-            access_key_id: &self.config.access_key_id,
-            signature,
-            signature_method: "HMAC-SHA1",
-            signature_version: "1.0",
-            signature_nonce: nonce,
-            timestamp,
+        params.insert("Format", "JSON");
+
+        let signature = aliyun_sign::sign_rpc_v1("POST", &params, &self.config.access_key_secret);
+        params.insert("Signature", &signature);
+
+        self.client.post(&self.config.endpoint).form(&params)
+    }
+
+    /// V3 签名：接口名、版本等放在 x-acs-* 请求头，业务参数以表单提交，签名放在 Authorization
+    fn acs3_request(&self, action: &str, params: &BTreeMap<&str, &str>) -> FlareResult<reqwest::RequestBuilder> {
+        let url = reqwest::Url::parse(&self.config.endpoint)
+            .map_err(|e| FlareError::Config(format!("无效的 SMS_ENDPOINT {}: {}", self.config.endpoint, e)))?;
+        let host = url.host_str().unwrap_or_default();
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
         };
 
-        let resp = self.client
-            .post(&self.config.endpoint)
-            .form(&req)
-            .send()
-            .await?;
+        let body = aliyun_sign::canonical_query(params);
+        let payload_hash = signing::sha256_hex(body.as_bytes());
+        let headers = BTreeMap::from([
+            ("content-type", "application/x-www-form-urlencoded".to_string()),
+            ("host", host),
+            ("x-acs-action", action.to_string()),
+            ("x-acs-content-sha256", payload_hash.clone()),
+            ("x-acs-date", chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string()),
+            ("x-acs-signature-nonce", uuid::Uuid::new_v4().simple().to_string()),
+            ("x-acs-version", API_VERSION.to_string()),
+        ]);
+        let authorization = aliyun_sign::Acs3Request {
+            method: "POST",
+            uri: url.path(),
+            query: &BTreeMap::new(),
+            headers: &headers,
+            payload_hash: &payload_hash,
+        }
+        .authorization(&self.config.access_key_id, &self.config.access_key_secret);
 
-        let text = resp.text().await?;
-        println!("Sms response: {}", text);
+        // host 由 reqwest 按 URL 填写，与参与签名的值一致
+        let request = headers
+            .iter()
+            .filter(|(name, _)| **name != "host")
+            .fold(self.client.post(url), |req, (name, value)| req.header(*name, value))
+            .header("authorization", authorization)
+            .body(body);
+        Ok(request)
+    }
+}
 
-        // 可在这里根据返回判断是否发送成功
-        Ok(())
+#[async_trait]
+impl SmsGateway for SmsSender {
+    /// param 为模板参数 JSON 字符串，返回 BizId
+    async fn send_templated(&self, phone: &str, param: &str, template: &SmsTemplate) -> FlareResult<Option<String>> {
+        Ok(self.send_sms_with(phone, param, template).await?.biz_id)
     }
 }
 
 #[async_trait]
 impl Sender for SmsSender {
     async fn send(&self, notification: &Notification) -> FlareResult<()> {
-        // 假设 Notification.to 是手机号，body 是 JSON 参数字符串
-        self.send_sms(&notification.to, &notification.body).await
+        // 假设 Notification.to 是手机号，body 是 JSON 参数字符串，subject 可指定模板别名
+        let template = SmsTemplate::from_subject(&notification.subject);
+        self.send_sms_with(&notification.to, &notification.body, &template).await?;
+        Ok(())
     }
 }
 
@@ -159,4 +445,267 @@ mod tests {
 
         println!("短信发送完成！result: {:?}", result);
     }
-}
\ No newline at end of file
+
+    mod response {
+        use super::*;
+        use crate::mock_server::MockServer;
+        use serde_json::json;
+
+        async fn build_mock_sender(response: serde_json::Value) -> (MockServer, SmsSender) {
+            build_mock_sender_with(response, SmsSignMethod::HmacSha1).await
+        }
+
+        async fn build_mock_sender_with(
+            response: serde_json::Value,
+            sign_method: SmsSignMethod,
+        ) -> (MockServer, SmsSender) {
+            let server = MockServer::start().await;
+            server.respond_json("/", response);
+            let cfg = SmsConfig {
+                endpoint: server.url("/"),
+                region_id: "ap-southeast-1".into(),
+                sign_method,
+                access_key_id: "test-key-id".into(),
+                access_key_secret: "test-secret".into(),
+                sign_name: "阿里云短信测试".into(),
+                template_code: "SMS_154950909".into(),
+                templates: [("order_shipped".to_string(), "SMS_456".to_string())].into(),
+            };
+            (server, SmsSender::new(cfg))
+        }
+
+        #[tokio::test]
+        async fn ok_returns_biz_id() {
+            let (server, sender) = build_mock_sender(json!({
+                "Code": "OK",
+                "Message": "OK",
+                "BizId": "9006197469364984400",
+                "RequestId": "F655A8D5-B967-440B-8683-DAD6FF8DE990"
+            })).await;
+
+            let result = sender.send_sms("13800000000", "{\"code\":\"1234\"}").await.unwrap();
+            assert_eq!(result.biz_id.as_deref(), Some("9006197469364984400"));
+            assert_eq!(result.request_id.as_deref(), Some("F655A8D5-B967-440B-8683-DAD6FF8DE990"));
+
+            let req = &server.requests()[0];
+            assert_eq!(req.form_param("Format").as_deref(), Some("JSON"));
+            assert_eq!(req.form_param("PhoneNumbers").as_deref(), Some("13800000000"));
+            assert_eq!(req.form_param("TemplateParam").as_deref(), Some("{\"code\":\"1234\"}"));
+            assert!(req.form_param("Signature").is_some());
+        }
+
+        #[tokio::test]
+        async fn flow_control_is_retryable() {
+            let (_server, sender) = build_mock_sender(json!({
+                "Code": "isv.BUSINESS_LIMIT_CONTROL",
+                "Message": "触发分钟级流控Permits:1",
+                "RequestId": "req-1"
+            })).await;
+
+            let err = sender.send_sms("13800000000", "{}").await.unwrap_err();
+            match &err {
+                FlareError::Sms { provider, code, request_id, .. } => {
+                    assert_eq!(*provider, "aliyun");
+                    assert_eq!(code, "isv.BUSINESS_LIMIT_CONTROL");
+                    assert_eq!(request_id.as_deref(), Some("req-1"));
+                }
+                other => panic!("unexpected error: {:?}", other),
+            }
+            assert!(err.is_retryable());
+        }
+
+        #[tokio::test]
+        async fn signature_mismatch_is_not_retryable() {
+            let (_server, sender) = build_mock_sender(json!({
+                "Code": "SignatureDoesNotMatch",
+                "Message": "Specified signature is not matched with our calculation.",
+                "RequestId": "req-2"
+            })).await;
+
+            let err = sender.send_sms("13800000000", "{}").await.unwrap_err();
+            assert!(matches!(&err, FlareError::Sms { code, .. } if code == "SignatureDoesNotMatch"));
+            assert!(!err.is_retryable());
+        }
+
+        #[tokio::test]
+        async fn template_alias_and_sign_override() {
+            let (server, sender) = build_mock_sender(json!({ "Code": "OK", "BizId": "1" })).await;
+
+            let template = SmsTemplate {
+                template_code: Some("order_shipped".into()),
+                sign_name: Some("商城".into()),
+            };
+            sender.send_sms_with("13800000000", "{}", &template).await.unwrap();
+            let template = SmsTemplate { template_code: Some("SMS_789".into()), sign_name: None };
+            sender.send_sms_with("13800000000", "{}", &template).await.unwrap();
+            sender.send_sms("13800000000", "{}").await.unwrap();
+
+            let requests = server.requests();
+            let fields = |i: usize| {
+                (requests[i].form_param("TemplateCode").unwrap(), requests[i].form_param("SignName").unwrap())
+            };
+            assert_eq!(fields(0), ("SMS_456".to_string(), "商城".to_string()));
+            assert_eq!(fields(1), ("SMS_789".to_string(), "阿里云短信测试".to_string()));
+            assert_eq!(fields(2), ("SMS_154950909".to_string(), "阿里云短信测试".to_string()));
+        }
+
+        #[tokio::test]
+        async fn unknown_template_alias_is_rejected() {
+            let (server, sender) = build_mock_sender(json!({ "Code": "OK" })).await;
+
+            let template = SmsTemplate { template_code: Some("login_cdoe".into()), sign_name: None };
+            let err = sender.send_sms_with("13800000000", "{}", &template).await.unwrap_err();
+            assert!(matches!(err, FlareError::Config(_)));
+            assert!(server.requests().is_empty());
+        }
+
+        fn batch_items(count: usize) -> Vec<BatchSmsItem> {
+            (0..count)
+                .map(|i| BatchSmsItem {
+                    phone: format!("138{:08}", i),
+                    sign_name: (i == 1).then(|| "商城".to_string()),
+                    template_param: (i == 0).then(|| "{\"name\":\"张三\"}".to_string()),
+                })
+                .collect()
+        }
+
+        #[tokio::test]
+        async fn batch_sends_json_arrays() {
+            let (server, sender) = build_mock_sender(json!({ "Code": "OK", "BizId": "batch-1" })).await;
+
+            let results = sender.send_batch_sms(&batch_items(2), &SmsTemplate::default()).await.unwrap();
+            assert_eq!(results.len(), 2);
+            assert!(results.iter().all(|r| r.is_success() && r.biz_id.as_deref() == Some("batch-1")));
+
+            let req = &server.requests()[0];
+            assert_eq!(req.form_param("Action").as_deref(), Some("SendBatchSms"));
+            let json_param = |key| serde_json::from_str::<serde_json::Value>(&req.form_param(key).unwrap()).unwrap();
+            assert_eq!(json_param("PhoneNumberJson"), json!(["13800000000", "13800000001"]));
+            assert_eq!(json_param("SignNameJson"), json!(["阿里云短信测试", "商城"]));
+            assert_eq!(json_param("TemplateParamJson"), json!([{ "name": "张三" }, {}]));
+            assert_eq!(req.form_param("TemplateCode").as_deref(), Some("SMS_154950909"));
+        }
+
+        #[tokio::test]
+        async fn batch_chunks_to_limit() {
+            let (server, sender) = build_mock_sender(json!({ "Code": "OK", "BizId": "b" })).await;
+
+            let results = sender.send_batch_sms(&batch_items(BATCH_LIMIT + 5), &SmsTemplate::default()).await.unwrap();
+            assert_eq!(results.len(), BATCH_LIMIT + 5);
+            assert_eq!(results[BATCH_LIMIT].phone, format!("138{:08}", BATCH_LIMIT));
+
+            let requests = server.requests();
+            assert_eq!(requests.len(), 2);
+            let count = |i: usize| {
+                serde_json::from_str::<Vec<String>>(&requests[i].form_param("PhoneNumberJson").unwrap()).unwrap().len()
+            };
+            assert_eq!((count(0), count(1)), (BATCH_LIMIT, 5));
+        }
+
+        #[tokio::test]
+        async fn batch_reports_failure_per_number() {
+            let (_server, sender) = build_mock_sender(json!({
+                "Code": "isv.BUSINESS_LIMIT_CONTROL",
+                "Message": "触发天级流控"
+            })).await;
+
+            let results = sender.send_batch_sms(&batch_items(2), &SmsTemplate::default()).await.unwrap();
+            for result in &results {
+                assert!(!result.is_success());
+                assert!(result.retryable);
+                assert_eq!(result.error.as_ref().unwrap().0, "isv.BUSINESS_LIMIT_CONTROL");
+            }
+        }
+
+        #[tokio::test]
+        async fn batch_rejects_invalid_template_param() {
+            let (server, sender) = build_mock_sender(json!({ "Code": "OK" })).await;
+
+            let mut items = batch_items(2);
+            items[1].template_param = Some("{'code':'1'}".into());
+            let err = sender.send_batch_sms(&items, &SmsTemplate::default()).await.unwrap_err();
+            assert!(matches!(err, FlareError::Config(_)));
+            assert!(server.requests().is_empty());
+        }
+
+        #[tokio::test]
+        async fn query_send_details_parses_records() {
+            let (server, sender) = build_mock_sender(json!({
+                "Code": "OK",
+                "Message": "OK",
+                "TotalCount": 2,
+                "SmsSendDetailDTOs": { "SmsSendDetailDTO": [
+                    { "PhoneNum": "13800000000", "SendStatus": 3, "ErrCode": "DELIVERED",
+                      "SendDate": "2024-05-20 10:00:00", "ReceiveDate": "2024-05-20 10:00:03" },
+                    { "PhoneNum": "13800000000", "SendStatus": 2, "ErrCode": "MK:0001" }
+                ]}
+            })).await;
+
+            let details = sender.query_send_details("13800000000", "20240520", Some("biz-1")).await.unwrap();
+            assert_eq!(details.len(), 2);
+            assert_eq!(details[0].delivery_status(), DeliveryStatus::Delivered);
+            assert_eq!(details[1].delivery_status(), DeliveryStatus::Failed);
+            assert_eq!(details[1].err_code.as_deref(), Some("MK:0001"));
+
+            let req = &server.requests()[0];
+            assert_eq!(req.form_param("Action").as_deref(), Some("QuerySendDetails"));
+            assert_eq!(req.form_param("SendDate").as_deref(), Some("20240520"));
+            assert_eq!(req.form_param("BizId").as_deref(), Some("biz-1"));
+        }
+
+        #[test]
+        fn sms_report_status() {
+            let reports: Vec<SmsReport> = serde_json::from_value(json!([
+                { "phone_number": "13800000000", "success": true, "biz_id": "b1",
+                  "err_code": "DELIVERED", "err_msg": "用户接收成功", "send_time": "2024-05-20 10:00:00",
+                  "report_time": "2024-05-20 10:00:03", "sms_size": "1" },
+                { "phone_number": "13800000001", "success": false, "biz_id": "b1", "err_code": "MK:0001" }
+            ])).unwrap();
+            assert_eq!(reports[0].delivery_status(), DeliveryStatus::Delivered);
+            assert_eq!(reports[1].delivery_status(), DeliveryStatus::Failed);
+        }
+
+        #[tokio::test]
+        async fn rpc_v1_uses_configured_region() {
+            let (server, sender) = build_mock_sender(json!({ "Code": "OK" })).await;
+            sender.send_sms("+6591234567", "{}").await.unwrap();
+
+            let req = &server.requests()[0];
+            assert_eq!(req.form_param("RegionId").as_deref(), Some("ap-southeast-1"));
+            assert_eq!(req.form_param("SignatureMethod").as_deref(), Some("HMAC-SHA1"));
+        }
+
+        #[tokio::test]
+        async fn acs3_signs_headers_and_form_body() {
+            let (server, sender) = build_mock_sender_with(json!({ "Code": "OK", "BizId": "v3" }), SmsSignMethod::Acs3HmacSha256).await;
+            let result = sender.send_sms("13800000000", "{\"code\":\"1234\"}").await.unwrap();
+            assert_eq!(result.biz_id.as_deref(), Some("v3"));
+
+            let req = &server.requests()[0];
+            assert_eq!(req.header("x-acs-action"), Some("SendSms"));
+            assert_eq!(req.header("x-acs-version"), Some(API_VERSION));
+            assert_eq!(req.form_param("TemplateParam").as_deref(), Some("{\"code\":\"1234\"}"));
+            assert!(req.form_param("Signature").is_none());
+
+            // 按实际收到的请求头和请求体重新计算签名
+            let payload_hash = signing::sha256_hex(&req.body);
+            assert_eq!(req.header("x-acs-content-sha256"), Some(payload_hash.as_str()));
+            let headers: BTreeMap<&str, String> = [
+                "content-type", "host", "x-acs-action", "x-acs-content-sha256",
+                "x-acs-date", "x-acs-signature-nonce", "x-acs-version",
+            ]
+            .into_iter()
+            .map(|name| (name, req.header(name).unwrap().to_string()))
+            .collect();
+            let expected = aliyun_sign::Acs3Request {
+                method: "POST",
+                uri: "/",
+                query: &BTreeMap::new(),
+                headers: &headers,
+                payload_hash: &payload_hash,
+            }
+            .authorization("test-key-id", "test-secret");
+            assert_eq!(req.header("authorization"), Some(expected.as_str()));
+        }
+    }
+}
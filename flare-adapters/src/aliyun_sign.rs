@@ -0,0 +1,162 @@
+use std::collections::BTreeMap;
+
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
+use crate::signing::{hmac_sha256_hex, sha256_hex};
+
+/// 阿里云签名使用的百分号编码：只保留 RFC 3986 非保留字符，空格编码为 %20
+pub(crate) fn percent_encode(value: &str) -> String {
+    urlencoding::encode(value).into_owned()
+}
+
+/// 按参数名排序后拼成 `k=v&k=v`，键值均做百分号编码
+pub(crate) fn canonical_query(params: &BTreeMap<&str, &str>) -> String {
+    params
+        .iter()
+        .map(|(k, v)| format!("{}={}", percent_encode(k), percent_encode(v)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// RPC 风格 V1 签名（HMAC-SHA1），`params` 为除 Signature 外的全部参数
+pub(crate) fn sign_rpc_v1(method: &str, params: &BTreeMap<&str, &str>, secret: &str) -> String {
+    let string_to_sign = format!(
+        "{}&{}&{}",
+        method,
+        percent_encode("/"),
+        percent_encode(&canonical_query(params))
+    );
+    let mut mac = Hmac::<Sha1>::new_from_slice(format!("{}&", secret).as_bytes())
+        .expect("HMAC 接受任意长度的密钥");
+    mac.update(string_to_sign.as_bytes());
+    base64::engine::general_purpose::STANDARD.encode(mac.finalize().into_bytes())
+}
+
+/// ACS3-HMAC-SHA256 待签名的请求
+pub(crate) struct Acs3Request<'a> {
+    pub method: &'a str,
+    pub uri: &'a str,
+    pub query: &'a BTreeMap<&'a str, &'a str>,
+    /// 参与签名的请求头（host、x-acs-*、content-type），名称须为小写
+    pub headers: &'a BTreeMap<&'a str, String>,
+    /// 请求体的 SHA256 十六进制摘要，与 x-acs-content-sha256 一致
+    pub payload_hash: &'a str,
+}
+
+impl Acs3Request<'_> {
+    fn canonical_request(&self) -> String {
+        let canonical_headers: String = self
+            .headers
+            .iter()
+            .map(|(k, v)| format!("{}:{}\n", k, v.trim()))
+            .collect();
+        format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            self.method,
+            self.uri,
+            canonical_query(self.query),
+            canonical_headers,
+            self.signed_headers(),
+            self.payload_hash
+        )
+    }
+
+    fn signed_headers(&self) -> String {
+        self.headers.keys().copied().collect::<Vec<_>>().join(";")
+    }
+
+    /// 计算签名，返回 Authorization 请求头的值
+    pub fn authorization(&self, access_key_id: &str, secret: &str) -> String {
+        let string_to_sign = format!(
+            "ACS3-HMAC-SHA256\n{}",
+            sha256_hex(self.canonical_request().as_bytes())
+        );
+        format!(
+            "ACS3-HMAC-SHA256 Credential={},SignedHeaders={},Signature={}",
+            access_key_id,
+            self.signed_headers(),
+            hmac_sha256_hex(secret.as_bytes(), string_to_sign.as_bytes())
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 阿里云《RPC 风格签名机制》文档示例
+    #[test]
+    fn rpc_v1_documented_example() {
+        let params = BTreeMap::from([
+            ("AccessKeyId", "testid"),
+            ("Action", "DescribeRegions"),
+            ("Format", "XML"),
+            ("SignatureMethod", "HMAC-SHA1"),
+            ("SignatureNonce", "3ee8c1b8-83d3-44af-a94f-4e0ad82fd6cf"),
+            ("SignatureVersion", "1.0"),
+            ("Timestamp", "2016-02-23T12:46:24Z"),
+            ("Version", "2014-05-26"),
+        ]);
+        assert_eq!(sign_rpc_v1("GET", &params, "testsecret"), "OLeaidS1JvxuMvnyHOwuJ+uX5qY=");
+    }
+
+    // 短信服务《HTTP 协议及签名》文档示例，含中文签名与 JSON 参数
+    #[test]
+    fn rpc_v1_documented_sms_example() {
+        let params = BTreeMap::from([
+            ("AccessKeyId", "testId"),
+            ("Action", "SendSms"),
+            ("Format", "XML"),
+            ("OutId", "123"),
+            ("PhoneNumbers", "15300000001"),
+            ("RegionId", "cn-hangzhou"),
+            ("SignName", "阿里云短信测试专用"),
+            ("SignatureMethod", "HMAC-SHA1"),
+            ("SignatureNonce", "45e25e9b-0a6f-4070-8c85-2956eda1b466"),
+            ("SignatureVersion", "1.0"),
+            ("TemplateCode", "SMS_71390007"),
+            ("TemplateParam", "{\"customer\":\"test\"}"),
+            ("Timestamp", "2017-07-12T02:42:19Z"),
+            ("Version", "2017-05-25"),
+        ]);
+        assert_eq!(sign_rpc_v1("GET", &params, "testSecret"), "zJDF+Lrzhj/ThnlvIToysFRq6t4=");
+    }
+
+    // 阿里云《V3 版本请求体&签名机制》文档示例
+    #[test]
+    fn acs3_documented_example() {
+        let query = BTreeMap::from([
+            ("ImageId", "win2019_1809_x64_dtc_zh-cn_40G_alibase_20230811.vhd"),
+            ("RegionId", "cn-shanghai"),
+        ]);
+        let payload_hash = sha256_hex(b"");
+        let headers = BTreeMap::from([
+            ("host", "ecs.cn-shanghai.aliyuncs.com".to_string()),
+            ("x-acs-action", "RunInstances".to_string()),
+            ("x-acs-content-sha256", payload_hash.clone()),
+            ("x-acs-date", "2023-10-26T10:22:32Z".to_string()),
+            ("x-acs-signature-nonce", "3156853299f313e23d1673dc12e1703d".to_string()),
+            ("x-acs-version", "2014-05-26".to_string()),
+        ]);
+        let request = Acs3Request {
+            method: "POST",
+            uri: "/",
+            query: &query,
+            headers: &headers,
+            payload_hash: &payload_hash,
+        };
+
+        assert_eq!(
+            sha256_hex(request.canonical_request().as_bytes()),
+            "7ea06492da5221eba5297e897ce16e55f964061054b7695beedaac1145b1e259"
+        );
+        assert_eq!(
+            request.authorization("YourAccessKeyId", "YourAccessKeySecret"),
+            "ACS3-HMAC-SHA256 Credential=YourAccessKeyId,\
+             SignedHeaders=host;x-acs-action;x-acs-content-sha256;x-acs-date;x-acs-signature-nonce;x-acs-version,\
+             Signature=06563a9e1b43f5dfe96b81484da74bceab24a1d853912eee15083a6f0f3283c0"
+        );
+    }
+}
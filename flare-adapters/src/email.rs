@@ -1,37 +1,300 @@
+use std::time::Duration;
+
 use async_trait::async_trait;
-use lettre::message::{Mailbox, Message};
+use base64::Engine;
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment, Mailbox, Message, MultiPart, MultiPartBuilder, SinglePart};
+use lettre::transport::smtp::authentication::{Credentials, Mechanism};
+use lettre::transport::smtp::PoolConfig;
 use lettre::transport::smtp::AsyncSmtpTransport;
-use lettre::AsyncTransport;
-use flare_common::{EmailConfig, FlareResult};
+use lettre::{AsyncTransport, Tokio1Executor};
+use serde::{Deserialize, Deserializer};
+use flare_common::{EmailConfig, FlareError, FlareResult, SmtpAuthMechanism, SmtpTlsMode};
 use flare_core::Sender;
 use flare_core::Notification;
 
+use crate::local_file;
+
+/// 邮件内容：Notification.body 为该结构的 JSON，纯文本视为 text。
+/// Notification.to 为收件人，多个以 `,` 或 `;` 分隔，可带显示名如 `张三 <zhangsan@example.com>`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EmailContent {
+    /// 纯文本正文，同时提供 html 时作为不支持 HTML 的客户端的备选
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub html: Option<String>,
+    #[serde(default)]
+    pub attachments: Vec<EmailAttachment>,
+    /// 抄送，数组或以 `,` / `;` 分隔的字符串，下同
+    #[serde(default, deserialize_with = "address_list")]
+    pub cc: Vec<String>,
+    /// 密送，不出现在邮件头中
+    #[serde(default, deserialize_with = "address_list")]
+    pub bcc: Vec<String>,
+    #[serde(default, deserialize_with = "address_list")]
+    pub reply_to: Vec<String>,
+}
+
+/// 附件内容由 base64 或 path（Worker 所在机器上 `EMAIL_ATTACHMENT_DIR` 目录内的文件）提供
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EmailAttachment {
+    pub filename: String,
+    /// 未指定时按扩展名推断
+    #[serde(default)]
+    pub content_type: Option<String>,
+    #[serde(default)]
+    pub base64: Option<String>,
+    #[serde(default)]
+    pub path: Option<String>,
+    /// 设置后作为内联资源，HTML 中以 `cid:{content_id}` 引用
+    #[serde(default)]
+    pub content_id: Option<String>,
+}
+
+impl EmailContent {
+    pub fn parse(body: &str) -> Self {
+        match serde_json::from_str::<EmailContent>(body) {
+            Ok(content)
+                if content.text.is_some()
+                    || content.html.is_some()
+                    || !content.attachments.is_empty()
+                    || !content.cc.is_empty()
+                    || !content.bcc.is_empty()
+                    || !content.reply_to.is_empty() =>
+            {
+                content
+            }
+            _ => EmailContent { text: Some(body.to_string()), ..Default::default() },
+        }
+    }
+}
+
+impl EmailAttachment {
+    async fn load(&self, attachment_dir: Option<&str>) -> FlareResult<SinglePart> {
+        let bytes = match (&self.base64, &self.path) {
+            (Some(data), _) => base64::engine::general_purpose::STANDARD
+                .decode(data)
+                .map_err(|e| FlareError::Config(format!("附件 {} 的 base64 解析失败: {}", self.filename, e)))?,
+            (None, Some(path)) => local_file::read_under(attachment_dir, path, "EMAIL_ATTACHMENT_DIR").await?,
+            (None, None) => return Err(FlareError::Config(format!("附件 {} 缺少 base64 或 path", self.filename))),
+        };
+        let content_type = self.content_type.as_deref().unwrap_or_else(|| guess_content_type(&self.filename));
+        let content_type = ContentType::parse(content_type)
+            .map_err(|e| FlareError::Config(format!("附件 {} 的 content_type 无效: {}", self.filename, e)))?;
+
+        Ok(match &self.content_id {
+            Some(cid) => Attachment::new_inline(cid.clone()).body(bytes, content_type),
+            None => Attachment::new(self.filename.clone()).body(bytes, content_type),
+        })
+    }
+}
+
+fn address_list<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum List {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match List::deserialize(deserializer)? {
+        List::One(list) => split_addresses(&list),
+        List::Many(list) => list,
+    })
+}
+
+/// 按 `,` 或 `;` 拆分地址列表，忽略引号内及尖括号内的分隔符，如 `"张, 三" <a@b.com>`
+pub fn split_addresses(list: &str) -> Vec<String> {
+    let mut addresses = Vec::new();
+    let mut current = String::new();
+    let (mut quoted, mut angle) = (false, false);
+    for c in list.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            '<' if !quoted => angle = true,
+            '>' if !quoted => angle = false,
+            ',' | ';' if !quoted && !angle => {
+                addresses.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    addresses.push(current);
+    addresses.into_iter().map(|a| a.trim().to_string()).filter(|a| !a.is_empty()).collect()
+}
+
+/// 逐个校验地址，出错时指明是哪个地址
+fn parse_mailboxes(addresses: &[String]) -> FlareResult<Vec<Mailbox>> {
+    addresses.iter().map(|address| parse_mailbox(address)).collect()
+}
+
+fn parse_mailbox(address: &str) -> FlareResult<Mailbox> {
+    address
+        .parse::<Mailbox>()
+        .map_err(|source| FlareError::InvalidAddress { address: address.to_string(), source })
+}
+
+/// 按扩展名推断常见附件类型
+fn guess_content_type(filename: &str) -> &'static str {
+    let ext = filename.rsplit_once('.').map(|(_, ext)| ext.to_ascii_lowercase()).unwrap_or_default();
+    match ext.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        "txt" => "text/plain; charset=utf-8",
+        "csv" => "text/csv; charset=utf-8",
+        "html" | "htm" => "text/html; charset=utf-8",
+        "json" => "application/json",
+        "zip" => "application/zip",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        _ => "application/octet-stream",
+    }
+}
+
+/// MIME 中的一个部分
+enum Body {
+    Single(SinglePart),
+    Multi(MultiPart),
+}
+
+/// 将 `parts` 按顺序组装为 `kind` 类型的 multipart，`parts` 不能为空
+fn assemble(kind: MultiPartBuilder, parts: Vec<Body>) -> MultiPart {
+    let mut parts = parts.into_iter();
+    let mut multipart = match parts.next().expect("multipart 至少包含一个部分") {
+        Body::Single(part) => kind.singlepart(part),
+        Body::Multi(part) => kind.multipart(part),
+    };
+    for part in parts {
+        multipart = match part {
+            Body::Single(part) => multipart.singlepart(part),
+            Body::Multi(part) => multipart.multipart(part),
+        };
+    }
+    multipart
+}
+
+/// 组装 MIME 结构，只在需要时嵌套：
+/// mixed（普通附件）> alternative（text 与 html）> related（html 与内联资源）
+async fn build_message(notification: &Notification, attachment_dir: Option<&str>) -> FlareResult<Message> {
+    let content = EmailContent::parse(&notification.body);
+    let to = parse_mailboxes(&split_addresses(&notification.to))?;
+    let cc = parse_mailboxes(&content.cc)?;
+    let bcc = parse_mailboxes(&content.bcc)?;
+    if to.is_empty() && cc.is_empty() && bcc.is_empty() {
+        return Err(FlareError::Config("邮件缺少收件人".into()));
+    }
+
+    let mut builder = Message::builder().from(parse_mailbox(&notification.from)?).subject(&notification.subject);
+    for mailbox in to {
+        builder = builder.to(mailbox);
+    }
+    for mailbox in cc {
+        builder = builder.cc(mailbox);
+    }
+    for mailbox in bcc {
+        builder = builder.bcc(mailbox);
+    }
+    for mailbox in parse_mailboxes(&content.reply_to)? {
+        builder = builder.reply_to(mailbox);
+    }
+
+    let mut inline = Vec::new();
+    let mut attachments = Vec::new();
+    for attachment in &content.attachments {
+        let part = Body::Single(attachment.load(attachment_dir).await?);
+        match attachment.content_id {
+            Some(_) => inline.push(part),
+            None => attachments.push(part),
+        }
+    }
+
+    let html = match content.html {
+        Some(html) if inline.is_empty() => Some(Body::Single(SinglePart::html(html))),
+        Some(html) => {
+            let mut parts = vec![Body::Single(SinglePart::html(html))];
+            parts.extend(inline);
+            Some(Body::Multi(assemble(MultiPart::related(), parts)))
+        }
+        None if !inline.is_empty() => return Err(FlareError::Config("内联附件需要 html 正文".into())),
+        None => None,
+    };
+    let text = content.text.map(|text| Body::Single(SinglePart::plain(text)));
+    let body = match (text, html) {
+        (Some(text), Some(html)) => Some(Body::Multi(assemble(MultiPart::alternative(), vec![text, html]))),
+        (text, html) => text.or(html),
+    };
+    let body = match attachments.is_empty() {
+        true => body,
+        false => {
+            let mut parts: Vec<Body> = body.into_iter().collect();
+            parts.extend(attachments);
+            Some(Body::Multi(assemble(MultiPart::mixed(), parts)))
+        }
+    };
+
+    match body {
+        Some(Body::Single(part)) => Ok(builder.singlepart(part)?),
+        Some(Body::Multi(part)) => Ok(builder.multipart(part)?),
+        None => Err(FlareError::Config("邮件缺少正文或附件".into())),
+    }
+}
+
+/// SMTP 邮件发送器，连接按 EmailConfig 配置的加密方式建立并在连接池中复用
 pub struct EmailSender {
-    mailer: AsyncSmtpTransport<lettre::Tokio1Executor>,
+    mailer: AsyncSmtpTransport<Tokio1Executor>,
+    attachment_dir: Option<String>,
 }
 
 impl EmailSender {
-    pub fn new(config: &EmailConfig) -> Self {
-        let mailer = AsyncSmtpTransport::<lettre::Tokio1Executor>::relay(&config.smtp_server)
-            .unwrap()
-            .credentials(lettre::transport::smtp::authentication::Credentials::new(
-                config.smtp_user.clone(),
-                config.smtp_pass.clone(),
-            ))
-            .build();
+    /// 需在 Tokio 运行时中调用，连接池会启动后台任务回收空闲连接
+    pub fn new(config: &EmailConfig) -> FlareResult<Self> {
+        let server = config.smtp_server.trim();
+        if server.is_empty() {
+            return Err(FlareError::Config("SMTP_SERVER 不能为空".into()));
+        }
+        if tokio::runtime::Handle::try_current().is_err() {
+            return Err(FlareError::Config("SMTP 发送器需在 Tokio 运行时中创建".into()));
+        }
+        let builder = match config.smtp_tls {
+            SmtpTlsMode::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(server),
+            SmtpTlsMode::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(server),
+            SmtpTlsMode::None => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(server)),
+        }
+        .map_err(|e| FlareError::Config(format!("SMTP_SERVER 无效 {}: {}", server, e)))?;
+
+        let mut builder = builder
+            .port(config.smtp_port)
+            .timeout(Some(Duration::from_secs(config.smtp_timeout_secs)))
+            .pool_config(
+                PoolConfig::new()
+                    .max_size(config.smtp_pool_size)
+                    .idle_timeout(Duration::from_secs(config.smtp_pool_idle_timeout_secs)),
+            );
+        // 未配置账号时不认证，用于内网中继
+        if let (Some(user), Some(pass)) = (&config.smtp_user, &config.smtp_pass) {
+            builder = builder.credentials(Credentials::new(user.clone(), pass.clone()));
+        }
+        if let Some(mechanism) = config.smtp_auth_mechanism {
+            builder = builder.authentication(vec![match mechanism {
+                SmtpAuthMechanism::Plain => Mechanism::Plain,
+                SmtpAuthMechanism::Login => Mechanism::Login,
+                SmtpAuthMechanism::Xoauth2 => Mechanism::Xoauth2,
+            }]);
+        }
 
-        Self { mailer }
+        Ok(Self { mailer: builder.build(), attachment_dir: config.attachment_dir.clone() })
     }
 }
 
 #[async_trait]
 impl Sender for EmailSender {
     async fn send(&self, notification: &Notification) -> FlareResult<()> {
-        let email = Message::builder()
-            .from(notification.from.parse::<Mailbox>()?)
-            .to(notification.to.parse::<Mailbox>()?)
-            .subject(&notification.subject)
-            .body(notification.body.clone())?;
+        let email = build_message(notification, self.attachment_dir.as_deref()).await?;
 
         self.mailer.send(email).await?;
         Ok(())
@@ -41,18 +304,22 @@ impl Sender for EmailSender {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::{Arc, Mutex};
+
     use flare_common::ChannelType;
+    use serde_json::json;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 
     use super::*;
 
     #[tokio::test]
     async fn test_send_email() {
         let email_cfg = EmailConfig::from_env().expect("加载邮件配置失败");
-        let email_sender = EmailSender::new(&email_cfg);
+        let email_sender = EmailSender::new(&email_cfg).unwrap();
 
 
         let notification = Notification {
-            from: email_cfg.smtp_user.clone(),
+            from: email_cfg.sender().expect("缺少发件人").to_string(),
             to: "fangbaichun@beemwork.com".into(),
             subject: std::env::var("EMAIL_SUBJECT").unwrap_or_else(|_| "测试邮件".into()),
             body: std::env::var("EMAIL_BODY").unwrap_or_else(|_| "这是一封测试邮件".into()),
@@ -62,4 +329,247 @@ mod tests {
         email_sender.send(&notification).await.unwrap();
         println!("邮件发送完成！");
     }
-}
\ No newline at end of file
+
+    fn notification(body: String) -> Notification {
+        Notification {
+            from: "Flare <noreply@example.com>".into(),
+            to: "user@example.com".into(),
+            subject: "订单已发货".into(),
+            body,
+            channel: ChannelType::Email,
+        }
+    }
+
+    async fn formatted(body: String) -> String {
+        let message = build_message(&notification(body), None).await.unwrap();
+        String::from_utf8(message.formatted()).unwrap()
+    }
+
+    /// 按出现顺序返回各部分的 Content-Type（不含参数）
+    fn content_types(raw: &str) -> Vec<String> {
+        raw.lines()
+            .filter_map(|line| line.strip_prefix("Content-Type: "))
+            .map(|v| v.split(';').next().unwrap().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn plain_text_body_stays_single_part() {
+        let raw = formatted("您的订单已发货".into()).await;
+        assert_eq!(content_types(&raw), ["text/plain"]);
+    }
+
+    #[tokio::test]
+    async fn html_with_text_fallback_inline_image_and_attachment() {
+        let dir = std::env::temp_dir().join(format!("flare-email-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("orders.csv"), "order_id,status\n42,shipped\n").unwrap();
+        let body = json!({
+            "text": "您的订单 42 已发货",
+            "html": "<p>您的订单 <b>42</b> 已发货</p><img src=\"cid:logo\">",
+            "attachments": [
+                { "filename": "logo.png", "base64": "iVBORw0KGgo=", "content_id": "logo" },
+                { "filename": "orders.csv", "path": "orders.csv" }
+            ]
+        });
+
+        let message = build_message(&notification(body.to_string()), dir.to_str()).await.unwrap();
+        let raw = String::from_utf8(message.formatted()).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(
+            content_types(&raw),
+            ["multipart/mixed", "multipart/alternative", "text/plain", "multipart/related", "text/html", "image/png", "text/csv"]
+        );
+        assert!(raw.contains("Content-ID: <logo>"));
+        assert!(raw.contains("Content-Disposition: inline"));
+        assert!(raw.contains("Content-Disposition: attachment; filename=\"orders.csv\""));
+    }
+
+    #[tokio::test]
+    async fn html_only_without_attachments() {
+        let raw = formatted(json!({ "html": "<p>hi</p>" }).to_string()).await;
+        assert_eq!(content_types(&raw), ["text/html"]);
+    }
+
+    /// 极简 SMTP 服务：记录收到的每一行（含邮件内容），认证一律通过，返回监听端口
+    async fn start_smtp_server() -> (u16, Arc<Mutex<Vec<String>>>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let log = received.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let log = log.clone();
+                tokio::spawn(async move {
+                    let (read, mut write) = stream.into_split();
+                    let mut lines = BufReader::new(read).lines();
+                    write.write_all(b"220 localhost ESMTP\r\n").await.unwrap();
+                    let mut in_data = false;
+                    while let Ok(Some(line)) = lines.next_line().await {
+                        log.lock().unwrap().push(line.clone());
+                        let reply = if in_data {
+                            if line != "." {
+                                continue;
+                            }
+                            in_data = false;
+                            "250 2.0.0 queued\r\n"
+                        } else {
+                            match line.split(' ').next().unwrap_or_default().to_ascii_uppercase().as_str() {
+                                "EHLO" => "250-localhost\r\n250-AUTH PLAIN LOGIN\r\n250 8BITMIME\r\n",
+                                "AUTH" => "235 2.7.0 Authentication successful\r\n",
+                                "DATA" => {
+                                    in_data = true;
+                                    "354 End data with <CR><LF>.<CR><LF>\r\n"
+                                }
+                                "QUIT" => "221 2.0.0 Bye\r\n",
+                                _ => "250 2.0.0 OK\r\n",
+                            }
+                        };
+                        if write.write_all(reply.as_bytes()).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        (port, received)
+    }
+
+    fn relay_config(port: u16) -> EmailConfig {
+        EmailConfig {
+            smtp_server: "127.0.0.1".into(),
+            smtp_port: port,
+            smtp_tls: SmtpTlsMode::None,
+            smtp_user: None,
+            smtp_pass: None,
+            smtp_auth_mechanism: None,
+            smtp_from: Some("noreply@example.com".into()),
+            smtp_pool_size: 2,
+            smtp_pool_idle_timeout_secs: 60,
+            smtp_timeout_secs: 5,
+            attachment_dir: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn sends_through_no_auth_relay_on_configured_port() {
+        let (port, received) = start_smtp_server().await;
+        let sender = EmailSender::new(&relay_config(port)).unwrap();
+
+        sender.send(&notification("您的订单已发货".into())).await.unwrap();
+        sender.send(&notification("第二封复用连接".into())).await.unwrap();
+
+        let received = received.lock().unwrap();
+        assert!(received.iter().any(|l| l == "MAIL FROM:<noreply@example.com>"));
+        assert_eq!(received.iter().filter(|l| *l == "RCPT TO:<user@example.com>").count(), 2);
+        assert!(!received.iter().any(|l| l.starts_with("AUTH")));
+    }
+
+    #[tokio::test]
+    async fn authenticates_with_configured_mechanism() {
+        let (port, received) = start_smtp_server().await;
+        let cfg = EmailConfig {
+            smtp_user: Some("flare".into()),
+            smtp_pass: Some("secret".into()),
+            smtp_auth_mechanism: Some(SmtpAuthMechanism::Plain),
+            ..relay_config(port)
+        };
+
+        EmailSender::new(&cfg).unwrap().send(&notification("hi".into())).await.unwrap();
+
+        let expected = format!("AUTH PLAIN {}", base64::engine::general_purpose::STANDARD.encode("\0flare\0secret"));
+        assert!(received.lock().unwrap().contains(&expected));
+    }
+
+    #[tokio::test]
+    async fn empty_server_is_a_config_error() {
+        let cfg = EmailConfig { smtp_server: " ".into(), smtp_tls: SmtpTlsMode::Tls, ..relay_config(465) };
+        assert!(matches!(EmailSender::new(&cfg), Err(FlareError::Config(_))));
+    }
+
+    #[test]
+    fn outside_runtime_is_a_config_error_not_a_panic() {
+        assert!(matches!(EmailSender::new(&relay_config(25)), Err(FlareError::Config(_))));
+    }
+
+    #[test]
+    fn splits_address_lists_outside_quotes() {
+        assert_eq!(
+            split_addresses("张三 <zhangsan@example.com>, \"Li, Si\" <lisi@example.com>; wangwu@example.com,"),
+            ["张三 <zhangsan@example.com>", "\"Li, Si\" <lisi@example.com>", "wangwu@example.com"]
+        );
+    }
+
+    #[tokio::test]
+    async fn multiple_recipients_with_cc_bcc_and_reply_to() {
+        let mut notification = notification(
+            json!({
+                "text": "周报见附件",
+                "cc": ["王五 <wangwu@example.com>", "zhaoliu@example.com"],
+                "bcc": "audit@example.com",
+                "reply_to": "Support <support@example.com>"
+            })
+            .to_string(),
+        );
+        notification.to = "张三 <zhangsan@example.com>, \"Li, Si\" <lisi@example.com>".into();
+
+        let message = build_message(&notification, None).await.unwrap();
+
+        let recipients: Vec<String> = message.envelope().to().iter().map(|a| a.to_string()).collect();
+        assert_eq!(
+            recipients,
+            ["zhangsan@example.com", "lisi@example.com", "wangwu@example.com", "zhaoliu@example.com", "audit@example.com"]
+        );
+        let raw = String::from_utf8(message.formatted()).unwrap();
+        let to = message.headers().get::<lettre::message::header::To>().unwrap();
+        let names: Vec<Option<String>> = lettre::message::Mailboxes::from(to).iter().map(|m| m.name.clone()).collect();
+        assert_eq!(names, [Some("张三".to_string()), Some("Li, Si".to_string())]);
+        assert!(raw.contains("Reply-To: Support <support@example.com>"));
+        assert!(raw.contains("Cc: "));
+        // 密送不出现在邮件头中
+        assert!(!raw.contains("audit@example.com"));
+    }
+
+    #[tokio::test]
+    async fn invalid_address_is_named_in_error() {
+        let mut notification = notification("hi".into());
+        notification.to = "ok@example.com, not-an-address".into();
+        match build_message(&notification, None).await.unwrap_err() {
+            FlareError::InvalidAddress { address, .. } => assert_eq!(address, "not-an-address"),
+            other => panic!("unexpected error: {}", other),
+        }
+
+        notification.to = " , ".into();
+        let err = build_message(&notification, None).await.unwrap_err();
+        assert!(matches!(err, FlareError::Config(_)));
+    }
+
+    #[tokio::test]
+    async fn invalid_attachments_are_config_errors() {
+        let body = json!({ "text": "hi", "attachments": [{ "filename": "a.bin", "base64": "not base64!" }] });
+        let err = build_message(&notification(body.to_string()), None).await.unwrap_err();
+        assert!(matches!(err, FlareError::Config(_)));
+
+        let body = json!({ "text": "hi", "attachments": [{ "filename": "logo.png", "base64": "AA==", "content_id": "logo" }] });
+        let err = build_message(&notification(body.to_string()), None).await.unwrap_err();
+        assert!(matches!(err, FlareError::Config(_)));
+    }
+
+    #[tokio::test]
+    async fn attachment_paths_are_limited_to_attachment_dir() {
+        let dir = std::env::temp_dir().join(format!("flare-email-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let outside = std::env::temp_dir().join(format!("flare-email-{}.env", uuid::Uuid::new_v4()));
+        std::fs::write(&outside, "SMTP_PASS=secret").unwrap();
+        let body = json!({ "text": "hi", "attachments": [{ "filename": "a.env", "path": outside.to_str().unwrap() }] });
+
+        let escaped = build_message(&notification(body.to_string()), dir.to_str()).await;
+        let unconfigured = build_message(&notification(body.to_string()), None).await;
+        std::fs::remove_dir_all(&dir).ok();
+        std::fs::remove_file(&outside).ok();
+
+        assert!(matches!(escaped, Err(FlareError::Config(_))));
+        assert!(matches!(unconfigured, Err(FlareError::Config(_))));
+    }
+}
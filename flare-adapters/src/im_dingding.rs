@@ -1,13 +1,29 @@
 use reqwest::Client;
 use serde::Deserialize;
 use serde_json::json;
-use hmac::{Hmac, Mac};
-use sha2::Sha256;
-use base64::Engine;
 
-use flare_common::{FlareError, FlareResult, DingdingConfig, DingdingMessageType};
+use flare_common::{FlareError, FlareResult, DingdingAppConfig, DingdingConfig, DingdingMessageType};
 use flare_core::{Notification, Sender};
 
+use crate::mention::Mention;
+use crate::signing;
+use crate::token_cache::TokenCache;
+
+/// access_token 不合法 / 已过期
+const TOKEN_EXPIRED_CODES: [i64; 2] = [40014, 42001];
+
+/// 系统繁忙与限流错误码，稍后重试可恢复；310000（关键词/签名/IP 校验失败）等重试无意义
+const RETRYABLE_CODES: [i64; 3] = [-1, 130101, 410100];
+
+fn dingding_error(code: i64, message: String) -> FlareError {
+    FlareError::Platform {
+        platform: "dingding",
+        code,
+        message,
+        retryable: RETRYABLE_CODES.contains(&code),
+    }
+}
+
 pub struct DingdingSender {
     client: Client,
     config: DingdingConfig,
@@ -27,6 +43,8 @@ struct DingdingIncoming<'a> {
     content: Option<serde_json::Value>,
     #[serde(default)]
     text: Option<&'a str>,
+    #[serde(default)]
+    at: Option<Mention>,
 }
 
 #[async_trait::async_trait]
@@ -36,11 +54,7 @@ impl Sender for DingdingSender {
 
         if let Some(secret) = &self.config.secret {
             let ts = chrono::Utc::now().timestamp_millis();
-            let string_to_sign = format!("{}\n{}", ts, secret);
-            let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
-                .map_err(|e| FlareError::Config(format!("dingding secret error: {}", e)))?;
-            mac.update(string_to_sign.as_bytes());
-            let sign = base64::engine::general_purpose::STANDARD.encode(mac.finalize().into_bytes());
+            let sign = signing::robot_sign(ts, secret);
             let sep = if url.contains('?') { '&' } else { '?' };
             url = format!("{}{}timestamp={}&sign={}", url, sep, ts, urlencoding::encode(&sign));
         }
@@ -48,9 +62,10 @@ impl Sender for DingdingSender {
         // 解析 body：允许直接传 text，或 content 对象
         let parsed: Result<DingdingIncoming, _> = serde_json::from_str(&notification.body);
         let body_value = match parsed {
-            Ok(incoming) => {
+            Ok(mut incoming) => {
+                let mention = incoming.at.take().unwrap_or_default();
                 let msg_type = incoming.msg_type.unwrap_or(DingdingMessageType::Text);
-                match msg_type {
+                let mut value = match msg_type {
                     DingdingMessageType::Text => {
                         let text = incoming
                             .content
@@ -92,7 +107,9 @@ impl Sender for DingdingSender {
                         let content = incoming.content.unwrap_or_else(|| json!({}));
                         json!({ "msgtype": "video", "video": content })
                     }
-                }
+                };
+                mention.apply_dingding(&mut value);
+                value
             }
             Err(_) => json!({ "msgtype": "text", "text": { "content": notification.body } }),
         };
@@ -112,14 +129,236 @@ impl Sender for DingdingSender {
         println!("响应状态: {}", status);
         println!("响应内容: {}", response_text);
         
+        // 钉钉在 HTTP 200 中通过 errcode 返回业务错误
+        if let Ok(resp) = serde_json::from_str::<DingdingApiResponse>(&response_text) {
+            resp.into_result()?;
+        }
         if !status.is_success() {
-            return Err(FlareError::String(format!("钉钉API错误 {}: {}", status, response_text)));
+            return Err(FlareError::HttpStatus { status: status.as_u16(), body: response_text });
         }
 
         Ok(())
     }
 }
 
+/// 工作通知消息体。与机器人消息字段基本一致，但卡片为 `action_card`、语音为 `voice`，
+/// 图片/文件使用 media_id，不支持 feedCard 与视频
+fn build_work_msg(body: &str) -> FlareResult<serde_json::Value> {
+    let incoming = match serde_json::from_str::<DingdingIncoming>(body) {
+        Ok(incoming) => incoming,
+        Err(_) => return Ok(json!({ "msgtype": "text", "text": { "content": body } })),
+    };
+
+    let msg_type = incoming.msg_type.unwrap_or(DingdingMessageType::Text);
+    let key = match msg_type {
+        DingdingMessageType::Text => {
+            let text = incoming
+                .content
+                .as_ref()
+                .and_then(|v| v.get("content").and_then(|x| x.as_str()))
+                .or(incoming.text)
+                .unwrap_or(body);
+            return Ok(json!({ "msgtype": "text", "text": { "content": text } }));
+        }
+        DingdingMessageType::Markdown => "markdown",
+        DingdingMessageType::Link => "link",
+        DingdingMessageType::ActionCard => "action_card",
+        DingdingMessageType::Image => "image",
+        DingdingMessageType::File => "file",
+        DingdingMessageType::Audio => "voice",
+        DingdingMessageType::FeedCard | DingdingMessageType::Video => {
+            let msg_type: &'static str = msg_type.into();
+            return Err(FlareError::Config(format!("钉钉工作通知不支持 {} 消息", msg_type)));
+        }
+    };
+
+    let content = incoming.content.unwrap_or_else(|| json!({}));
+    Ok(json!({ "msgtype": key, key: content }))
+}
+
+/// 将 `Notification.to` 解析为 userid_list / dept_id_list / to_all_user。
+///
+/// 多个接收人以 `,` 或 `|` 分隔，部门加 `dept:` 前缀，其余视为 userid，
+/// 例如 `manager001,user002,dept:1`；`@all` 表示全员。
+fn parse_work_recipients(to: &str) -> FlareResult<serde_json::Map<String, serde_json::Value>> {
+    let (mut users, mut depts, mut to_all) = (Vec::new(), Vec::new(), false);
+    for item in to.split([',', '|']).map(str::trim).filter(|s| !s.is_empty()) {
+        if item == "@all" {
+            to_all = true;
+        } else if let Some(id) = item.strip_prefix("dept:") {
+            depts.push(id.trim());
+        } else {
+            users.push(item.strip_prefix("user:").unwrap_or(item).trim());
+        }
+    }
+
+    let mut map = serde_json::Map::new();
+    if to_all {
+        map.insert("to_all_user".into(), json!(true));
+    }
+    if !users.is_empty() {
+        map.insert("userid_list".into(), json!(users.join(",")));
+    }
+    if !depts.is_empty() {
+        map.insert("dept_id_list".into(), json!(depts.join(",")));
+    }
+    if map.is_empty() {
+        return Err(FlareError::Config("钉钉工作通知缺少接收人".into()));
+    }
+    Ok(map)
+}
+
+#[derive(Debug, Deserialize)]
+struct DingdingApiResponse {
+    errcode: i64,
+    #[serde(default)]
+    errmsg: String,
+    #[serde(default)]
+    access_token: String,
+    #[serde(default)]
+    expires_in: u64,
+    #[serde(default)]
+    task_id: Option<i64>,
+    #[serde(default)]
+    send_result: Option<DingdingSendResult>,
+    #[serde(default)]
+    progress: Option<DingdingSendProgress>,
+}
+
+impl DingdingApiResponse {
+    fn into_result(self) -> FlareResult<Self> {
+        if self.errcode != 0 {
+            return Err(dingding_error(self.errcode, self.errmsg));
+        }
+        Ok(self)
+    }
+}
+
+/// 工作通知的发送结果
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct DingdingSendResult {
+    /// 无效的 userid
+    pub invalid_user_id_list: Vec<String>,
+    /// 因发送消息超过上限而被流控的 userid
+    pub forbidden_user_id_list: Vec<String>,
+    /// 发送失败的 userid
+    pub failed_user_id_list: Vec<String>,
+    /// 已读的 userid
+    pub read_user_id_list: Vec<String>,
+    /// 未读的 userid
+    pub unread_user_id_list: Vec<String>,
+    /// 无效的部门 ID
+    pub invalid_dept_id_list: Vec<i64>,
+}
+
+/// 工作通知的发送进度
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct DingdingSendProgress {
+    /// 取值 0~100，表示处理的百分比
+    pub progress_in_percent: i64,
+    /// 0：未开始，1：处理中，2：处理完毕
+    pub status: i64,
+}
+
+/// 钉钉企业内部应用工作通知发送器，可发给指定员工、部门或全员
+pub struct DingdingWorkNoticeSender {
+    client: Client,
+    config: DingdingAppConfig,
+    token: TokenCache,
+}
+
+impl DingdingWorkNoticeSender {
+    pub fn new(config: DingdingAppConfig) -> Self {
+        Self {
+            client: Client::new(),
+            config,
+            token: TokenCache::default(),
+        }
+    }
+
+    async fn access_token(&self) -> FlareResult<String> {
+        self.token
+            .get_or_fetch(|| async {
+                let url = format!("{}/gettoken", self.config.api_base);
+                let resp: DingdingApiResponse = self.client
+                    .get(&url)
+                    .query(&[("appkey", &self.config.app_key), ("appsecret", &self.config.app_secret)])
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await?;
+                let resp = resp.into_result()?;
+                Ok((resp.access_token, resp.expires_in))
+            })
+            .await
+    }
+
+    /// 调用 topapi 接口，令牌过期时刷新后重试一次
+    async fn call(&self, path: &str, body: &serde_json::Value) -> FlareResult<DingdingApiResponse> {
+        let url = format!("{}{}", self.config.api_base, path);
+        let mut retried = false;
+        loop {
+            let token = self.access_token().await?;
+            let resp: DingdingApiResponse = self.client
+                .post(&url)
+                .query(&[("access_token", token)])
+                .json(body)
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+
+            if TOKEN_EXPIRED_CODES.contains(&resp.errcode) && !retried {
+                self.token.invalidate().await;
+                retried = true;
+                continue;
+            }
+            return resp.into_result();
+        }
+    }
+
+    /// 异步发送工作通知，返回用于查询结果的 task_id
+    pub async fn send_work_notice(&self, notification: &Notification) -> FlareResult<i64> {
+        let mut body = serde_json::Map::new();
+        body.insert("agent_id".into(), json!(self.config.agent_id));
+        body.extend(parse_work_recipients(&notification.to)?);
+        body.insert("msg".into(), build_work_msg(&notification.body)?);
+
+        let resp = self
+            .call("/topapi/message/corpconversation/asyncsend_v2", &serde_json::Value::Object(body))
+            .await?;
+        resp.task_id
+            .ok_or_else(|| FlareError::String("钉钉工作通知响应缺少 task_id".into()))
+    }
+
+    /// 查询工作通知的发送结果
+    pub async fn get_send_result(&self, task_id: i64) -> FlareResult<DingdingSendResult> {
+        let body = json!({ "agent_id": self.config.agent_id, "task_id": task_id });
+        let resp = self.call("/topapi/message/corpconversation/getsendresult", &body).await?;
+        Ok(resp.send_result.unwrap_or_default())
+    }
+
+    /// 查询工作通知的发送进度
+    pub async fn get_send_progress(&self, task_id: i64) -> FlareResult<DingdingSendProgress> {
+        let body = json!({ "agent_id": self.config.agent_id, "task_id": task_id });
+        let resp = self.call("/topapi/message/corpconversation/getsendprogress", &body).await?;
+        Ok(resp.progress.unwrap_or_default())
+    }
+}
+
+#[async_trait::async_trait]
+impl Sender for DingdingWorkNoticeSender {
+    async fn send(&self, notification: &Notification) -> FlareResult<()> {
+        let task_id = self.send_work_notice(notification).await?;
+        tracing::info!("钉钉工作通知已提交 task_id={}", task_id);
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -307,6 +546,253 @@ mod tests {
             Err(e) => println!("❌ 文件消息发送失败: {}", e),
         }
     }
-}
 
+    mod mention {
+        use super::*;
+        use crate::mock_server::MockServer;
 
+        async fn send_via_mock(body: serde_json::Value) -> serde_json::Value {
+            let server = MockServer::start().await;
+            server.respond_json("/robot/send", json!({ "errcode": 0, "errmsg": "ok" }));
+            let sender = DingdingSender::new(DingdingConfig { webhook: server.url("/robot/send"), secret: None });
+            let n = Notification {
+                from: String::new(),
+                to: String::new(),
+                subject: String::new(),
+                body: body.to_string(),
+                channel: flare_common::ChannelType::ImDingding,
+            };
+            sender.send(&n).await.unwrap();
+            server.requests_to("/robot/send")[0].json()
+        }
+
+        #[tokio::test]
+        async fn text_renders_at_block() {
+            let sent = send_via_mock(json!({
+                "msg_type": "text",
+                "content": { "content": "服务告警" },
+                "at": { "mobiles": ["13800000000"], "user_ids": ["manager01"] }
+            })).await;
+
+            assert_eq!(sent, json!({
+                "msgtype": "text",
+                "text": { "content": "服务告警 @13800000000 @manager01" },
+                "at": { "atMobiles": ["13800000000"], "atUserIds": ["manager01"], "isAtAll": false }
+            }));
+        }
+
+        #[tokio::test]
+        async fn markdown_accepts_native_field_names() {
+            let sent = send_via_mock(json!({
+                "msg_type": "markdown",
+                "content": { "title": "告警", "text": "### CPU 90% @13800000000" },
+                "at": { "atMobiles": ["13800000000"], "isAtAll": true }
+            })).await;
+
+            // 正文已包含的 @ 不重复追加
+            assert_eq!(sent["markdown"]["text"], "### CPU 90% @13800000000");
+            assert_eq!(sent["at"]["isAtAll"], true);
+            assert_eq!(sent["at"]["atMobiles"], json!(["13800000000"]));
+        }
+
+        #[tokio::test]
+        async fn mention_that_is_a_prefix_of_another_is_still_appended() {
+            let sent = send_via_mock(json!({
+                "msg_type": "text",
+                "content": { "content": "值班 @13800000001" },
+                "at": { "mobiles": ["1380000000", "13800000001"] }
+            })).await;
+
+            assert_eq!(sent["text"]["content"], "值班 @13800000001 @1380000000");
+        }
+
+        #[tokio::test]
+        async fn action_card_appends_mentions() {
+            let sent = send_via_mock(json!({
+                "msg_type": "action_card",
+                "content": { "title": "发布", "text": "请确认", "singleTitle": "查看", "singleURL": "https://example.com" },
+                "at": { "user_ids": ["ops01"] }
+            })).await;
+
+            assert_eq!(sent["actionCard"]["text"], "请确认 @ops01");
+            assert_eq!(sent["at"]["atUserIds"], json!(["ops01"]));
+        }
+
+        #[tokio::test]
+        async fn no_mention_leaves_body_untouched() {
+            let sent = send_via_mock(json!({ "msg_type": "text", "content": { "content": "hi" } })).await;
+            assert_eq!(sent, json!({ "msgtype": "text", "text": { "content": "hi" } }));
+        }
+    }
+
+    mod response {
+        use super::*;
+        use crate::mock_server::MockServer;
+
+        async fn send_with_response(body: serde_json::Value) -> FlareResult<()> {
+            let server = MockServer::start().await;
+            server.respond_json("/robot/send", body);
+            let sender = DingdingSender::new(DingdingConfig { webhook: server.url("/robot/send"), secret: None });
+            let n = Notification {
+                from: String::new(),
+                to: String::new(),
+                subject: String::new(),
+                body: "hello".into(),
+                channel: flare_common::ChannelType::ImDingding,
+            };
+            sender.send(&n).await
+        }
+
+        #[tokio::test]
+        async fn keyword_mismatch_is_not_retryable() {
+            let err = send_with_response(json!({ "errcode": 310000, "errmsg": "keywords not in content" }))
+                .await
+                .unwrap_err();
+
+            assert!(matches!(err, FlareError::Platform { platform: "dingding", code: 310000, .. }));
+            assert!(!err.is_retryable());
+        }
+
+        #[tokio::test]
+        async fn send_too_fast_is_retryable() {
+            let err = send_with_response(json!({ "errcode": 130101, "errmsg": "send too fast, exceed 20 times per minute" }))
+                .await
+                .unwrap_err();
+
+            assert!(err.is_retryable());
+        }
+    }
+
+    mod work_notice {
+        use super::*;
+        use crate::mock_server::MockServer;
+
+        const TOKEN_PATH: &str = "/gettoken";
+        const SEND_PATH: &str = "/topapi/message/corpconversation/asyncsend_v2";
+        const RESULT_PATH: &str = "/topapi/message/corpconversation/getsendresult";
+        const PROGRESS_PATH: &str = "/topapi/message/corpconversation/getsendprogress";
+
+        async fn build_work_sender() -> (MockServer, DingdingWorkNoticeSender) {
+            let server = MockServer::start().await;
+            server.respond_json(TOKEN_PATH, json!({
+                "errcode": 0, "errmsg": "ok", "access_token": "token-1", "expires_in": 7200
+            }));
+            let cfg = DingdingAppConfig {
+                api_base: server.url(""),
+                app_key: "app-key".into(),
+                app_secret: "app-secret".into(),
+                agent_id: 123456,
+            };
+            (server, DingdingWorkNoticeSender::new(cfg))
+        }
+
+        fn notification(to: &str, body: String) -> Notification {
+            Notification {
+                from: String::new(),
+                to: to.into(),
+                subject: String::new(),
+                body,
+                channel: flare_common::ChannelType::ImDingding,
+            }
+        }
+
+        #[tokio::test]
+        async fn send_work_notice_returns_task_id() {
+            let (server, sender) = build_work_sender().await;
+            server.respond_json(SEND_PATH, json!({ "errcode": 0, "errmsg": "ok", "task_id": 256271667526_i64 }));
+
+            let n = notification("user001|user002,dept:10", json!({
+                "msg_type": "action_card",
+                "content": { "title": "审批", "markdown": "请处理", "single_title": "查看", "single_url": "https://example.com" }
+            }).to_string());
+            let task_id = sender.send_work_notice(&n).await.unwrap();
+            assert_eq!(task_id, 256271667526);
+
+            let token_req = &server.requests_to(TOKEN_PATH)[0];
+            assert_eq!(token_req.query_param("appkey").as_deref(), Some("app-key"));
+            let req = &server.requests_to(SEND_PATH)[0];
+            assert_eq!(req.query_param("access_token").as_deref(), Some("token-1"));
+            assert_eq!(req.json(), json!({
+                "agent_id": 123456,
+                "userid_list": "user001,user002",
+                "dept_id_list": "10",
+                "msg": {
+                    "msgtype": "action_card",
+                    "action_card": { "title": "审批", "markdown": "请处理", "single_title": "查看", "single_url": "https://example.com" }
+                }
+            }));
+        }
+
+        #[tokio::test]
+        async fn send_to_all_user_with_plain_text() {
+            let (server, sender) = build_work_sender().await;
+            server.respond_json(SEND_PATH, json!({ "errcode": 0, "errmsg": "ok", "task_id": 1 }));
+
+            sender.send(&notification("@all", "全员通知".into())).await.unwrap();
+
+            assert_eq!(server.requests_to(SEND_PATH)[0].json(), json!({
+                "agent_id": 123456,
+                "to_all_user": true,
+                "msg": { "msgtype": "text", "text": { "content": "全员通知" } }
+            }));
+        }
+
+        #[tokio::test]
+        async fn send_refreshes_expired_token() {
+            let (server, sender) = build_work_sender().await;
+            server.respond_json(SEND_PATH, json!({ "errcode": 42001, "errmsg": "access_token expired" }));
+            server.respond_json(SEND_PATH, json!({ "errcode": 0, "errmsg": "ok", "task_id": 2 }));
+
+            let task_id = sender.send_work_notice(&notification("user001", "hi".into())).await.unwrap();
+            assert_eq!(task_id, 2);
+            assert_eq!(server.requests_to(TOKEN_PATH).len(), 2);
+        }
+
+        #[tokio::test]
+        async fn unsupported_type_and_missing_recipients_fail() {
+            let (server, sender) = build_work_sender().await;
+
+            let feed_card = json!({ "msg_type": "feed_card", "content": { "links": [] } }).to_string();
+            assert!(matches!(
+                sender.send_work_notice(&notification("user001", feed_card)).await,
+                Err(FlareError::Config(_))
+            ));
+            assert!(matches!(
+                sender.send_work_notice(&notification("", "hi".into())).await,
+                Err(FlareError::Config(_))
+            ));
+            assert!(server.requests_to(SEND_PATH).is_empty());
+        }
+
+        #[tokio::test]
+        async fn query_result_and_progress() {
+            let (server, sender) = build_work_sender().await;
+            server.respond_json(RESULT_PATH, json!({
+                "errcode": 0,
+                "send_result": {
+                    "invalid_user_id_list": ["ghost"],
+                    "read_user_id_list": ["user001"],
+                    "unread_user_id_list": ["user002"],
+                    "invalid_dept_id_list": [99]
+                }
+            }));
+            server.respond_json(PROGRESS_PATH, json!({
+                "errcode": 0,
+                "progress": { "progress_in_percent": 100, "status": 2 }
+            }));
+
+            let result = sender.get_send_result(42).await.unwrap();
+            assert_eq!(result.invalid_user_id_list, vec!["ghost"]);
+            assert_eq!(result.read_user_id_list, vec!["user001"]);
+            assert_eq!(result.unread_user_id_list, vec!["user002"]);
+            assert_eq!(result.invalid_dept_id_list, vec![99]);
+            assert!(result.failed_user_id_list.is_empty());
+
+            let progress = sender.get_send_progress(42).await.unwrap();
+            assert_eq!(progress.progress_in_percent, 100);
+            assert_eq!(progress.status, 2);
+
+            assert_eq!(server.requests_to(RESULT_PATH)[0].json(), json!({ "agent_id": 123456, "task_id": 42 }));
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+
+use lru::LruCache;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use flare_common::{DiscordConfig, DiscordMessageType, FlareError, FlareResult};
+use flare_core::{Notification, Sender};
+
+use crate::rate_limit::{send_with_rate_limit, RateLimitBucket};
+
+/// content 最大字符数
+const CONTENT_LIMIT: usize = 2000;
+
+/// 单条消息最多的 embed 数
+const EMBED_LIMIT: usize = 10;
+
+/// 最多保留限流窗口的 webhook 数
+const BUCKET_CACHE_SIZE: usize = 256;
+
+#[derive(Debug, Default, Deserialize)]
+struct DiscordIncoming {
+    #[serde(default)]
+    msg_type: Option<DiscordMessageType>,
+    /// 文本消息为 {"content": "..."}，embed 消息为单个 embed 对象或数组
+    #[serde(default)]
+    content: Option<Value>,
+    // 兼容直接传 {"text":"..."}；embed 消息中作为附带的文本
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    username: Option<String>,
+    #[serde(default)]
+    avatar_url: Option<String>,
+    /// 发到论坛/子区
+    #[serde(default)]
+    thread_id: Option<String>,
+    #[serde(default)]
+    allowed_mentions: Option<Value>,
+}
+
+/// Discord 在 4xx 响应体中返回的错误，如 {"code": 50006, "message": "Cannot send an empty message"}
+#[derive(Debug, Deserialize)]
+struct DiscordErrorResponse {
+    code: i64,
+    message: String,
+}
+
+/// 组装 webhook 请求体，返回 (请求体, thread_id)
+fn build_body(body: &str, config: &DiscordConfig) -> FlareResult<(Value, Option<String>)> {
+    let incoming = match serde_json::from_str::<DiscordIncoming>(body) {
+        Ok(incoming) => incoming,
+        Err(_) => DiscordIncoming { text: Some(body.to_string()), ..Default::default() },
+    };
+
+    let mut map = Map::new();
+    match incoming.msg_type.unwrap_or(DiscordMessageType::Text) {
+        DiscordMessageType::Text => {
+            let text = incoming
+                .content
+                .as_ref()
+                .and_then(|v| v.get("content").and_then(|x| x.as_str()))
+                .or(incoming.text.as_deref())
+                .unwrap_or(body);
+            map.insert("content".into(), json!(text));
+        }
+        DiscordMessageType::Embed => {
+            let embeds = match incoming.content {
+                Some(Value::Array(embeds)) => embeds,
+                Some(embed @ Value::Object(_)) => vec![embed],
+                _ => return Err(FlareError::Config("Discord embed 消息缺少 content".into())),
+            };
+            if embeds.len() > EMBED_LIMIT {
+                return Err(FlareError::Config(format!("Discord 单条消息最多 {} 个 embed", EMBED_LIMIT)));
+            }
+            map.insert("embeds".into(), json!(embeds));
+            if let Some(text) = incoming.text {
+                map.insert("content".into(), json!(text));
+            }
+        }
+    }
+
+    if let Some(text) = map.get("content").and_then(|v| v.as_str()) {
+        if text.chars().count() > CONTENT_LIMIT {
+            return Err(FlareError::Config(format!("Discord 消息内容超过 {} 字符", CONTENT_LIMIT)));
+        }
+    }
+    if let Some(username) = incoming.username.or_else(|| config.username.clone()) {
+        map.insert("username".into(), json!(username));
+    }
+    if let Some(avatar_url) = incoming.avatar_url.or_else(|| config.avatar_url.clone()) {
+        map.insert("avatar_url".into(), json!(avatar_url));
+    }
+    if let Some(allowed_mentions) = incoming.allowed_mentions {
+        map.insert("allowed_mentions".into(), allowed_mentions);
+    }
+
+    Ok((Value::Object(map), incoming.thread_id))
+}
+
+/// Discord webhook 发送器；Notification.to 非空时作为 webhook 地址，覆盖默认配置。
+/// 按 X-RateLimit-* 头在额度用尽时等待窗口重置，收到 429 时按 Retry-After 重试；
+/// Discord 的限流按 webhook 计算，各 webhook 的窗口分别记录
+pub struct DiscordSender {
+    client: Client,
+    config: DiscordConfig,
+    buckets: Mutex<LruCache<String, Arc<RateLimitBucket>>>,
+}
+
+impl DiscordSender {
+    pub fn new(config: DiscordConfig) -> Self {
+        Self {
+            client: Client::new(),
+            config,
+            buckets: Mutex::new(LruCache::new(NonZeroUsize::new(BUCKET_CACHE_SIZE).unwrap())),
+        }
+    }
+
+    fn bucket(&self, url: &str) -> Arc<RateLimitBucket> {
+        self.buckets.lock().unwrap().get_or_insert(url.to_string(), Default::default).clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl Sender for DiscordSender {
+    async fn send(&self, notification: &Notification) -> FlareResult<()> {
+        let url = if notification.to.is_empty() { &self.config.webhook } else { &notification.to };
+        let (body_value, thread_id) = build_body(&notification.body, &self.config)?;
+
+        // wait=true 使 Discord 在消息创建后才返回，校验错误会在响应中体现
+        let mut query = vec![("wait", "true".to_string())];
+        if let Some(thread_id) = thread_id {
+            query.push(("thread_id", thread_id));
+        }
+
+        let bucket = self.bucket(url);
+        bucket.wait().await;
+        let response = send_with_rate_limit("Discord", || {
+            self.client.post(url).query(&query).json(&body_value)
+        })
+        .await?;
+        bucket.update(response.headers()).await;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        let text = response.text().await.unwrap_or_default();
+        match serde_json::from_str::<DiscordErrorResponse>(&text) {
+            Ok(err) if status.is_client_error() => Err(FlareError::Platform {
+                platform: "discord",
+                code: err.code,
+                message: err.message,
+                retryable: false,
+            }),
+            _ => Err(FlareError::HttpStatus { status: status.as_u16(), body: text }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::{MockResponse, MockServer};
+    use flare_common::ChannelType;
+
+    const WEBHOOK_PATH: &str = "/api/webhooks/123/token";
+
+    fn response(status: u16, body: Value, headers: &[(&str, &str)]) -> MockResponse {
+        let mut response = MockResponse::json(status, body);
+        response.headers.extend(headers.iter().map(|(k, v)| (k.to_string(), v.to_string())));
+        response
+    }
+
+    async fn build_sender() -> (MockServer, DiscordSender) {
+        let server = MockServer::start().await;
+        let cfg = DiscordConfig {
+            webhook: server.url(WEBHOOK_PATH),
+            username: Some("Flare".into()),
+            avatar_url: None,
+        };
+        (server, DiscordSender::new(cfg))
+    }
+
+    fn notification(body: String) -> Notification {
+        Notification {
+            from: String::new(),
+            to: String::new(),
+            subject: String::new(),
+            body,
+            channel: ChannelType::ImDiscord,
+        }
+    }
+
+    #[tokio::test]
+    async fn send_text() {
+        let (server, sender) = build_sender().await;
+        server.respond_json(WEBHOOK_PATH, json!({ "id": "1" }));
+
+        sender.send(&notification("**部署完成**".into())).await.unwrap();
+
+        let req = &server.requests_to(WEBHOOK_PATH)[0];
+        assert_eq!(req.query_param("wait").as_deref(), Some("true"));
+        assert_eq!(req.json(), json!({ "content": "**部署完成**", "username": "Flare" }));
+    }
+
+    #[tokio::test]
+    async fn send_embed_to_thread() {
+        let (server, sender) = build_sender().await;
+        server.respond_json(WEBHOOK_PATH, json!({ "id": "2" }));
+        let embed = json!({ "title": "CPU 告警", "color": 15158332, "fields": [{ "name": "节点", "value": "node-1" }] });
+        let body = json!({ "msg_type": "embed", "content": embed, "text": "<@&42>", "thread_id": "9001" });
+
+        sender.send(&notification(body.to_string())).await.unwrap();
+
+        let req = &server.requests_to(WEBHOOK_PATH)[0];
+        assert_eq!(req.query_param("thread_id").as_deref(), Some("9001"));
+        assert_eq!(req.json(), json!({ "embeds": [embed], "content": "<@&42>", "username": "Flare" }));
+    }
+
+    #[tokio::test]
+    async fn too_many_embeds_is_rejected() {
+        let (_server, sender) = build_sender().await;
+        let body = json!({ "msg_type": "embed", "content": vec![json!({ "title": "x" }); 11] });
+
+        let err = sender.send(&notification(body.to_string())).await.unwrap_err();
+        assert!(matches!(err, FlareError::Config(_)));
+    }
+
+    #[tokio::test]
+    async fn retries_after_rate_limit() {
+        let (server, sender) = build_sender().await;
+        server.respond(WEBHOOK_PATH, response(
+            429,
+            json!({ "message": "You are being rate limited.", "retry_after": 0.01, "global": false }),
+            &[("retry-after", "0.01")],
+        ));
+        server.respond(WEBHOOK_PATH, response(200, json!({ "id": "3" }), &[]));
+
+        sender.send(&notification("hello".into())).await.unwrap();
+
+        assert_eq!(server.requests_to(WEBHOOK_PATH).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn exhausted_webhook_does_not_block_others() {
+        let (server, sender) = build_sender().await;
+        const OTHER_PATH: &str = "/api/webhooks/456/other";
+        server.respond(WEBHOOK_PATH, response(
+            200,
+            json!({ "id": "4" }),
+            &[("x-ratelimit-remaining", "0"), ("x-ratelimit-reset-after", "5")],
+        ));
+        server.respond_json(OTHER_PATH, json!({ "id": "5" }));
+
+        sender.send(&notification("first".into())).await.unwrap();
+        let other = Notification { to: server.url(OTHER_PATH), ..notification("second".into()) };
+        tokio::time::timeout(std::time::Duration::from_secs(1), sender.send(&other))
+            .await
+            .expect("其他 webhook 不应等待已用尽的限流窗口")
+            .unwrap();
+
+        assert_eq!(server.requests_to(OTHER_PATH).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn api_error_is_platform_error() {
+        let (server, sender) = build_sender().await;
+        server.respond(WEBHOOK_PATH, response(400, json!({ "code": 50006, "message": "Cannot send an empty message" }), &[]));
+
+        let err = sender.send(&notification(json!({ "text": "" }).to_string())).await.unwrap_err();
+        assert!(matches!(err, FlareError::Platform { platform: "discord", code: 50006, retryable: false, .. }));
+    }
+}
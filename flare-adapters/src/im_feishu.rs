@@ -1,15 +1,56 @@
-use flare_common::{FeishuConfig, FeishuMessageType, FlareError, FlareResult};
+use flare_common::{FeishuAppConfig, FeishuConfig, FeishuMessageType, FlareError, FlareResult};
 use flare_core::{Notification, Sender};
 use reqwest::Client;
 use serde::Deserialize;
-use hmac::{Hmac, Mac};
-use sha2::Sha256;
 use base64::Engine;
 use serde_json::json;
+use lru::LruCache;
+use sha2::Digest;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+
+use crate::local_file;
+use crate::mention::Mention;
+use crate::signing;
+use crate::token_cache::TokenCache;
+
+/// tenant_access_token 缺失 / 无效 / 过期
+const TOKEN_INVALID_CODES: [i64; 3] = [99991661, 99991663, 99991677];
+
+/// 缓存的媒体 key 数量上限，超出后淘汰最久未使用的
+const MEDIA_KEY_CACHE_SIZE: usize = 1024;
+
+/// 飞书上传接口的大小上限：图片 10 MB，文件 30 MB
+const IMAGE_UPLOAD_LIMIT: usize = 10 * 1024 * 1024;
+const FILE_UPLOAD_LIMIT: usize = 30 * 1024 * 1024;
+
+/// 限流类错误码，稍后重试可恢复；其余（签名、关键词、IP 白名单、参数等）重试无意义
+const RETRYABLE_CODES: [i64; 3] = [9499, 11232, 99991400];
+
+fn feishu_error(code: i64, message: String) -> FlareError {
+    FlareError::Platform {
+        platform: "feishu",
+        code,
+        message,
+        retryable: RETRYABLE_CODES.contains(&code),
+    }
+}
+
+/// 飞书在 HTTP 200 或 4xx 中都会返回业务错误码，先看响应体再看 HTTP 状态
+async fn read_response(response: reqwest::Response) -> FlareResult<FeishuApiResponse> {
+    let status = response.status();
+    let text = response.text().await?;
+    match serde_json::from_str::<FeishuApiResponse>(&text) {
+        Ok(resp) if resp.code != 0 || status.is_success() => Ok(resp),
+        _ => Err(FlareError::HttpStatus { status: status.as_u16(), body: text }),
+    }
+}
 
 pub struct FeishuSender {
     client: Client,
     config: FeishuConfig,
+    // 自定义机器人无法上传媒体，借助同租户的应用上传
+    uploader: Option<Arc<FeishuAppSender>>,
 }
 
 impl FeishuSender {
@@ -17,8 +58,15 @@ impl FeishuSender {
         Self {
             client: Client::new(),
             config,
+            uploader: None,
         }
     }
+
+    /// 使用飞书应用上传图片/文件，使 webhook 消息也能直接传 base64、本地路径或 URL
+    pub fn with_uploader(mut self, uploader: Arc<FeishuAppSender>) -> Self {
+        self.uploader = Some(uploader);
+        self
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -32,6 +80,54 @@ struct FeishuIncoming<'a> {
     // 兼容直接传 {"text":"..."}
     #[serde(default)]
     text: Option<&'a str>,
+    #[serde(default)]
+    at: Option<Mention>,
+}
+
+/// 解析 notification.body，返回消息类型及其 content（卡片消息为 card）。
+/// 机器人 webhook 与应用消息接口共用同一套消息体。
+fn build_content(body: &str) -> (&'static str, serde_json::Value) {
+    let parsed: Result<FeishuIncoming, _> = serde_json::from_str(body);
+    match parsed {
+        Ok(mut incoming) => {
+            let mention = incoming.at.take().unwrap_or_default();
+            let msg_type = incoming.msg_type.unwrap_or(FeishuMessageType::Text);
+            let (msg_type, mut content) = match msg_type {
+                // 文本消息：content = { text }
+                FeishuMessageType::Text => {
+                    let text = incoming
+                        .content
+                        .as_ref()
+                        .and_then(|v| v.get("text").and_then(|x| x.as_str()))
+                        .or(incoming.text)
+                        .unwrap_or(body);
+                    ("text", json!({ "text": text }))
+                }
+                // 富文本：content = { post: {...} }
+                FeishuMessageType::Post
+                // 图片：content = { image_key }
+                | FeishuMessageType::Image
+                // 文件/音频/视频/表情/分享/系统
+                | FeishuMessageType::File
+                | FeishuMessageType::Audio
+                | FeishuMessageType::Media
+                | FeishuMessageType::Sticker
+                | FeishuMessageType::ShareChat
+                | FeishuMessageType::ShareUser
+                | FeishuMessageType::System => {
+                    let content = incoming.content.unwrap_or_else(|| json!({}));
+                    (msg_type.into(), content)
+                }
+                // 卡片（交互）：使用 card 字段
+                FeishuMessageType::Interactive => {
+                    ("interactive", incoming.card.unwrap_or_else(|| json!({})))
+                }
+            };
+            mention.apply_feishu(msg_type, &mut content);
+            (msg_type, content)
+        }
+        Err(_) => ("text", json!({ "text": body })),
+    }
 }
 
 #[async_trait::async_trait]
@@ -42,76 +138,421 @@ impl Sender for FeishuSender {
         // 如果配置了 secret，需要附带签名
         if let Some(secret) = &self.config.secret {
             let ts = chrono::Utc::now().timestamp();
-            let string_to_sign = format!("{}\n{}", ts, secret);
-
-            let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
-                .map_err(|e| FlareError::Config(format!("feishu secret error: {}", e)))?;
-            mac.update(string_to_sign.as_bytes());
-            let sign = base64::engine::general_purpose::STANDARD.encode(mac.finalize().into_bytes());
+            let sign = signing::robot_sign(ts, secret);
 
             let sep = if url.contains('?') { '&' } else { '?' };
             url = format!("{}{}timestamp={}&sign=\"{}\"", url, sep, ts, sign);
         }
 
-        // 解析 notification.body 来支持多类型
-        let parsed: Result<FeishuIncoming, _> = serde_json::from_str(&notification.body);
-        let body_value = match parsed {
-            Ok(incoming) => {
-                let msg_type = incoming.msg_type.unwrap_or(FeishuMessageType::Text);
-                match msg_type {
-                    // 文本消息：content = { text }
-                    FeishuMessageType::Text => {
-                        let text = incoming
-                            .content
-                            .as_ref()
-                            .and_then(|v| v.get("text").and_then(|x| x.as_str()))
-                            .or(incoming.text)
-                            .unwrap_or(notification.body.as_str());
-                        json!({
-                            "msg_type": "text",
-                            "content": { "text": text }
-                        })
-                    }
-                    // 富文本：content = { post: {...} }
-                    FeishuMessageType::Post
-                    // 图片：content = { image_key }
-                    | FeishuMessageType::Image
-                    // 文件/音频/视频/表情/分享/系统
-                    | FeishuMessageType::File
-                    | FeishuMessageType::Audio
-                    | FeishuMessageType::Media
-                    | FeishuMessageType::Sticker
-                    | FeishuMessageType::ShareChat
-                    | FeishuMessageType::ShareUser
-                    | FeishuMessageType::System => {
-                        let content = incoming.content.unwrap_or_else(|| json!({}));
-                        let msg_type: &'static str = msg_type.into();
-                        json!({
-                            "msg_type": msg_type,
-                            "content": content
-                        })
-                    }
-                    // 卡片（交互）：使用 card 字段
-                    FeishuMessageType::Interactive => {
-                        let card = incoming.card.unwrap_or_else(|| json!({}));
-                        json!({
-                            "msg_type": "interactive",
-                            "card": card
-                        })
+        let (msg_type, mut content) = build_content(&notification.body);
+        if media_source(msg_type, &content).is_some() {
+            let uploader = self.uploader.as_ref().ok_or_else(|| {
+                FlareError::Config("上传飞书媒体需要配置飞书应用 (FEISHU_APP_ID/FEISHU_APP_SECRET)".into())
+            })?;
+            content = uploader.resolve_media(msg_type, content).await?;
+        }
+
+        let body_value = match msg_type {
+            "interactive" => json!({ "msg_type": "interactive", "card": content }),
+            _ => json!({ "msg_type": msg_type, "content": content }),
+        };
+
+        let response = self.client
+            .post(&url)
+            .json(&body_value)
+            .send()
+            .await?;
+
+        let result = read_response(response).await?;
+        if result.code != 0 {
+            return Err(feishu_error(result.code, result.msg));
+        }
+        Ok(())
+    }
+}
+
+/// 根据接收人 ID 推断 receive_id_type。
+///
+/// 支持显式前缀 `open_id:` / `user_id:` / `union_id:` / `email:` / `chat_id:`，
+/// 否则按 ID 形态推断：`ou_` 为 open_id，`on_` 为 union_id，`oc_` 为 chat_id，
+/// 含 `@` 为 email，其余视为 user_id。
+fn parse_receive_id(to: &str) -> FlareResult<(&'static str, &str)> {
+    let to = to.trim();
+    if to.is_empty() {
+        return Err(FlareError::Config("飞书应用消息缺少接收人".into()));
+    }
+
+    for id_type in ["open_id", "user_id", "union_id", "email", "chat_id"] {
+        if let Some(id) = to.strip_prefix(id_type).and_then(|rest| rest.strip_prefix(':')) {
+            return Ok((id_type, id.trim()));
+        }
+    }
+
+    let id_type = if to.starts_with("ou_") {
+        "open_id"
+    } else if to.starts_with("on_") {
+        "union_id"
+    } else if to.starts_with("oc_") {
+        "chat_id"
+    } else if to.contains('@') {
+        "email"
+    } else {
+        "user_id"
+    };
+    Ok((id_type, to))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(from = "RawFeishuResponse")]
+struct FeishuApiResponse {
+    code: i64,
+    msg: String,
+    data: Option<serde_json::Value>,
+}
+
+/// 自定义机器人 webhook 还会带旧版的 StatusCode / StatusMessage，
+/// 新旧字段可能同时出现，以 code / msg 为准
+#[derive(Deserialize)]
+struct RawFeishuResponse {
+    #[serde(default)]
+    code: Option<i64>,
+    #[serde(default)]
+    msg: Option<String>,
+    #[serde(default, rename = "StatusCode")]
+    status_code: Option<i64>,
+    #[serde(default, rename = "StatusMessage")]
+    status_message: Option<String>,
+    #[serde(default)]
+    data: Option<serde_json::Value>,
+}
+
+impl From<RawFeishuResponse> for FeishuApiResponse {
+    fn from(raw: RawFeishuResponse) -> Self {
+        Self {
+            code: raw.code.or(raw.status_code).unwrap_or_default(),
+            msg: raw.msg.or(raw.status_message).unwrap_or_default(),
+            data: raw.data,
+        }
+    }
+}
+
+/// 待上传的媒体来源：内联 base64、本地路径或 HTTP URL
+#[derive(Debug, Clone, PartialEq)]
+enum MediaSource {
+    Base64(String),
+    Path(String),
+    Url(String),
+}
+
+/// 图片/文件类消息未给出 key 而给出了 base64 / path / url 时，返回需要上传的来源
+fn media_source(msg_type: &str, content: &serde_json::Value) -> Option<MediaSource> {
+    let key_field = match msg_type {
+        "image" => "image_key",
+        "file" | "audio" | "media" => "file_key",
+        _ => return None,
+    };
+    if content.get(key_field).is_some() {
+        return None;
+    }
+
+    let field = |name: &str| content.get(name).and_then(|v| v.as_str()).map(str::to_string);
+    field("base64")
+        .map(MediaSource::Base64)
+        .or_else(|| field("path").map(MediaSource::Path))
+        .or_else(|| field("url").map(MediaSource::Url))
+}
+
+/// 按扩展名推断 im/v1/files 的 file_type
+fn file_type_for(msg_type: &str, file_name: &str) -> &'static str {
+    match msg_type {
+        "audio" => return "opus",
+        "media" => return "mp4",
+        _ => {}
+    }
+    let ext = file_name.rsplit_once('.').map(|(_, ext)| ext.to_ascii_lowercase());
+    match ext.as_deref() {
+        Some("pdf") => "pdf",
+        Some("doc" | "docx") => "doc",
+        Some("xls" | "xlsx") => "xls",
+        Some("ppt" | "pptx") => "ppt",
+        Some("mp4") => "mp4",
+        Some("opus") => "opus",
+        _ => "stream",
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct FeishuTokenResponse {
+    code: i64,
+    #[serde(default)]
+    msg: String,
+    #[serde(default)]
+    tenant_access_token: String,
+    #[serde(default)]
+    expire: u64,
+}
+
+/// 飞书应用机器人发送器，通过 tenant_access_token 向用户或群聊发消息
+pub struct FeishuAppSender {
+    client: Client,
+    config: FeishuAppConfig,
+    token: TokenCache,
+    // 内容哈希 -> image_key / file_key，相同内容不重复上传
+    media_keys: Mutex<LruCache<String, String>>,
+}
+
+impl FeishuAppSender {
+    pub fn new(config: FeishuAppConfig) -> Self {
+        Self {
+            // 媒体 URL 的白名单只能校验请求的地址，因此不跟随跳转
+            client: Client::builder()
+                .redirect(reqwest::redirect::Policy::none())
+                .build()
+                .expect("构建 HTTP 客户端失败"),
+            config,
+            token: TokenCache::default(),
+            media_keys: Mutex::new(LruCache::new(NonZeroUsize::new(MEDIA_KEY_CACHE_SIZE).unwrap())),
+        }
+    }
+
+    /// 媒体 URL 只允许 http(s) 且主机在 FEISHU_MEDIA_URL_HOSTS 白名单内，防止借此访问内网服务
+    fn check_media_url(&self, url: &str) -> FlareResult<()> {
+        let parsed = reqwest::Url::parse(url).map_err(|e| FlareError::Config(format!("飞书媒体 URL 无效 {}: {}", url, e)))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(FlareError::Config(format!("飞书媒体 URL 只支持 http(s): {}", url)));
+        }
+        let host = parsed.host_str().unwrap_or_default().to_ascii_lowercase();
+        let allowed = self
+            .config
+            .media_url_hosts
+            .iter()
+            .any(|allowed| host == *allowed || host.ends_with(&format!(".{}", allowed)));
+        if !allowed {
+            return Err(FlareError::Config(format!("飞书媒体 URL 的主机不在 FEISHU_MEDIA_URL_HOSTS 中: {}", host)));
+        }
+        Ok(())
+    }
+
+    /// 读取媒体内容；URL 的响应超过 `limit` 字节时不再继续下载
+    async fn load_media(&self, source: &MediaSource, limit: usize) -> FlareResult<(Vec<u8>, String)> {
+        match source {
+            MediaSource::Base64(data) => {
+                let bytes = base64::engine::general_purpose::STANDARD
+                    .decode(data)
+                    .map_err(|e| FlareError::Config(format!("飞书媒体 base64 解析失败: {}", e)))?;
+                Ok((bytes, "file".to_string()))
+            }
+            MediaSource::Path(path) => {
+                let bytes = local_file::read_under(self.config.media_dir.as_deref(), path, "FEISHU_MEDIA_DIR").await?;
+                let name = std::path::Path::new(path)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| "file".to_string());
+                Ok((bytes, name))
+            }
+            MediaSource::Url(url) => {
+                self.check_media_url(url)?;
+                let mut response = self.client.get(url).send().await?.error_for_status()?;
+                if response.status().is_redirection() {
+                    return Err(FlareError::Config(format!("飞书媒体 URL 不允许跳转: {}", url)));
+                }
+                let too_large =
+                    || FlareError::Config(format!("飞书媒体 URL 的内容超过 {} MB: {}", limit / 1024 / 1024, url));
+                if response.content_length().is_some_and(|len| len > limit as u64) {
+                    return Err(too_large());
+                }
+                let mut bytes = Vec::new();
+                while let Some(chunk) = response.chunk().await? {
+                    if bytes.len() + chunk.len() > limit {
+                        return Err(too_large());
                     }
-                    // 兜底：按文本处理（理论上不会到这）
+                    bytes.extend_from_slice(&chunk);
                 }
+                let name = url
+                    .split(['?', '#'])
+                    .next()
+                    .and_then(|u| u.rsplit('/').next())
+                    .filter(|n| !n.is_empty())
+                    .unwrap_or("file")
+                    .to_string();
+                Ok((bytes, name))
             }
-            Err(_) => json!({ "msg_type": "text", "content": { "text": notification.body } }),
+        }
+    }
+
+    async fn upload(
+        &self,
+        msg_type: &str,
+        bytes: Vec<u8>,
+        file_name: String,
+        duration: Option<i64>,
+    ) -> FlareResult<String> {
+        let (path, key_field) = match msg_type {
+            "image" => ("/open-apis/im/v1/images", "image_key"),
+            _ => ("/open-apis/im/v1/files", "file_key"),
         };
+        let url = format!("{}{}", self.config.api_base, path);
+
+        let mut retried = false;
+        loop {
+            let part = reqwest::multipart::Part::bytes(bytes.clone()).file_name(file_name.clone());
+            let form = match msg_type {
+                "image" => reqwest::multipart::Form::new()
+                    .text("image_type", "message")
+                    .part("image", part),
+                _ => {
+                    let form = reqwest::multipart::Form::new()
+                        .text("file_type", file_type_for(msg_type, &file_name))
+                        .text("file_name", file_name.clone());
+                    let form = match duration {
+                        Some(duration) => form.text("duration", duration.to_string()),
+                        None => form,
+                    };
+                    form.part("file", part)
+                }
+            };
+
+            let token = self.tenant_access_token().await?;
+            let response = self.client
+                .post(&url)
+                .bearer_auth(token)
+                .multipart(form)
+                .send()
+                .await?;
+            let resp = read_response(response).await?;
+
+            if TOKEN_INVALID_CODES.contains(&resp.code) && !retried {
+                self.token.invalidate().await;
+                retried = true;
+                continue;
+            }
+            if resp.code != 0 {
+                return Err(feishu_error(resp.code, format!("上传失败: {}", resp.msg)));
+            }
+            return resp
+                .data
+                .as_ref()
+                .and_then(|d| d.get(key_field))
+                .and_then(|k| k.as_str())
+                .map(str::to_string)
+                .ok_or_else(|| FlareError::String(format!("飞书上传响应缺少 {}", key_field)));
+        }
+    }
+
+    /// 将 content 中的 base64 / path / url 上传并替换为 image_key / file_key；
+    /// 已上传过的相同内容直接复用缓存的 key
+    async fn resolve_media(
+        &self,
+        msg_type: &str,
+        content: serde_json::Value,
+    ) -> FlareResult<serde_json::Value> {
+        let Some(source) = media_source(msg_type, &content) else {
+            return Ok(content);
+        };
+
+        let limit = if msg_type == "image" { IMAGE_UPLOAD_LIMIT } else { FILE_UPLOAD_LIMIT };
+        let (bytes, default_name) = self.load_media(&source, limit).await?;
+        let file_name = content
+            .get("file_name")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .unwrap_or(default_name);
+        let key_field = if msg_type == "image" { "image_key" } else { "file_key" };
+
+        let digest = sha2::Sha256::digest(&bytes)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>();
+        // 图片与文件的 key 不通用，缓存键需区分类型
+        let cache_key = format!("{}:{}", key_field, digest);
+
+        let cached = self.media_keys.lock().unwrap().get(&cache_key).cloned();
+        let media_key = match cached {
+            Some(key) => key,
+            None => {
+                let duration = content.get("duration").and_then(|v| v.as_i64());
+                let key = self.upload(msg_type, bytes, file_name, duration).await?;
+                self.media_keys.lock().unwrap().put(cache_key, key.clone());
+                key
+            }
+        };
+
+        let mut content = content;
+        if let Some(map) = content.as_object_mut() {
+            for field in ["base64", "path", "url", "file_name", "duration"] {
+                map.remove(field);
+            }
+            map.insert(key_field.into(), json!(media_key));
+        }
+        Ok(content)
+    }
 
-        self.client
+    async fn tenant_access_token(&self) -> FlareResult<String> {
+        self.token
+            .get_or_fetch(|| async {
+                let url = format!(
+                    "{}/open-apis/auth/v3/tenant_access_token/internal",
+                    self.config.api_base
+                );
+                let resp: FeishuTokenResponse = self.client
+                    .post(&url)
+                    .json(&json!({
+                        "app_id": self.config.app_id,
+                        "app_secret": self.config.app_secret,
+                    }))
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await?;
+                if resp.code != 0 {
+                    return Err(feishu_error(resp.code, format!("获取 tenant_access_token 失败: {}", resp.msg)));
+                }
+                Ok((resp.tenant_access_token, resp.expire))
+            })
+            .await
+    }
+
+    async fn post_message(
+        &self,
+        receive_id_type: &str,
+        body: &serde_json::Value,
+    ) -> FlareResult<FeishuApiResponse> {
+        let token = self.tenant_access_token().await?;
+        let url = format!("{}/open-apis/im/v1/messages", self.config.api_base);
+        let response = self.client
             .post(&url)
-            .json(&body_value)
+            .query(&[("receive_id_type", receive_id_type)])
+            .bearer_auth(token)
+            .json(body)
             .send()
-            .await?
-            .error_for_status()?;
+            .await?;
+        read_response(response).await
+    }
+}
+
+#[async_trait::async_trait]
+impl Sender for FeishuAppSender {
+    async fn send(&self, notification: &Notification) -> FlareResult<()> {
+        let (receive_id_type, receive_id) = parse_receive_id(&notification.to)?;
+        let (msg_type, content) = build_content(&notification.body);
+        let content = self.resolve_media(msg_type, content).await?;
+        // 应用消息接口的 content 为 JSON 字符串
+        let body = json!({
+            "receive_id": receive_id,
+            "msg_type": msg_type,
+            "content": content.to_string(),
+        });
 
+        let mut result = self.post_message(receive_id_type, &body).await?;
+        if TOKEN_INVALID_CODES.contains(&result.code) {
+            self.token.invalidate().await;
+            result = self.post_message(receive_id_type, &body).await?;
+        }
+
+        if result.code != 0 {
+            return Err(feishu_error(result.code, result.msg));
+        }
         Ok(())
     }
 }
@@ -286,4 +727,393 @@ mod tests {
         };
         let _ = sender.send(&n).await;
     }
-}
\ No newline at end of file
+
+    mod mention {
+        use super::*;
+        use crate::mock_server::MockServer;
+
+        async fn send_via_mock(body: serde_json::Value) -> serde_json::Value {
+            let server = MockServer::start().await;
+            server.respond_json("/hook", json!({ "code": 0, "msg": "success" }));
+            let sender = FeishuSender::new(FeishuConfig { webhook: server.url("/hook"), secret: None });
+            let n = Notification {
+                from: String::new(),
+                to: String::new(),
+                subject: String::new(),
+                body: body.to_string(),
+                channel: flare_common::ChannelType::ImFeishu,
+            };
+            sender.send(&n).await.unwrap();
+            server.requests_to("/hook")[0].json()
+        }
+
+        #[tokio::test]
+        async fn text_appends_at_tags() {
+            let sent = send_via_mock(json!({
+                "msg_type": "text",
+                "content": { "text": "服务告警" },
+                "at": { "user_ids": ["ou_1"], "all": true }
+            })).await;
+
+            assert_eq!(
+                sent["content"]["text"],
+                "服务告警 <at user_id=\"ou_1\"></at> <at user_id=\"all\">所有人</at>"
+            );
+        }
+
+        #[tokio::test]
+        async fn text_skips_existing_at_tags_by_exact_id() {
+            let sent = send_via_mock(json!({
+                "msg_type": "text",
+                "content": { "text": "请 <at user_id=\"ou_10\"></at> 处理" },
+                "at": { "user_ids": ["ou_1", "ou_10"] }
+            })).await;
+
+            assert_eq!(
+                sent["content"]["text"],
+                "请 <at user_id=\"ou_10\"></at> 处理 <at user_id=\"ou_1\"></at>"
+            );
+        }
+
+        #[tokio::test]
+        async fn post_appends_at_line() {
+            let sent = send_via_mock(json!({
+                "msg_type": "post",
+                "content": { "post": { "zh_cn": { "title": "告警", "content": [[{ "tag": "text", "text": "CPU 90%" }]] } } },
+                "at": { "user_ids": ["ou_1", "ou_2"] }
+            })).await;
+
+            assert_eq!(sent["content"]["post"]["zh_cn"]["content"], json!([
+                [{ "tag": "text", "text": "CPU 90%" }],
+                [{ "tag": "at", "user_id": "ou_1" }, { "tag": "at", "user_id": "ou_2" }]
+            ]));
+        }
+
+        #[tokio::test]
+        async fn card_appends_markdown_element() {
+            let sent = send_via_mock(json!({
+                "msg_type": "interactive",
+                "card": { "header": { "title": { "content": "告警", "tag": "plain_text" } } },
+                "at": { "user_ids": ["ou_1"], "all": true }
+            })).await;
+
+            assert_eq!(sent["card"]["elements"], json!([
+                { "tag": "markdown", "content": "<at id=ou_1></at> <at id=all></at>" }
+            ]));
+        }
+    }
+
+    mod response {
+        use super::*;
+        use crate::mock_server::{MockResponse, MockServer};
+
+        async fn send_with_response(response: MockResponse) -> FlareResult<()> {
+            let server = MockServer::start().await;
+            server.respond("/hook", response);
+            let sender = FeishuSender::new(FeishuConfig { webhook: server.url("/hook"), secret: None });
+            let n = Notification {
+                from: String::new(),
+                to: String::new(),
+                subject: String::new(),
+                body: "hello".into(),
+                channel: flare_common::ChannelType::ImFeishu,
+            };
+            sender.send(&n).await
+        }
+
+        #[tokio::test]
+        async fn legacy_and_new_success_envelope() {
+            let ok = json!({ "StatusCode": 0, "StatusMessage": "success", "code": 0, "msg": "success", "data": {} });
+            send_with_response(MockResponse::json(200, ok)).await.unwrap();
+        }
+
+        #[tokio::test]
+        async fn signature_failure_is_not_retryable() {
+            let body = json!({ "code": 19021, "msg": "sign match fail or timestamp is not within one hour from current time" });
+            let err = send_with_response(MockResponse::json(200, body)).await.unwrap_err();
+
+            assert!(matches!(err, FlareError::Platform { platform: "feishu", code: 19021, .. }));
+            assert!(!err.is_retryable());
+        }
+
+        #[tokio::test]
+        async fn frequency_limit_is_retryable() {
+            let body = json!({ "code": 11232, "msg": "frequency limited psm" });
+            let err = send_with_response(MockResponse::json(200, body)).await.unwrap_err();
+
+            assert!(matches!(err, FlareError::Platform { code: 11232, .. }));
+            assert!(err.is_retryable());
+        }
+
+        #[tokio::test]
+        async fn server_error_without_envelope_is_retryable() {
+            let response = MockResponse {
+                status: 502,
+                headers: vec![],
+                body: "Bad Gateway".into(),
+            };
+            let err = send_with_response(response).await.unwrap_err();
+
+            assert!(matches!(err, FlareError::HttpStatus { status: 502, .. }));
+            assert!(err.is_retryable());
+        }
+    }
+
+    mod app {
+        use super::*;
+        use crate::mock_server::MockServer;
+
+        const TOKEN_PATH: &str = "/open-apis/auth/v3/tenant_access_token/internal";
+        const MESSAGE_PATH: &str = "/open-apis/im/v1/messages";
+
+        async fn build_app_sender() -> (MockServer, FeishuAppSender) {
+            let server = MockServer::start().await;
+            let cfg = FeishuAppConfig {
+                api_base: server.url(""),
+                app_id: "cli_test".into(),
+                app_secret: "secret".into(),
+                media_dir: Some(std::env::temp_dir().to_string_lossy().into_owned()),
+                media_url_hosts: vec!["127.0.0.1".into()],
+            };
+            (server, FeishuAppSender::new(cfg))
+        }
+
+        fn token_response(token: &str) -> serde_json::Value {
+            json!({ "code": 0, "msg": "ok", "tenant_access_token": token, "expire": 7200 })
+        }
+
+        fn notification(to: &str, body: serde_json::Value) -> Notification {
+            Notification {
+                from: String::new(),
+                to: to.into(),
+                subject: String::new(),
+                body: body.to_string(),
+                channel: flare_common::ChannelType::ImFeishu,
+            }
+        }
+
+        #[test]
+        fn receive_id_type_inference() {
+            assert_eq!(parse_receive_id("ou_123").unwrap(), ("open_id", "ou_123"));
+            assert_eq!(parse_receive_id("on_123").unwrap(), ("union_id", "on_123"));
+            assert_eq!(parse_receive_id("oc_123").unwrap(), ("chat_id", "oc_123"));
+            assert_eq!(parse_receive_id("a@b.com").unwrap(), ("email", "a@b.com"));
+            assert_eq!(parse_receive_id("5d9bdxxx").unwrap(), ("user_id", "5d9bdxxx"));
+            assert_eq!(parse_receive_id("union_id:abc").unwrap(), ("union_id", "abc"));
+            assert!(parse_receive_id(" ").is_err());
+        }
+
+        #[tokio::test]
+        async fn send_text_to_open_id() {
+            let (server, sender) = build_app_sender().await;
+            server.respond_json(TOKEN_PATH, token_response("t-1"));
+            server.respond_json(MESSAGE_PATH, json!({ "code": 0, "msg": "success", "data": {} }));
+
+            let n = notification("ou_abc", json!({ "msg_type": "text", "content": { "text": "hi" } }));
+            sender.send(&n).await.unwrap();
+
+            assert_eq!(server.requests_to(TOKEN_PATH)[0].json(), json!({
+                "app_id": "cli_test",
+                "app_secret": "secret"
+            }));
+            let req = &server.requests_to(MESSAGE_PATH)[0];
+            assert_eq!(req.query_param("receive_id_type").as_deref(), Some("open_id"));
+            assert_eq!(req.header("authorization"), Some("Bearer t-1"));
+            assert_eq!(req.json(), json!({
+                "receive_id": "ou_abc",
+                "msg_type": "text",
+                "content": "{\"text\":\"hi\"}"
+            }));
+        }
+
+        #[tokio::test]
+        async fn send_card_to_chat_reuses_token() {
+            let (server, sender) = build_app_sender().await;
+            server.respond_json(TOKEN_PATH, token_response("t-1"));
+            server.respond_json(MESSAGE_PATH, json!({ "code": 0, "msg": "success" }));
+
+            let card = json!({ "header": { "title": { "content": "card", "tag": "plain_text" } } });
+            let n = notification("chat_id:oc_xyz", json!({ "msg_type": "interactive", "card": card }));
+            sender.send(&n).await.unwrap();
+            sender.send(&n).await.unwrap();
+
+            assert_eq!(server.requests_to(TOKEN_PATH).len(), 1);
+            let req = &server.requests_to(MESSAGE_PATH)[1];
+            assert_eq!(req.query_param("receive_id_type").as_deref(), Some("chat_id"));
+            let body = req.json();
+            assert_eq!(body["msg_type"], "interactive");
+            assert_eq!(body["content"], card.to_string());
+        }
+
+        #[tokio::test]
+        async fn send_refreshes_invalid_token() {
+            let (server, sender) = build_app_sender().await;
+            server.respond_json(TOKEN_PATH, token_response("stale"));
+            server.respond_json(TOKEN_PATH, token_response("fresh"));
+            server.respond(MESSAGE_PATH, crate::mock_server::MockResponse::json(
+                400,
+                json!({ "code": 99991663, "msg": "Invalid access token for authorization." }),
+            ));
+            server.respond_json(MESSAGE_PATH, json!({ "code": 0, "msg": "success" }));
+
+            sender.send(&notification("a@b.com", json!({ "text": "hi" }))).await.unwrap();
+
+            let sends = server.requests_to(MESSAGE_PATH);
+            assert_eq!(sends.len(), 2);
+            assert_eq!(sends[1].header("authorization"), Some("Bearer fresh"));
+        }
+
+        #[tokio::test]
+        async fn send_returns_api_error() {
+            let (server, sender) = build_app_sender().await;
+            server.respond_json(TOKEN_PATH, token_response("t-1"));
+            server.respond(MESSAGE_PATH, crate::mock_server::MockResponse::json(
+                400,
+                json!({ "code": 230002, "msg": "Bot/User can NOT be out of the chat." }),
+            ));
+
+            let err = sender
+                .send(&notification("oc_xyz", json!({ "text": "hi" })))
+                .await
+                .unwrap_err();
+            assert!(err.to_string().contains("230002"));
+        }
+
+        const IMAGE_UPLOAD_PATH: &str = "/open-apis/im/v1/images";
+        const FILE_UPLOAD_PATH: &str = "/open-apis/im/v1/files";
+
+        fn multipart_text(req: &crate::mock_server::RecordedRequest) -> String {
+            String::from_utf8_lossy(&req.body).into_owned()
+        }
+
+        #[tokio::test]
+        async fn send_image_uploads_once_per_content() {
+            let (server, sender) = build_app_sender().await;
+            server.respond_json(TOKEN_PATH, token_response("t-1"));
+            server.respond_json(IMAGE_UPLOAD_PATH, json!({ "code": 0, "msg": "success", "data": { "image_key": "img_v2_1" } }));
+            server.respond_json(MESSAGE_PATH, json!({ "code": 0, "msg": "success" }));
+
+            let data = base64::engine::general_purpose::STANDARD.encode(b"PNGDATA");
+            let n = notification("ou_abc", json!({ "msg_type": "image", "content": { "base64": data } }));
+            sender.send(&n).await.unwrap();
+            sender.send(&n).await.unwrap();
+
+            let uploads = server.requests_to(IMAGE_UPLOAD_PATH);
+            assert_eq!(uploads.len(), 1);
+            assert_eq!(uploads[0].header("authorization"), Some("Bearer t-1"));
+            let form = multipart_text(&uploads[0]);
+            assert!(form.contains("name=\"image_type\"") && form.contains("message"));
+            assert!(form.contains("PNGDATA"));
+
+            for req in server.requests_to(MESSAGE_PATH) {
+                assert_eq!(req.json()["content"], "{\"image_key\":\"img_v2_1\"}");
+            }
+        }
+
+        #[tokio::test]
+        async fn send_file_from_local_path() {
+            let (server, sender) = build_app_sender().await;
+            server.respond_json(TOKEN_PATH, token_response("t-1"));
+            server.respond_json(FILE_UPLOAD_PATH, json!({ "code": 0, "msg": "success", "data": { "file_key": "file_v2_1" } }));
+            server.respond_json(MESSAGE_PATH, json!({ "code": 0, "msg": "success" }));
+
+            let path = std::env::temp_dir().join(format!("flare-feishu-{}.pdf", uuid::Uuid::new_v4()));
+            std::fs::write(&path, b"%PDF-1.4").unwrap();
+            let n = notification("ou_abc", json!({
+                "msg_type": "file",
+                "content": { "path": path.to_string_lossy() }
+            }));
+            let result = sender.send(&n).await;
+            std::fs::remove_file(&path).ok();
+            result.unwrap();
+
+            let form = multipart_text(&server.requests_to(FILE_UPLOAD_PATH)[0]);
+            assert!(form.contains("name=\"file_type\"\r\n\r\npdf"));
+            assert!(form.contains(&format!("name=\"file_name\"\r\n\r\n{}", path.file_name().unwrap().to_string_lossy())));
+            assert_eq!(
+                server.requests_to(MESSAGE_PATH)[0].json()["content"],
+                "{\"file_key\":\"file_v2_1\"}"
+            );
+        }
+
+        #[tokio::test]
+        async fn webhook_image_from_url_uses_app_uploader() {
+            let (server, app_sender) = build_app_sender().await;
+            server.respond_json(TOKEN_PATH, token_response("t-1"));
+            server.respond_json(IMAGE_UPLOAD_PATH, json!({ "code": 0, "msg": "success", "data": { "image_key": "img_v2_2" } }));
+            server.respond("/assets/logo.png", crate::mock_server::MockResponse {
+                status: 200,
+                headers: vec![("content-type".into(), "image/png".into())],
+                body: "LOGO".into(),
+            });
+            server.respond_json("/hook", json!({ "code": 0, "msg": "success" }));
+
+            let sender = FeishuSender::new(FeishuConfig { webhook: server.url("/hook"), secret: None })
+                .with_uploader(Arc::new(app_sender));
+            let n = notification("", json!({
+                "msg_type": "image",
+                "content": { "url": server.url("/assets/logo.png") }
+            }));
+            sender.send(&n).await.unwrap();
+
+            assert!(multipart_text(&server.requests_to(IMAGE_UPLOAD_PATH)[0]).contains("LOGO"));
+            assert_eq!(server.requests_to("/hook")[0].json(), json!({
+                "msg_type": "image",
+                "content": { "image_key": "img_v2_2" }
+            }));
+        }
+
+        #[tokio::test]
+        async fn media_outside_allowed_dir_or_hosts_is_rejected() {
+            let (server, sender) = build_app_sender().await;
+            server.respond_json(TOKEN_PATH, token_response("t-1"));
+            server.respond("/internal", crate::mock_server::MockResponse {
+                status: 302,
+                headers: vec![("location".into(), "http://169.254.169.254/latest/meta-data".into())],
+                body: String::new(),
+            });
+
+            for content in [
+                json!({ "path": "/etc/passwd" }),
+                json!({ "path": "../../etc/passwd" }),
+                json!({ "url": "http://169.254.169.254/latest/meta-data" }),
+                json!({ "url": "file:///etc/passwd" }),
+                json!({ "url": server.url("/internal") }),
+            ] {
+                let n = notification("ou_abc", json!({ "msg_type": "file", "content": content }));
+                let err = sender.send(&n).await.unwrap_err();
+                assert!(matches!(err, FlareError::Config(_)), "{}: {}", content, err);
+            }
+            assert!(server.requests_to(FILE_UPLOAD_PATH).is_empty());
+            assert!(server.requests_to(MESSAGE_PATH).is_empty());
+        }
+
+        #[tokio::test]
+        async fn oversized_media_url_is_rejected() {
+            let (server, sender) = build_app_sender().await;
+            server.respond_json(TOKEN_PATH, token_response("t-1"));
+            server.respond("/assets/huge.png", crate::mock_server::MockResponse {
+                status: 200,
+                headers: vec![("content-type".into(), "image/png".into())],
+                body: "x".repeat(IMAGE_UPLOAD_LIMIT + 1),
+            });
+
+            let n = notification("ou_abc", json!({
+                "msg_type": "image",
+                "content": { "url": server.url("/assets/huge.png") }
+            }));
+            let err = sender.send(&n).await.unwrap_err();
+
+            assert!(matches!(err, FlareError::Config(_)), "{}", err);
+            assert!(server.requests_to(IMAGE_UPLOAD_PATH).is_empty());
+        }
+
+        #[tokio::test]
+        async fn webhook_media_without_uploader_fails() {
+            let sender = FeishuSender::new(FeishuConfig { webhook: "http://127.0.0.1:9/hook".into(), secret: None });
+            let n = notification("", json!({ "msg_type": "image", "content": { "base64": "UE5H" } }));
+
+            assert!(matches!(sender.send(&n).await, Err(FlareError::Config(_))));
+        }
+    }
+}
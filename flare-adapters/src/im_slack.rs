@@ -0,0 +1,302 @@
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use flare_common::{FlareError, FlareResult, SlackAppConfig, SlackConfig};
+use flare_core::{Notification, Sender};
+
+use crate::rate_limit::send_with_rate_limit;
+
+/// 限流与 Slack 侧故障，稍后重试可恢复
+const RETRYABLE_ERRORS: [&str; 5] = [
+    "ratelimited",
+    "rate_limited",
+    "internal_error",
+    "fatal_error",
+    "service_unavailable",
+];
+
+/// Slack 的错误码是字符串（如 channel_not_found），放在 message 中，code 固定为 0
+fn slack_error(error: &str, detail: Option<String>) -> FlareError {
+    FlareError::Platform {
+        platform: "slack",
+        code: 0,
+        message: match detail {
+            Some(detail) => format!("{}: {}", error, detail),
+            None => error.to_string(),
+        },
+        retryable: RETRYABLE_ERRORS.contains(&error),
+    }
+}
+
+/// 消息体：纯文本按 mrkdwn 发送；JSON 对象原样透传（text、blocks、thread_ts、
+/// reply_broadcast、unfurl_links 等）；JSON 数组视为 Block Kit blocks
+fn build_payload(body: &str) -> FlareResult<Value> {
+    let payload = match serde_json::from_str::<Value>(body) {
+        Ok(Value::Object(map)) => Value::Object(map),
+        Ok(Value::Array(blocks)) => json!({ "blocks": blocks }),
+        _ => return Ok(json!({ "text": body })),
+    };
+
+    if payload.get("text").is_none() && payload.get("blocks").is_none() {
+        return Err(FlareError::Config("Slack 消息缺少 text 或 blocks".into()));
+    }
+    Ok(payload)
+}
+
+/// Slack Incoming Webhook 发送器
+pub struct SlackSender {
+    client: Client,
+    config: SlackConfig,
+}
+
+impl SlackSender {
+    pub fn new(config: SlackConfig) -> Self {
+        Self { client: Client::new(), config }
+    }
+}
+
+#[async_trait::async_trait]
+impl Sender for SlackSender {
+    async fn send(&self, notification: &Notification) -> FlareResult<()> {
+        let payload = build_payload(&notification.body)?;
+
+        let response =
+            send_with_rate_limit("Slack", || self.client.post(&self.config.webhook).json(&payload)).await?;
+
+        // Incoming Webhook 成功时返回纯文本 ok，失败时以 4xx 返回错误码文本（如 invalid_payload）
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(FlareError::HttpStatus { status: status.as_u16(), body });
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PostMessageResponse {
+    ok: bool,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    warning: Option<String>,
+    #[serde(default)]
+    channel: Option<String>,
+    #[serde(default)]
+    ts: Option<String>,
+    #[serde(default)]
+    response_metadata: Option<ResponseMetadata>,
+}
+
+/// invalid_blocks 等错误的详细说明
+#[derive(Debug, Default, Deserialize)]
+struct ResponseMetadata {
+    #[serde(default)]
+    messages: Vec<String>,
+    #[serde(default)]
+    warnings: Vec<String>,
+}
+
+/// chat.postMessage 发送结果，ts 可作为后续回复的 thread_ts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlackPostedMessage {
+    pub channel: String,
+    pub ts: String,
+}
+
+/// Slack Bot（chat.postMessage）发送器，Notification.to 为频道 ID / 用户 ID
+pub struct SlackAppSender {
+    client: Client,
+    config: SlackAppConfig,
+}
+
+impl SlackAppSender {
+    pub fn new(config: SlackAppConfig) -> Self {
+        Self { client: Client::new(), config }
+    }
+
+    /// 发送消息到指定频道；channel 为空时使用消息体中的 channel
+    pub async fn post_message(&self, channel: &str, body: &str) -> FlareResult<SlackPostedMessage> {
+        let mut payload = build_payload(body)?;
+        if !channel.is_empty() {
+            payload["channel"] = json!(channel);
+        }
+        if payload.get("channel").and_then(|v| v.as_str()).is_none() {
+            return Err(FlareError::Config("Slack 消息缺少 channel".into()));
+        }
+
+        let url = format!("{}/chat.postMessage", self.config.api_base);
+        let response = send_with_rate_limit("Slack", || {
+            self.client
+                .post(&url)
+                .bearer_auth(&self.config.bot_token)
+                .json(&payload)
+        })
+        .await?
+        .error_for_status()?;
+
+        // Web API 在 HTTP 200 中通过 ok:false 返回业务错误
+        let result: PostMessageResponse = response.json().await?;
+        let metadata = result.response_metadata.unwrap_or_default();
+        if !result.ok {
+            let error = result.error.unwrap_or_else(|| "unknown_error".into());
+            let detail = (!metadata.messages.is_empty()).then(|| metadata.messages.join("; "));
+            return Err(slack_error(&error, detail));
+        }
+        if let Some(warning) = result.warning {
+            tracing::warn!("Slack 消息发送警告 {}: {:?}", warning, metadata.warnings);
+        }
+
+        Ok(SlackPostedMessage {
+            channel: result.channel.unwrap_or_default(),
+            ts: result.ts.unwrap_or_default(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Sender for SlackAppSender {
+    async fn send(&self, notification: &Notification) -> FlareResult<()> {
+        self.post_message(&notification.to, &notification.body).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::{MockResponse, MockServer};
+    use flare_common::ChannelType;
+
+    const WEBHOOK_PATH: &str = "/services/T000/B000/XXXX";
+    const POST_MESSAGE_PATH: &str = "/chat.postMessage";
+
+    fn text_response(status: u16, body: &str, headers: &[(&str, &str)]) -> MockResponse {
+        MockResponse {
+            status,
+            headers: headers.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            body: body.into(),
+        }
+    }
+
+    async fn build_sender() -> (MockServer, SlackSender) {
+        let server = MockServer::start().await;
+        let cfg = SlackConfig { webhook: server.url(WEBHOOK_PATH) };
+        (server, SlackSender::new(cfg))
+    }
+
+    async fn build_app_sender() -> (MockServer, SlackAppSender) {
+        let server = MockServer::start().await;
+        let cfg = SlackAppConfig {
+            api_base: server.url(""),
+            bot_token: "xoxb-test".into(),
+        };
+        (server, SlackAppSender::new(cfg))
+    }
+
+    fn notification(to: &str, body: String) -> Notification {
+        Notification {
+            from: String::new(),
+            to: to.into(),
+            subject: String::new(),
+            body,
+            channel: ChannelType::ImSlack,
+        }
+    }
+
+    #[tokio::test]
+    async fn webhook_sends_plain_text_as_mrkdwn() {
+        let (server, sender) = build_sender().await;
+        server.respond(WEBHOOK_PATH, text_response(200, "ok", &[]));
+
+        sender.send(&notification("", "*部署完成* <https://example.com|详情>".into())).await.unwrap();
+
+        let requests = server.requests_to(WEBHOOK_PATH);
+        assert_eq!(requests[0].json(), json!({ "text": "*部署完成* <https://example.com|详情>" }));
+    }
+
+    #[tokio::test]
+    async fn webhook_passes_blocks_through() {
+        let (server, sender) = build_sender().await;
+        server.respond(WEBHOOK_PATH, text_response(200, "ok", &[]));
+        let blocks = json!([{ "type": "section", "text": { "type": "mrkdwn", "text": "*告警*" } }]);
+
+        sender.send(&notification("", blocks.to_string())).await.unwrap();
+
+        assert_eq!(server.requests_to(WEBHOOK_PATH)[0].json(), json!({ "blocks": blocks }));
+    }
+
+    #[tokio::test]
+    async fn webhook_retries_after_rate_limit() {
+        let (server, sender) = build_sender().await;
+        server.respond(WEBHOOK_PATH, text_response(429, "rate_limited", &[("retry-after", "0")]));
+        server.respond(WEBHOOK_PATH, text_response(200, "ok", &[]));
+
+        sender.send(&notification("", "hello".into())).await.unwrap();
+
+        assert_eq!(server.requests_to(WEBHOOK_PATH).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn webhook_error_status_is_not_retryable() {
+        let (server, sender) = build_sender().await;
+        server.respond(WEBHOOK_PATH, text_response(400, "invalid_payload", &[]));
+
+        let err = sender.send(&notification("", "hello".into())).await.unwrap_err();
+        assert!(matches!(err, FlareError::HttpStatus { status: 400, ref body } if body == "invalid_payload"));
+        assert!(!err.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn post_message_with_thread() {
+        let (server, sender) = build_app_sender().await;
+        server.respond_json(POST_MESSAGE_PATH, json!({ "ok": true, "channel": "C123", "ts": "1700000000.000200" }));
+        let body = json!({ "text": "回复", "thread_ts": "1700000000.000100" }).to_string();
+
+        let posted = sender.post_message("C123", &body).await.unwrap();
+
+        assert_eq!(posted, SlackPostedMessage { channel: "C123".into(), ts: "1700000000.000200".into() });
+        let req = &server.requests_to(POST_MESSAGE_PATH)[0];
+        assert_eq!(req.header("authorization"), Some("Bearer xoxb-test"));
+        assert_eq!(req.json(), json!({ "channel": "C123", "text": "回复", "thread_ts": "1700000000.000100" }));
+    }
+
+    #[tokio::test]
+    async fn post_message_returns_api_error() {
+        let (server, sender) = build_app_sender().await;
+        server.respond_json(POST_MESSAGE_PATH, json!({
+            "ok": false,
+            "error": "invalid_blocks",
+            "response_metadata": { "messages": ["[ERROR] missing required field: text [json-pointer:/blocks/0/text]"] }
+        }));
+
+        let err = sender.send(&notification("C123", "[{\"type\":\"section\"}]".into())).await.unwrap_err();
+        match err {
+            FlareError::Platform { platform, message, retryable, .. } => {
+                assert_eq!(platform, "slack");
+                assert!(message.starts_with("invalid_blocks: [ERROR] missing required field"));
+                assert!(!retryable);
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn post_message_gives_up_on_long_retry_after() {
+        let (server, sender) = build_app_sender().await;
+        server.respond(POST_MESSAGE_PATH, text_response(429, "", &[("retry-after", "120")]));
+
+        let err = sender.send(&notification("C123", "hello".into())).await.unwrap_err();
+        assert!(matches!(err, FlareError::HttpStatus { status: 429, .. }));
+        assert!(err.is_retryable());
+        assert_eq!(server.requests_to(POST_MESSAGE_PATH).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn post_message_requires_channel() {
+        let (_server, sender) = build_app_sender().await;
+        let err = sender.send(&notification("", "hello".into())).await.unwrap_err();
+        assert!(matches!(err, FlareError::Config(_)));
+    }
+}
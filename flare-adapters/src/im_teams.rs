@@ -0,0 +1,254 @@
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use flare_common::{FlareError, FlareResult, TeamsConfig, TeamsMessageType};
+use flare_core::{Notification, Sender};
+
+use crate::rate_limit::send_with_rate_limit;
+
+const ADAPTIVE_CARD_SCHEMA: &str = "http://adaptivecards.io/schemas/adaptive-card.json";
+const ADAPTIVE_CARD_VERSION: &str = "1.4";
+
+#[derive(Debug, Deserialize)]
+struct TeamsIncoming {
+    #[serde(default)]
+    msg_type: Option<TeamsMessageType>,
+    #[serde(default)]
+    content: Option<Value>,
+    // 兼容直接传 {"text":"..."}
+    #[serde(default)]
+    text: Option<String>,
+}
+
+/// 补全 Adaptive Card 的 type / $schema / version
+fn adaptive_card(mut card: Value) -> Value {
+    if let Some(map) = card.as_object_mut() {
+        map.entry("type").or_insert_with(|| json!("AdaptiveCard"));
+        map.entry("$schema").or_insert_with(|| json!(ADAPTIVE_CARD_SCHEMA));
+        map.entry("version").or_insert_with(|| json!(ADAPTIVE_CARD_VERSION));
+    }
+    card
+}
+
+fn text_card(text: &str) -> Value {
+    adaptive_card(json!({ "body": [{ "type": "TextBlock", "text": text, "wrap": true }] }))
+}
+
+/// Workflows 与 Incoming Webhook 都接受以附件形式发送的 Adaptive Card
+fn card_message(card: Value) -> Value {
+    json!({
+        "type": "message",
+        "attachments": [{
+            "contentType": "application/vnd.microsoft.card.adaptive",
+            "contentUrl": null,
+            "content": card
+        }]
+    })
+}
+
+fn build_body(body: &str) -> FlareResult<Value> {
+    let incoming = match serde_json::from_str::<TeamsIncoming>(body) {
+        Ok(incoming) => incoming,
+        Err(_) => return Ok(card_message(text_card(body))),
+    };
+
+    let value = match incoming.msg_type.unwrap_or(TeamsMessageType::Text) {
+        TeamsMessageType::Text => {
+            let text = incoming
+                .content
+                .as_ref()
+                .and_then(|v| v.get("text").and_then(|x| x.as_str()))
+                .or(incoming.text.as_deref())
+                .unwrap_or(body);
+            card_message(text_card(text))
+        }
+        TeamsMessageType::AdaptiveCard => {
+            let card = incoming
+                .content
+                .ok_or_else(|| FlareError::Config("Teams Adaptive Card 消息缺少 content".into()))?;
+            card_message(adaptive_card(card))
+        }
+        // 旧版 Connector 要求 summary 或 text 至少其一
+        TeamsMessageType::MessageCard => {
+            let mut card = incoming.content.unwrap_or_else(|| json!({}));
+            if let Some(map) = card.as_object_mut() {
+                map.insert("@type".into(), json!("MessageCard"));
+                map.insert("@context".into(), json!("https://schema.org/extensions"));
+                if !map.contains_key("summary") && !map.contains_key("text") {
+                    let summary = map.get("title").cloned().unwrap_or_else(|| json!(""));
+                    map.insert("summary".into(), summary);
+                }
+            }
+            card
+        }
+    };
+
+    Ok(value)
+}
+
+/// 旧版 Incoming Webhook 投递失败时仍返回 HTTP 200，错误写在响应体中，
+/// 例如 `Webhook message delivery failed with error: Microsoft Teams endpoint returned HTTP error 429 ...`
+fn delivery_error(text: &str) -> Option<FlareError> {
+    if !text.contains("delivery failed") {
+        return None;
+    }
+    let code = text
+        .split("HTTP error ")
+        .nth(1)
+        .and_then(|rest| rest.split(|c: char| !c.is_ascii_digit()).next())
+        .and_then(|digits| digits.parse::<i64>().ok())
+        .unwrap_or(0);
+    Some(FlareError::Platform {
+        platform: "teams",
+        code,
+        message: text.to_string(),
+        retryable: code == 429 || code >= 500,
+    })
+}
+
+/// Microsoft Teams 发送器，支持 Workflows 与旧版 Incoming Webhook；
+/// Notification.to 非空时作为 webhook 地址，覆盖默认配置
+pub struct TeamsSender {
+    client: Client,
+    config: TeamsConfig,
+}
+
+impl TeamsSender {
+    pub fn new(config: TeamsConfig) -> Self {
+        Self { client: Client::new(), config }
+    }
+}
+
+#[async_trait::async_trait]
+impl Sender for TeamsSender {
+    async fn send(&self, notification: &Notification) -> FlareResult<()> {
+        let url = if notification.to.is_empty() { &self.config.webhook } else { &notification.to };
+        let body_value = build_body(&notification.body)?;
+
+        let response = send_with_rate_limit("Teams", || self.client.post(url).json(&body_value)).await?;
+
+        // Workflows 返回 202 Accepted，旧版 Incoming Webhook 返回 200 与文本 1
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        if !status.is_success() {
+            return Err(FlareError::HttpStatus { status: status.as_u16(), body: text });
+        }
+        match delivery_error(&text) {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::{MockResponse, MockServer};
+    use flare_common::ChannelType;
+
+    const WEBHOOK_PATH: &str = "/workflows/abc/triggers/manual/paths/invoke";
+
+    fn text_response(status: u16, body: &str, headers: &[(&str, &str)]) -> MockResponse {
+        MockResponse {
+            status,
+            headers: headers.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            body: body.into(),
+        }
+    }
+
+    async fn build_sender() -> (MockServer, TeamsSender) {
+        let server = MockServer::start().await;
+        let cfg = TeamsConfig { webhook: server.url(WEBHOOK_PATH) };
+        (server, TeamsSender::new(cfg))
+    }
+
+    fn notification(body: String) -> Notification {
+        Notification {
+            from: String::new(),
+            to: String::new(),
+            subject: String::new(),
+            body,
+            channel: ChannelType::ImTeams,
+        }
+    }
+
+    #[tokio::test]
+    async fn text_is_wrapped_in_adaptive_card() {
+        let (server, sender) = build_sender().await;
+        server.respond(WEBHOOK_PATH, text_response(202, "", &[]));
+
+        sender.send(&notification("部署完成".into())).await.unwrap();
+
+        let body = server.requests_to(WEBHOOK_PATH)[0].json();
+        assert_eq!(body["type"], "message");
+        assert_eq!(body["attachments"][0]["contentType"], "application/vnd.microsoft.card.adaptive");
+        assert_eq!(body["attachments"][0]["content"], json!({
+            "type": "AdaptiveCard",
+            "$schema": ADAPTIVE_CARD_SCHEMA,
+            "version": "1.4",
+            "body": [{ "type": "TextBlock", "text": "部署完成", "wrap": true }]
+        }));
+    }
+
+    #[tokio::test]
+    async fn adaptive_card_keeps_given_version() {
+        let (server, sender) = build_sender().await;
+        server.respond(WEBHOOK_PATH, text_response(202, "", &[]));
+        let card = json!({
+            "version": "1.5",
+            "body": [{ "type": "FactSet", "facts": [{ "title": "服务", "value": "order-api" }] }]
+        });
+
+        sender
+            .send(&notification(json!({ "msg_type": "adaptive_card", "content": card }).to_string()))
+            .await
+            .unwrap();
+
+        let content = &server.requests_to(WEBHOOK_PATH)[0].json()["attachments"][0]["content"];
+        assert_eq!(content["version"], "1.5");
+        assert_eq!(content["type"], "AdaptiveCard");
+        assert_eq!(content["body"][0]["facts"][0]["value"], "order-api");
+    }
+
+    #[tokio::test]
+    async fn message_card_gets_summary_from_title() {
+        let (server, sender) = build_sender().await;
+        server.respond(WEBHOOK_PATH, text_response(200, "1", &[]));
+        let body = json!({ "msg_type": "message_card", "content": { "title": "告警", "sections": [] } });
+
+        sender.send(&notification(body.to_string())).await.unwrap();
+
+        assert_eq!(server.requests_to(WEBHOOK_PATH)[0].json(), json!({
+            "@type": "MessageCard",
+            "@context": "https://schema.org/extensions",
+            "title": "告警",
+            "summary": "告警",
+            "sections": []
+        }));
+    }
+
+    #[tokio::test]
+    async fn retries_after_rate_limit() {
+        let (server, sender) = build_sender().await;
+        server.respond(WEBHOOK_PATH, text_response(429, "", &[("retry-after", "0")]));
+        server.respond(WEBHOOK_PATH, text_response(202, "", &[]));
+
+        sender.send(&notification("hello".into())).await.unwrap();
+
+        assert_eq!(server.requests_to(WEBHOOK_PATH).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn delivery_failure_in_body_is_error() {
+        let (server, sender) = build_sender().await;
+        server.respond(WEBHOOK_PATH, text_response(
+            200,
+            "Webhook message delivery failed with error: Microsoft Teams endpoint returned HTTP error 429 with ContextId tcid=0",
+            &[],
+        ));
+
+        let err = sender.send(&notification("hello".into())).await.unwrap_err();
+        assert!(matches!(err, FlareError::Platform { platform: "teams", code: 429, retryable: true, .. }));
+    }
+}
@@ -0,0 +1,394 @@
+use base64::Engine;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use flare_common::{FlareError, FlareResult, TelegramConfig, TelegramMessageType, TelegramParseMode};
+use flare_core::{render_text_template, Notification, Sender};
+
+/// MarkdownV2 中需要转义的字符
+const MARKDOWN_V2_SPECIAL: &str = "_*[]()~`>#+-=|{}.!\\";
+
+/// 按 MarkdownV2 规则转义，使文本原样显示
+pub fn escape_markdown_v2(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if MARKDOWN_V2_SPECIAL.contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// 按 HTML parse_mode 规则转义 `&`、`<`、`>`、`"`
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn escape_for(parse_mode: Option<TelegramParseMode>, text: &str) -> String {
+    match parse_mode {
+        Some(TelegramParseMode::MarkdownV2) => escape_markdown_v2(text),
+        Some(TelegramParseMode::Html) => escape_html(text),
+        None => text.to_string(),
+    }
+}
+
+fn telegram_error(code: i64, message: String) -> FlareError {
+    FlareError::Platform {
+        platform: "telegram",
+        code,
+        message,
+        // 429 为限流，5xx 为 Telegram 侧故障；400/403（会话不存在、机器人被移出群）重试无意义
+        retryable: code == 429 || code >= 500,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TelegramIncoming {
+    #[serde(default)]
+    msg_type: Option<TelegramMessageType>,
+    #[serde(default)]
+    parse_mode: Option<TelegramParseMode>,
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    caption: Option<String>,
+    /// 代入 text / caption 中 `{{name}}` 的变量，按 parse_mode 转义
+    #[serde(default)]
+    params: Option<Value>,
+    /// 图片 URL 或 file_id
+    #[serde(default)]
+    photo: Option<String>,
+    /// 文件 URL 或 file_id
+    #[serde(default)]
+    document: Option<String>,
+    /// 以 base64 提供的图片/文件内容，通过 multipart 上传
+    #[serde(default)]
+    base64: Option<String>,
+    #[serde(default)]
+    file_name: Option<String>,
+    /// 其余参数原样透传，如 disable_notification、message_thread_id、reply_markup
+    #[serde(flatten)]
+    extra: Map<String, Value>,
+}
+
+/// 待上传的文件
+struct Upload {
+    field: &'static str,
+    bytes: Vec<u8>,
+    file_name: String,
+}
+
+/// 一次 Bot API 调用：方法名、参数及可选的上传文件
+struct TelegramRequest {
+    method: &'static str,
+    params: Map<String, Value>,
+    upload: Option<Upload>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TelegramResponse {
+    ok: bool,
+    #[serde(default)]
+    result: Option<Value>,
+    #[serde(default)]
+    error_code: Option<i64>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    parameters: Option<ResponseParameters>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseParameters {
+    #[serde(default)]
+    retry_after: Option<u64>,
+}
+
+/// Telegram Bot 发送器，支持文本、图片与文件；Notification.to 为 chat_id
+pub struct TelegramSender {
+    client: Client,
+    config: TelegramConfig,
+}
+
+impl TelegramSender {
+    pub fn new(config: TelegramConfig) -> Self {
+        Self { client: Client::new(), config }
+    }
+
+    /// 纯文本按默认 parse_mode 整体转义；JSON 中的 text / caption 视为已格式化的标记，
+    /// 只有 params 代入的值会被转义
+    fn build_request(&self, chat_id: &str, body: &str) -> FlareResult<TelegramRequest> {
+        let incoming = match serde_json::from_str::<TelegramIncoming>(body) {
+            Ok(incoming) => incoming,
+            Err(_) => {
+                let mut params = Map::new();
+                params.insert("chat_id".into(), json!(chat_id));
+                params.insert("text".into(), json!(escape_for(self.config.parse_mode, body)));
+                if let Some(mode) = self.config.parse_mode {
+                    params.insert("parse_mode".into(), json!(<&'static str>::from(mode)));
+                }
+                return Ok(TelegramRequest { method: "sendMessage", params, upload: None });
+            }
+        };
+
+        let parse_mode = incoming.parse_mode.or(self.config.parse_mode);
+        let render = |text: String| match &incoming.params {
+            Some(data) => render_text_template(&text, data, move |s| escape_for(parse_mode, s)),
+            None => Ok(text),
+        };
+
+        let msg_type = incoming.msg_type.clone().unwrap_or(match (&incoming.photo, &incoming.document) {
+            (Some(_), _) => TelegramMessageType::Photo,
+            (None, Some(_)) => TelegramMessageType::Document,
+            (None, None) => TelegramMessageType::Text,
+        });
+
+        let mut params = incoming.extra.clone();
+        params.insert("chat_id".into(), json!(chat_id));
+        if let Some(mode) = parse_mode {
+            params.insert("parse_mode".into(), json!(<&'static str>::from(mode)));
+        }
+
+        let (method, field, source) = match msg_type {
+            TelegramMessageType::Text => {
+                let text = incoming
+                    .text
+                    .clone()
+                    .ok_or_else(|| FlareError::Config("Telegram 文本消息缺少 text".into()))?;
+                params.insert("text".into(), json!(render(text)?));
+                return Ok(TelegramRequest { method: "sendMessage", params, upload: None });
+            }
+            TelegramMessageType::Photo => ("sendPhoto", "photo", incoming.photo.clone()),
+            TelegramMessageType::Document => ("sendDocument", "document", incoming.document.clone()),
+        };
+
+        if let Some(caption) = incoming.caption.clone().or_else(|| incoming.text.clone()) {
+            params.insert("caption".into(), json!(render(caption)?));
+        }
+
+        let upload = match (source, &incoming.base64) {
+            (Some(source), _) => {
+                params.insert(field.into(), json!(source));
+                None
+            }
+            (None, Some(data)) => {
+                let bytes = base64::engine::general_purpose::STANDARD
+                    .decode(data)
+                    .map_err(|e| FlareError::Config(format!("Telegram 文件 base64 解析失败: {}", e)))?;
+                let file_name = incoming.file_name.clone().unwrap_or_else(|| field.to_string());
+                Some(Upload { field, bytes, file_name })
+            }
+            (None, None) => {
+                return Err(FlareError::Config(format!("Telegram {} 消息缺少 {} 或 base64", field, field)));
+            }
+        };
+
+        Ok(TelegramRequest { method, params, upload })
+    }
+
+    async fn call(&self, request: TelegramRequest) -> FlareResult<Value> {
+        let url = format!("{}/bot{}/{}", self.config.api_base, self.config.bot_token, request.method);
+        let builder = self.client.post(&url);
+        let builder = match request.upload {
+            Some(upload) => {
+                let form = request.params.into_iter().fold(reqwest::multipart::Form::new(), |form, (k, v)| {
+                    let value = match v {
+                        Value::String(s) => s,
+                        other => other.to_string(),
+                    };
+                    form.text(k, value)
+                });
+                let part = reqwest::multipart::Part::bytes(upload.bytes).file_name(upload.file_name);
+                builder.multipart(form.part(upload.field, part))
+            }
+            None => builder.json(&request.params),
+        };
+
+        // 业务错误以与 error_code 相同的 HTTP 状态码返回，响应体仍为 JSON
+        let response = builder.send().await?;
+        let status = response.status();
+        let text = response.text().await?;
+        let result: TelegramResponse = match serde_json::from_str(&text) {
+            Ok(result) => result,
+            Err(_) => return Err(FlareError::HttpStatus { status: status.as_u16(), body: text }),
+        };
+
+        if !result.ok {
+            let code = result.error_code.unwrap_or(status.as_u16() as i64);
+            let mut message = result.description.unwrap_or_default();
+            if let Some(retry_after) = result.parameters.and_then(|p| p.retry_after) {
+                message = format!("{}（retry_after {} 秒）", message, retry_after);
+            }
+            return Err(telegram_error(code, message));
+        }
+        Ok(result.result.unwrap_or(Value::Null))
+    }
+
+    /// 发送消息并返回 message_id；chat_id 为空时使用配置的默认会话
+    pub async fn send_message(&self, chat_id: &str, body: &str) -> FlareResult<i64> {
+        let chat_id = match (chat_id, &self.config.chat_id) {
+            ("", Some(default)) => default.as_str(),
+            ("", None) => return Err(FlareError::Config("Telegram 消息缺少 chat_id".into())),
+            (chat_id, _) => chat_id,
+        };
+
+        let request = self.build_request(chat_id, body)?;
+        let result = self.call(request).await?;
+        Ok(result.get("message_id").and_then(|v| v.as_i64()).unwrap_or_default())
+    }
+}
+
+#[async_trait::async_trait]
+impl Sender for TelegramSender {
+    async fn send(&self, notification: &Notification) -> FlareResult<()> {
+        self.send_message(&notification.to, &notification.body).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::{MockResponse, MockServer};
+    use flare_common::ChannelType;
+
+    const TOKEN: &str = "123456:ABC-test";
+
+    fn path(method: &str) -> String {
+        format!("/bot{}/{}", TOKEN, method)
+    }
+
+    fn ok_response(message_id: i64) -> Value {
+        json!({ "ok": true, "result": { "message_id": message_id, "chat": { "id": -1001 } } })
+    }
+
+    async fn build_sender(parse_mode: Option<TelegramParseMode>) -> (MockServer, TelegramSender) {
+        let server = MockServer::start().await;
+        let cfg = TelegramConfig {
+            api_base: server.url(""),
+            bot_token: TOKEN.into(),
+            chat_id: Some("-1001".into()),
+            parse_mode,
+        };
+        (server, TelegramSender::new(cfg))
+    }
+
+    fn notification(to: &str, body: String) -> Notification {
+        Notification {
+            from: String::new(),
+            to: to.into(),
+            subject: String::new(),
+            body,
+            channel: ChannelType::ImTelegram,
+        }
+    }
+
+    #[test]
+    fn escapes_markdown_v2_and_html() {
+        assert_eq!(escape_markdown_v2("cpu_usage > 90% (node-1.prod)!"), r"cpu\_usage \> 90% \(node\-1\.prod\)\!");
+        assert_eq!(escape_markdown_v2(r"a\b"), r"a\\b");
+        assert_eq!(escape_html(r#"<b>"A&B"</b>"#), "&lt;b&gt;&quot;A&amp;B&quot;&lt;/b&gt;");
+    }
+
+    #[tokio::test]
+    async fn plain_text_is_escaped_with_default_parse_mode() {
+        let (server, sender) = build_sender(Some(TelegramParseMode::MarkdownV2)).await;
+        server.respond_json(&path("sendMessage"), ok_response(7));
+
+        let id = sender.send_message("", "disk_usage 95.5%!").await.unwrap();
+
+        assert_eq!(id, 7);
+        assert_eq!(server.requests_to(&path("sendMessage"))[0].json(), json!({
+            "chat_id": "-1001",
+            "text": r"disk\_usage 95\.5%\!",
+            "parse_mode": "MarkdownV2"
+        }));
+    }
+
+    #[tokio::test]
+    async fn params_are_escaped_into_markup() {
+        let (server, sender) = build_sender(None).await;
+        server.respond_json(&path("sendMessage"), ok_response(8));
+        let body = json!({
+            "parse_mode": "HTML",
+            "text": "<b>{{service}}</b> 告警：{{detail}}",
+            "params": { "service": "order-api", "detail": "latency > 500ms & rising" },
+            "disable_notification": true
+        });
+
+        sender.send(&notification("-1002", body.to_string())).await.unwrap();
+
+        assert_eq!(server.requests_to(&path("sendMessage"))[0].json(), json!({
+            "chat_id": "-1002",
+            "text": "<b>order-api</b> 告警：latency &gt; 500ms &amp; rising",
+            "parse_mode": "HTML",
+            "disable_notification": true
+        }));
+    }
+
+    #[tokio::test]
+    async fn photo_by_url() {
+        let (server, sender) = build_sender(None).await;
+        server.respond_json(&path("sendPhoto"), ok_response(9));
+        let body = json!({ "photo": "https://example.com/chart.png", "caption": "QPS 曲线" });
+
+        sender.send(&notification("", body.to_string())).await.unwrap();
+
+        assert_eq!(server.requests_to(&path("sendPhoto"))[0].json(), json!({
+            "chat_id": "-1001",
+            "photo": "https://example.com/chart.png",
+            "caption": "QPS 曲线"
+        }));
+    }
+
+    #[tokio::test]
+    async fn document_upload_uses_multipart() {
+        let (server, sender) = build_sender(None).await;
+        server.respond_json(&path("sendDocument"), ok_response(10));
+        let data = base64::engine::general_purpose::STANDARD.encode(b"date,count\n2024-01-01,3\n");
+        let body = json!({ "msg_type": "document", "base64": data, "file_name": "report.csv", "caption": "日报" });
+
+        sender.send(&notification("", body.to_string())).await.unwrap();
+
+        let req = &server.requests_to(&path("sendDocument"))[0];
+        assert!(req.header("content-type").unwrap().starts_with("multipart/form-data"));
+        let text = String::from_utf8_lossy(&req.body);
+        assert!(text.contains("name=\"chat_id\"\r\n\r\n-1001"));
+        assert!(text.contains("name=\"caption\"\r\n\r\n日报"));
+        assert!(text.contains("name=\"document\"; filename=\"report.csv\""));
+        assert!(text.contains("2024-01-01,3"));
+    }
+
+    #[tokio::test]
+    async fn api_error_is_platform_error() {
+        let (server, sender) = build_sender(None).await;
+        server.respond(&path("sendMessage"), MockResponse::json(400, json!({
+            "ok": false,
+            "error_code": 400,
+            "description": "Bad Request: chat not found"
+        })));
+
+        let err = sender.send(&notification("-1", "hi".into())).await.unwrap_err();
+        assert!(matches!(err, FlareError::Platform { platform: "telegram", code: 400, retryable: false, .. }));
+    }
+
+    #[tokio::test]
+    async fn rate_limit_is_retryable() {
+        let (server, sender) = build_sender(None).await;
+        server.respond(&path("sendMessage"), MockResponse::json(429, json!({
+            "ok": false,
+            "error_code": 429,
+            "description": "Too Many Requests: retry after 5",
+            "parameters": { "retry_after": 5 }
+        })));
+
+        let err = sender.send(&notification("-1", "hi".into())).await.unwrap_err();
+        assert!(err.is_retryable());
+        assert!(err.to_string().contains("retry_after 5"));
+    }
+}
@@ -0,0 +1,557 @@
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+use base64::Engine;
+use md5::{Digest, Md5};
+
+use flare_common::{FlareError, FlareResult, WechatAppConfig, WechatConfig, WechatMessageType};
+use flare_core::{Notification, Sender};
+
+use crate::token_cache::TokenCache;
+
+/// access_token 已过期 / 不合法
+const TOKEN_EXPIRED_CODES: [i64; 2] = [42001, 40014];
+
+/// 系统繁忙与接口调用频率超限，稍后重试可恢复
+const RETRYABLE_CODES: [i64; 2] = [-1, 45009];
+
+fn wechat_error(code: i64, message: String) -> FlareError {
+    FlareError::Platform {
+        platform: "wechat",
+        code,
+        message,
+        retryable: RETRYABLE_CODES.contains(&code),
+    }
+}
+
+/// 企业微信群机器人发送器
+pub struct WechatSender {
+    client: Client,
+    config: WechatConfig,
+}
+
+impl WechatSender {
+    pub fn new(config: WechatConfig) -> Self {
+        Self { client: Client::new(), config }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct WechatIncoming {
+    #[serde(default)]
+    msg_type: Option<WechatMessageType>,
+    #[serde(default)]
+    content: Option<serde_json::Value>,
+    // 兼容直接传 {"text":"..."}
+    #[serde(default)]
+    text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WechatResponse {
+    errcode: i64,
+    #[serde(default)]
+    errmsg: String,
+    // 应用消息：无效或无权限的接收人
+    #[serde(default)]
+    invaliduser: Option<String>,
+    #[serde(default)]
+    invalidparty: Option<String>,
+    #[serde(default)]
+    invalidtag: Option<String>,
+}
+
+impl WechatResponse {
+    fn into_result(self) -> FlareResult<Self> {
+        if self.errcode != 0 {
+            return Err(wechat_error(self.errcode, self.errmsg));
+        }
+        Ok(self)
+    }
+}
+
+/// 图片消息需要 base64 内容及原始图片的 md5，未提供 md5 时自动计算
+fn build_image(content: serde_json::Value) -> FlareResult<serde_json::Value> {
+    let data = content
+        .get("base64")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| FlareError::Config("企业微信图片消息缺少 base64".into()))?;
+
+    if content.get("md5").and_then(|v| v.as_str()).is_some() {
+        return Ok(content);
+    }
+
+    let raw = base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .map_err(|e| FlareError::Config(format!("企业微信图片 base64 解析失败: {}", e)))?;
+    let md5 = Md5::digest(&raw)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+
+    Ok(json!({ "base64": data, "md5": md5 }))
+}
+
+/// 文本消息：content = { content, mentioned_list, mentioned_mobile_list }
+fn text_content(incoming: WechatIncoming, body: &str) -> serde_json::Value {
+    match incoming.content {
+        Some(content) if content.get("content").is_some() => content,
+        _ => json!({ "content": incoming.text.as_deref().unwrap_or(body) }),
+    }
+}
+
+/// 群机器人消息体
+fn build_body(body: &str) -> FlareResult<serde_json::Value> {
+    let incoming = match serde_json::from_str::<WechatIncoming>(body) {
+        Ok(incoming) => incoming,
+        Err(_) => return Ok(json!({ "msgtype": "text", "text": { "content": body } })),
+    };
+
+    let msg_type = incoming.msg_type.clone().unwrap_or(WechatMessageType::Text);
+    let value = match msg_type {
+        WechatMessageType::Text => {
+            json!({ "msgtype": "text", "text": text_content(incoming, body) })
+        }
+        WechatMessageType::Image => {
+            let image = build_image(incoming.content.unwrap_or_else(|| json!({})))?;
+            json!({ "msgtype": "image", "image": image })
+        }
+        // markdown / 图文 / 文件 / 模板卡片：content 原样放入对应字段
+        WechatMessageType::Markdown
+        | WechatMessageType::News
+        | WechatMessageType::File
+        | WechatMessageType::TemplateCard => {
+            let content = incoming.content.unwrap_or_else(|| json!({}));
+            let msg_type: &'static str = msg_type.into();
+            json!({ "msgtype": msg_type, msg_type: content })
+        }
+    };
+
+    Ok(value)
+}
+
+#[async_trait::async_trait]
+impl Sender for WechatSender {
+    async fn send(&self, notification: &Notification) -> FlareResult<()> {
+        let body_value = build_body(&notification.body)?;
+
+        let response = self.client
+            .post(&self.config.webhook)
+            .json(&body_value)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        // 企业微信在 HTTP 200 中通过 errcode 返回业务错误
+        response.json::<WechatResponse>().await?.into_result()?;
+        Ok(())
+    }
+}
+
+/// 应用消息体：图片、文件等使用 media_id，content 原样透传
+fn build_app_body(body: &str) -> serde_json::Value {
+    let incoming = match serde_json::from_str::<WechatIncoming>(body) {
+        Ok(incoming) => incoming,
+        Err(_) => return json!({ "msgtype": "text", "text": { "content": body } }),
+    };
+
+    match incoming.msg_type.clone().unwrap_or(WechatMessageType::Text) {
+        WechatMessageType::Text => json!({ "msgtype": "text", "text": text_content(incoming, body) }),
+        msg_type => {
+            let content = incoming.content.unwrap_or_else(|| json!({}));
+            let msg_type: &'static str = msg_type.into();
+            json!({ "msgtype": msg_type, msg_type: content })
+        }
+    }
+}
+
+/// 将 `Notification.to` 解析为 touser / toparty / totag。
+///
+/// 多个接收人以 `,` 或 `|` 分隔，部门和标签分别加 `party:`、`tag:` 前缀，
+/// 其余视为成员 userid，例如 `zhangsan,lisi,party:2,tag:5`；`@all` 表示全员。
+fn parse_recipients(to: &str) -> FlareResult<serde_json::Map<String, serde_json::Value>> {
+    let (mut users, mut parties, mut tags) = (Vec::new(), Vec::new(), Vec::new());
+    for item in to.split([',', '|']).map(str::trim).filter(|s| !s.is_empty()) {
+        if let Some(id) = item.strip_prefix("party:") {
+            parties.push(id.trim());
+        } else if let Some(id) = item.strip_prefix("tag:") {
+            tags.push(id.trim());
+        } else {
+            users.push(item.strip_prefix("user:").unwrap_or(item).trim());
+        }
+    }
+
+    let mut map = serde_json::Map::new();
+    for (key, ids) in [("touser", users), ("toparty", parties), ("totag", tags)] {
+        if !ids.is_empty() {
+            map.insert(key.into(), json!(ids.join("|")));
+        }
+    }
+    if map.is_empty() {
+        return Err(FlareError::Config("企业微信应用消息缺少接收人".into()));
+    }
+    Ok(map)
+}
+
+#[derive(Debug, Deserialize)]
+struct WechatTokenResponse {
+    errcode: i64,
+    #[serde(default)]
+    errmsg: String,
+    #[serde(default)]
+    access_token: String,
+    #[serde(default)]
+    expires_in: u64,
+}
+
+/// 企业微信自建应用消息发送器，可发给成员、部门或标签
+pub struct WechatAppSender {
+    client: Client,
+    config: WechatAppConfig,
+    token: TokenCache,
+}
+
+impl WechatAppSender {
+    pub fn new(config: WechatAppConfig) -> Self {
+        Self {
+            client: Client::new(),
+            config,
+            token: TokenCache::default(),
+        }
+    }
+
+    async fn access_token(&self) -> FlareResult<String> {
+        self.token
+            .get_or_fetch(|| async {
+                let url = format!("{}/cgi-bin/gettoken", self.config.api_base);
+                let resp: WechatTokenResponse = self.client
+                    .get(&url)
+                    .query(&[("corpid", &self.config.corp_id), ("corpsecret", &self.config.corp_secret)])
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await?;
+                if resp.errcode != 0 {
+                    return Err(wechat_error(resp.errcode, format!("获取 access_token 失败: {}", resp.errmsg)));
+                }
+                Ok((resp.access_token, resp.expires_in))
+            })
+            .await
+    }
+
+    async fn post_message(&self, body: &serde_json::Value) -> FlareResult<WechatResponse> {
+        let token = self.access_token().await?;
+        let url = format!("{}/cgi-bin/message/send", self.config.api_base);
+        let resp = self.client
+            .post(&url)
+            .query(&[("access_token", token)])
+            .json(body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(resp)
+    }
+}
+
+#[async_trait::async_trait]
+impl Sender for WechatAppSender {
+    async fn send(&self, notification: &Notification) -> FlareResult<()> {
+        let mut body = build_app_body(&notification.body);
+        let recipients = parse_recipients(&notification.to)?;
+        if let Some(map) = body.as_object_mut() {
+            map.extend(recipients);
+            map.insert("agentid".into(), json!(self.config.agent_id));
+        }
+
+        let mut result = self.post_message(&body).await?;
+        // 令牌过期或被提前作废时刷新后重试一次
+        if TOKEN_EXPIRED_CODES.contains(&result.errcode) {
+            self.token.invalidate().await;
+            result = self.post_message(&body).await?;
+        }
+
+        let result = result.into_result()?;
+        for (kind, ids) in [
+            ("user", &result.invaliduser),
+            ("party", &result.invalidparty),
+            ("tag", &result.invalidtag),
+        ] {
+            if let Some(ids) = ids.as_deref().filter(|s| !s.is_empty()) {
+                tracing::warn!("企业微信应用消息存在无效接收人 {}: {}", kind, ids);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::MockServer;
+
+    const WEBHOOK_PATH: &str = "/cgi-bin/webhook/send";
+
+    async fn build_sender() -> (MockServer, WechatSender) {
+        let server = MockServer::start().await;
+        server.respond_json(WEBHOOK_PATH, json!({ "errcode": 0, "errmsg": "ok" }));
+        let cfg = WechatConfig {
+            webhook: format!("{}?key=test-key", server.url(WEBHOOK_PATH)),
+        };
+        (server, WechatSender::new(cfg))
+    }
+
+    fn notification(body: String) -> Notification {
+        Notification {
+            from: String::new(),
+            to: String::new(),
+            subject: String::new(),
+            body,
+            channel: flare_common::ChannelType::ImWechat,
+        }
+    }
+
+    #[tokio::test]
+    async fn send_text() {
+        let (server, sender) = build_sender().await;
+        let n = notification(json!({
+            "msg_type": "text",
+            "content": { "content": "hello from wechat test", "mentioned_list": ["@all"] }
+        }).to_string());
+
+        sender.send(&n).await.unwrap();
+
+        let requests = server.requests_to(WEBHOOK_PATH);
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].query_param("key").as_deref(), Some("test-key"));
+        assert_eq!(requests[0].json(), json!({
+            "msgtype": "text",
+            "text": { "content": "hello from wechat test", "mentioned_list": ["@all"] }
+        }));
+    }
+
+    #[tokio::test]
+    async fn send_text_simple() {
+        let (server, sender) = build_sender().await;
+
+        sender.send(&notification("简单测试消息".to_string())).await.unwrap();
+
+        assert_eq!(server.requests_to(WEBHOOK_PATH)[0].json(), json!({
+            "msgtype": "text",
+            "text": { "content": "简单测试消息" }
+        }));
+    }
+
+    #[tokio::test]
+    async fn send_markdown() {
+        let (server, sender) = build_sender().await;
+        let n = notification(json!({
+            "msg_type": "markdown",
+            "content": { "content": "## 告警\n> <font color=\"warning\">CPU 90%</font>" }
+        }).to_string());
+
+        sender.send(&n).await.unwrap();
+
+        assert_eq!(server.requests_to(WEBHOOK_PATH)[0].json(), json!({
+            "msgtype": "markdown",
+            "markdown": { "content": "## 告警\n> <font color=\"warning\">CPU 90%</font>" }
+        }));
+    }
+
+    #[tokio::test]
+    async fn send_image_computes_md5() {
+        let (server, sender) = build_sender().await;
+        let data = base64::engine::general_purpose::STANDARD.encode(b"hello");
+        let n = notification(json!({
+            "msg_type": "image",
+            "content": { "base64": data }
+        }).to_string());
+
+        sender.send(&n).await.unwrap();
+
+        assert_eq!(server.requests_to(WEBHOOK_PATH)[0].json(), json!({
+            "msgtype": "image",
+            "image": { "base64": "aGVsbG8=", "md5": "5d41402abc4b2a76b9719d911017c592" }
+        }));
+    }
+
+    #[tokio::test]
+    async fn send_image_without_base64_fails() {
+        let (server, sender) = build_sender().await;
+        let n = notification(json!({ "msg_type": "image", "content": {} }).to_string());
+
+        assert!(matches!(sender.send(&n).await, Err(FlareError::Config(_))));
+        assert!(server.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn send_news() {
+        let (server, sender) = build_sender().await;
+        let articles = json!({
+            "articles": [{
+                "title": "发布通知",
+                "description": "v1.2.0 已上线",
+                "url": "https://example.com/release",
+                "picurl": "https://example.com/cover.png"
+            }]
+        });
+        let n = notification(json!({ "msg_type": "news", "content": articles }).to_string());
+
+        sender.send(&n).await.unwrap();
+
+        assert_eq!(
+            server.requests_to(WEBHOOK_PATH)[0].json(),
+            json!({ "msgtype": "news", "news": articles })
+        );
+    }
+
+    #[tokio::test]
+    async fn send_file() {
+        let (server, sender) = build_sender().await;
+        let n = notification(json!({
+            "msg_type": "file",
+            "content": { "media_id": "3a8asd892asd8asd" }
+        }).to_string());
+
+        sender.send(&n).await.unwrap();
+
+        assert_eq!(server.requests_to(WEBHOOK_PATH)[0].json(), json!({
+            "msgtype": "file",
+            "file": { "media_id": "3a8asd892asd8asd" }
+        }));
+    }
+
+    #[tokio::test]
+    async fn send_template_card() {
+        let (server, sender) = build_sender().await;
+        let card = json!({
+            "card_type": "text_notice",
+            "main_title": { "title": "审批提醒", "desc": "您有一条待审批" },
+            "card_action": { "type": 1, "url": "https://example.com" }
+        });
+        let n = notification(json!({ "msg_type": "template_card", "content": card }).to_string());
+
+        sender.send(&n).await.unwrap();
+
+        assert_eq!(
+            server.requests_to(WEBHOOK_PATH)[0].json(),
+            json!({ "msgtype": "template_card", "template_card": card })
+        );
+    }
+
+    #[tokio::test]
+    async fn send_returns_error_on_errcode() {
+        let server = MockServer::start().await;
+        server.respond_json(WEBHOOK_PATH, json!({ "errcode": 93000, "errmsg": "invalid webhook url" }));
+        let sender = WechatSender::new(WechatConfig { webhook: server.url(WEBHOOK_PATH) });
+
+        let err = sender.send(&notification("hi".into())).await.unwrap_err();
+        assert!(err.to_string().contains("93000"));
+    }
+
+    const TOKEN_PATH: &str = "/cgi-bin/gettoken";
+    const APP_SEND_PATH: &str = "/cgi-bin/message/send";
+
+    async fn build_app_sender() -> (MockServer, WechatAppSender) {
+        let server = MockServer::start().await;
+        let cfg = WechatAppConfig {
+            api_base: server.url(""),
+            corp_id: "corp-id".into(),
+            corp_secret: "corp-secret".into(),
+            agent_id: 1000002,
+        };
+        (server, WechatAppSender::new(cfg))
+    }
+
+    fn token_response(token: &str) -> serde_json::Value {
+        json!({ "errcode": 0, "errmsg": "ok", "access_token": token, "expires_in": 7200 })
+    }
+
+    #[tokio::test]
+    async fn app_send_maps_recipients() {
+        let (server, sender) = build_app_sender().await;
+        server.respond_json(TOKEN_PATH, token_response("token-1"));
+        server.respond_json(APP_SEND_PATH, json!({ "errcode": 0, "errmsg": "ok", "msgid": "m1" }));
+
+        let mut n = notification(json!({
+            "msg_type": "markdown",
+            "content": { "content": "**部署完成**" }
+        }).to_string());
+        n.to = "zhangsan|lisi,party:2,tag:5".into();
+        sender.send(&n).await.unwrap();
+
+        let token_req = &server.requests_to(TOKEN_PATH)[0];
+        assert_eq!(token_req.query_param("corpid").as_deref(), Some("corp-id"));
+        assert_eq!(token_req.query_param("corpsecret").as_deref(), Some("corp-secret"));
+
+        let send_req = &server.requests_to(APP_SEND_PATH)[0];
+        assert_eq!(send_req.query_param("access_token").as_deref(), Some("token-1"));
+        assert_eq!(send_req.json(), json!({
+            "touser": "zhangsan|lisi",
+            "toparty": "2",
+            "totag": "5",
+            "agentid": 1000002,
+            "msgtype": "markdown",
+            "markdown": { "content": "**部署完成**" }
+        }));
+    }
+
+    #[tokio::test]
+    async fn app_send_reuses_cached_token() {
+        let (server, sender) = build_app_sender().await;
+        server.respond_json(TOKEN_PATH, token_response("token-1"));
+        server.respond_json(APP_SEND_PATH, json!({ "errcode": 0, "errmsg": "ok" }));
+
+        let mut n = notification("hello".into());
+        n.to = "@all".into();
+        sender.send(&n).await.unwrap();
+        sender.send(&n).await.unwrap();
+
+        assert_eq!(server.requests_to(TOKEN_PATH).len(), 1);
+        assert_eq!(server.requests_to(APP_SEND_PATH).len(), 2);
+        assert_eq!(server.requests_to(APP_SEND_PATH)[1].json()["touser"], "@all");
+    }
+
+    #[tokio::test]
+    async fn app_send_refreshes_rejected_token() {
+        let (server, sender) = build_app_sender().await;
+        server.respond_json(TOKEN_PATH, token_response("stale"));
+        server.respond_json(TOKEN_PATH, token_response("fresh"));
+        server.respond_json(APP_SEND_PATH, json!({ "errcode": 42001, "errmsg": "access_token expired" }));
+        server.respond_json(APP_SEND_PATH, json!({ "errcode": 0, "errmsg": "ok" }));
+
+        let mut n = notification("hello".into());
+        n.to = "zhangsan".into();
+        sender.send(&n).await.unwrap();
+
+        let sends = server.requests_to(APP_SEND_PATH);
+        assert_eq!(server.requests_to(TOKEN_PATH).len(), 2);
+        assert_eq!(sends.len(), 2);
+        assert_eq!(sends[0].query_param("access_token").as_deref(), Some("stale"));
+        assert_eq!(sends[1].query_param("access_token").as_deref(), Some("fresh"));
+    }
+
+    #[tokio::test]
+    async fn app_send_without_recipients_fails() {
+        let (server, sender) = build_app_sender().await;
+
+        let result = sender.send(&notification("hello".into())).await;
+        assert!(matches!(result, Err(FlareError::Config(_))));
+        assert!(server.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn app_token_error_is_reported() {
+        let (server, sender) = build_app_sender().await;
+        server.respond_json(TOKEN_PATH, json!({ "errcode": 40013, "errmsg": "invalid corpid" }));
+
+        let mut n = notification("hello".into());
+        n.to = "zhangsan".into();
+        let err = sender.send(&n).await.unwrap_err();
+        assert!(err.to_string().contains("40013"));
+        assert!(server.requests_to(APP_SEND_PATH).is_empty());
+    }
+}
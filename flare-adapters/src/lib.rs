@@ -1,11 +1,56 @@
 mod email;
 mod ali_sms;
+mod tencent_sms;
+mod sms_router;
 mod im_feishu;
 mod im_dingding;
 mod im_wechat;
+mod im_slack;
+mod im_telegram;
+mod im_teams;
+mod im_discord;
+mod webhook;
+mod site_message;
+mod push;
+mod push_apns;
+mod push_fcm;
+mod push_huawei;
+mod push_honor;
+mod push_xiaomi;
+mod push_oppo;
+mod push_vivo;
+mod push_router;
+mod mention;
+mod token_cache;
+mod local_file;
+mod aliyun_sign;
+mod tencent_sign;
+mod signing;
+mod rate_limit;
+
+#[cfg(test)]
+mod mock_server;
 
 pub use email::*;
 pub use ali_sms::*;
+pub use tencent_sms::*;
+pub use sms_router::*;
 pub use im_feishu::*;
 pub use im_dingding::*;
-// pub use im_wechat::*;
+pub use im_wechat::*;
+pub use im_slack::*;
+pub use im_telegram::*;
+pub use im_teams::*;
+pub use im_discord::*;
+pub use webhook::*;
+pub use site_message::*;
+pub use push::*;
+pub use push_apns::*;
+pub use push_fcm::*;
+pub use push_huawei::*;
+pub use push_honor::*;
+pub use push_xiaomi::*;
+pub use push_oppo::*;
+pub use push_vivo::*;
+pub use push_router::*;
+pub use mention::*;
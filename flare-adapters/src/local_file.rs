@@ -0,0 +1,65 @@
+use std::path::Path;
+
+use flare_common::{FlareError, FlareResult};
+
+/// 读取消息中引用的本地文件，只允许 `root` 目录内的文件。
+///
+/// `path` 可以是相对 `root` 的路径，也可以是 `root` 内的绝对路径；解析 `..` 与符号链接后
+/// 仍须位于 `root` 内，防止消息生产方借此读取 `.env`、密钥等任意文件。
+/// 未配置 `root`（对应配置项 `setting`）时不允许读取本地文件。
+pub(crate) async fn read_under(root: Option<&str>, path: &str, setting: &str) -> FlareResult<Vec<u8>> {
+    let root = root.ok_or_else(|| FlareError::Config(format!("未配置 {}，不允许读取本地文件 {}", setting, path)))?;
+    let root = tokio::fs::canonicalize(root)
+        .await
+        .map_err(|e| FlareError::Config(format!("{} 目录 {} 无效: {}", setting, root, e)))?;
+    let resolved = tokio::fs::canonicalize(root.join(Path::new(path)))
+        .await
+        .map_err(|e| FlareError::Config(format!("无法读取文件 {}: {}", path, e)))?;
+    if !resolved.starts_with(&root) {
+        return Err(FlareError::Config(format!("文件 {} 不在 {} 目录内", path, setting)));
+    }
+    Ok(tokio::fs::read(&resolved).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_root() -> std::path::PathBuf {
+        let root = std::env::temp_dir().join(format!("flare-root-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("reports")).unwrap();
+        std::fs::write(root.join("reports/a.csv"), b"a,b").unwrap();
+        root
+    }
+
+    #[tokio::test]
+    async fn reads_relative_and_absolute_paths_inside_root() {
+        let root = temp_root();
+        let root_str = root.to_string_lossy();
+
+        let relative = read_under(Some(&root_str), "reports/a.csv", "ROOT").await;
+        let absolute = read_under(Some(&root_str), &root.join("reports/a.csv").to_string_lossy(), "ROOT").await;
+        std::fs::remove_dir_all(&root).ok();
+
+        assert_eq!(relative.unwrap(), b"a,b");
+        assert_eq!(absolute.unwrap(), b"a,b");
+    }
+
+    #[tokio::test]
+    async fn rejects_paths_outside_root() {
+        let root = temp_root();
+        let outside = std::env::temp_dir().join(format!("flare-outside-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&outside, b"secret").unwrap();
+        let root_str = root.to_string_lossy();
+
+        let escaped = read_under(Some(&root_str), &format!("../{}", outside.file_name().unwrap().to_string_lossy()), "ROOT").await;
+        let absolute = read_under(Some(&root_str), &outside.to_string_lossy(), "ROOT").await;
+        let unconfigured = read_under(None, "reports/a.csv", "ROOT").await;
+        std::fs::remove_dir_all(&root).ok();
+        std::fs::remove_file(&outside).ok();
+
+        assert!(matches!(escaped, Err(FlareError::Config(_))));
+        assert!(matches!(absolute, Err(FlareError::Config(_))));
+        assert!(matches!(unconfigured, Err(FlareError::Config(_))));
+    }
+}
@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// 机器人消息中的 @ 提醒，由各平台适配器渲染为原生格式。
+///
+/// 放在消息体的 `at` 字段中，例如
+/// `{"msg_type":"text","content":{...},"at":{"user_ids":["ou_xxx"],"mobiles":["138xxxx"]}}`；
+/// 也兼容钉钉原生的 `atMobiles` / `atUserIds` / `isAtAll` 字段名。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Mention {
+    /// 手机号（仅钉钉）
+    #[serde(default, alias = "atMobiles")]
+    pub mobiles: Vec<String>,
+    /// 用户 ID：钉钉为 userid，飞书为 open_id / user_id
+    #[serde(default, alias = "atUserIds")]
+    pub user_ids: Vec<String>,
+    /// @所有人
+    #[serde(default, alias = "isAtAll")]
+    pub all: bool,
+}
+
+impl Mention {
+    pub fn is_empty(&self) -> bool {
+        self.mobiles.is_empty() && self.user_ids.is_empty() && !self.all
+    }
+
+    /// 钉钉：写入 `at` 字段，并在正文末尾补上 `@手机号` / `@userid`，
+    /// markdown 消息只有正文中出现 @ 才会高亮提醒
+    pub(crate) fn apply_dingding(&self, body: &mut Value) {
+        if self.is_empty() {
+            return;
+        }
+        body["at"] = json!({
+            "atMobiles": self.mobiles,
+            "atUserIds": self.user_ids,
+            "isAtAll": self.all,
+        });
+
+        let field = match body.get("msgtype").and_then(|t| t.as_str()) {
+            Some("text") => ("text", "content"),
+            Some("markdown") => ("markdown", "text"),
+            Some("actionCard") => ("actionCard", "text"),
+            _ => return,
+        };
+        let Some(text) = body.get_mut(field.0).and_then(|v| v.get_mut(field.1)) else {
+            return;
+        };
+
+        let mut content = text.as_str().unwrap_or_default().to_string();
+        for id in self.mobiles.iter().chain(&self.user_ids) {
+            let tag = format!("@{}", id);
+            if !contains_token(&content, &tag) {
+                content.push(' ');
+                content.push_str(&tag);
+            }
+        }
+        *text = json!(content);
+    }
+
+    /// 飞书：文本追加 `<at user_id>` 标签，富文本追加一行 at 元素，卡片追加 markdown 元素
+    pub(crate) fn apply_feishu(&self, msg_type: &str, content: &mut Value) {
+        if self.is_empty() {
+            return;
+        }
+        let mut ids: Vec<&str> = self.user_ids.iter().map(String::as_str).collect();
+        if self.all {
+            ids.push("all");
+        }
+
+        match msg_type {
+            "text" => {
+                let mut text = content["text"].as_str().unwrap_or_default().to_string();
+                for id in &ids {
+                    // 正文已包含的 at 标签不重复追加
+                    if contains_token(&text, &format!("<at user_id=\"{}\"", id)) {
+                        continue;
+                    }
+                    let name = if *id == "all" { "所有人" } else { "" };
+                    text.push_str(&format!(" <at user_id=\"{}\">{}</at>", id, name));
+                }
+                content["text"] = json!(text);
+            }
+            "post" => {
+                let line: Vec<Value> = ids.iter().map(|id| json!({ "tag": "at", "user_id": id })).collect();
+                if let Some(locales) = content.get_mut("post").and_then(|p| p.as_object_mut()) {
+                    for locale in locales.values_mut() {
+                        if let Some(lines) = locale.get_mut("content").and_then(|c| c.as_array_mut()) {
+                            lines.push(json!(line));
+                        }
+                    }
+                }
+            }
+            "interactive" => {
+                let text = ids.iter().map(|id| format!("<at id={}></at>", id)).collect::<Vec<_>>().join(" ");
+                let element = json!({ "tag": "markdown", "content": text });
+                // 卡片 JSON 2.0 的元素在 body.elements 下
+                let elements = if content.get("body").is_some() {
+                    &mut content["body"]["elements"]
+                } else {
+                    &mut content["elements"]
+                };
+                match elements.as_array_mut() {
+                    Some(list) => list.push(element),
+                    None => *elements = json!([element]),
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `content` 中是否出现完整的 `tag`：前后不能紧跟 ID 字符，
+/// 避免 `@1380000000` 被 `@13800000001` 误判为已存在
+fn contains_token(content: &str, tag: &str) -> bool {
+    let is_id_char = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+    content.match_indices(tag).any(|(start, _)| {
+        let before = content[..start].chars().next_back();
+        let after = content[start + tag.len()..].chars().next();
+        !before.is_some_and(is_id_char) && !after.is_some_and(is_id_char)
+    })
+}
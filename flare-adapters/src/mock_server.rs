@@ -0,0 +1,155 @@
+//! 测试用的本地 HTTP 桩服务，记录收到的请求并按路径返回预设响应，
+//! 使适配器测试无需访问真实的第三方接口。
+
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use axum::body::{to_bytes, Body};
+use axum::extract::{Request, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::Response;
+use axum::Router;
+
+#[derive(Debug, Clone)]
+pub(crate) struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub query: Option<String>,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl RecordedRequest {
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_slice(&self.body).expect("请求体不是合法 JSON")
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|v| v.to_str().ok())
+    }
+
+    /// 解析 query string 中的某个参数（已 URL 解码）
+    pub fn query_param(&self, key: &str) -> Option<String> {
+        find_param(self.query.as_deref()?, key)
+    }
+
+    /// 解析 application/x-www-form-urlencoded 请求体中的某个参数
+    pub fn form_param(&self, key: &str) -> Option<String> {
+        find_param(&String::from_utf8_lossy(&self.body), key)
+    }
+}
+
+fn find_param(encoded: &str, key: &str) -> Option<String> {
+    encoded.split('&').find_map(|pair| {
+        let (k, v) = pair.split_once('=')?;
+        let v = v.replace('+', " ");
+        (k == key).then(|| urlencoding::decode(&v).map(|s| s.into_owned()).unwrap_or_default())
+    })
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl MockResponse {
+    pub fn json(status: u16, body: serde_json::Value) -> Self {
+        Self {
+            status,
+            headers: vec![("content-type".into(), "application/json".into())],
+            body: body.to_string(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct MockState {
+    requests: Vec<RecordedRequest>,
+    // 同一路径可预设多个响应，依次返回；只剩最后一个时重复返回
+    responses: HashMap<String, VecDeque<MockResponse>>,
+}
+
+pub(crate) struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockServer {
+    pub async fn start() -> Self {
+        let state = Arc::new(Mutex::new(MockState::default()));
+        let app = Router::new().fallback(handle).with_state(state.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        Self { addr, state }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+
+    /// 为指定路径追加一个响应
+    pub fn respond(&self, path: &str, response: MockResponse) {
+        self.state
+            .lock()
+            .unwrap()
+            .responses
+            .entry(path.to_string())
+            .or_default()
+            .push_back(response);
+    }
+
+    pub fn respond_json(&self, path: &str, body: serde_json::Value) {
+        self.respond(path, MockResponse::json(200, body));
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    pub fn requests_to(&self, path: &str) -> Vec<RecordedRequest> {
+        self.requests().into_iter().filter(|r| r.path == path).collect()
+    }
+}
+
+async fn handle(State(state): State<Arc<Mutex<MockState>>>, req: Request) -> Response {
+    let (parts, body) = req.into_parts();
+    let body = to_bytes(body, usize::MAX).await.unwrap_or_default().to_vec();
+    let path = parts.uri.path().to_string();
+
+    let mut state = state.lock().unwrap();
+    state.requests.push(RecordedRequest {
+        method: parts.method.to_string(),
+        path: path.clone(),
+        query: parts.uri.query().map(|q| q.to_string()),
+        headers: parts.headers,
+        body,
+    });
+
+    let response = state.responses.get_mut(&path).and_then(|queue| {
+        if queue.len() > 1 {
+            queue.pop_front()
+        } else {
+            queue.front().cloned()
+        }
+    });
+
+    match response {
+        Some(r) => {
+            let mut builder = Response::builder().status(r.status);
+            for (name, value) in &r.headers {
+                builder = builder.header(name, value);
+            }
+            builder.body(Body::from(r.body)).unwrap()
+        }
+        None => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::from(format!("no mock response for {}", path)))
+            .unwrap(),
+    }
+}
@@ -0,0 +1,301 @@
+use std::sync::Arc;
+
+use futures_util::stream::{self, StreamExt};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use flare_common::{FlareError, FlareResult};
+
+/// 同时发送的设备数
+const CONCURRENCY: usize = 16;
+
+/// 推送内容：Notification.body 为该结构的 JSON，纯文本视为 body；
+/// Notification.subject 在未指定 title 时作为标题
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PushPayload {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub body: Option<String>,
+    /// 应用图标角标数
+    #[serde(default)]
+    pub badge: Option<u32>,
+    /// 提示音，如 default
+    #[serde(default)]
+    pub sound: Option<String>,
+    /// 业务数据，随通知透传给应用
+    #[serde(default)]
+    pub data: Map<String, Value>,
+    /// 唤醒应用在后台处理 data；没有标题和内容时总是视为静默推送
+    #[serde(default)]
+    pub content_available: bool,
+    /// 相同 collapse_id 的通知只展示最新一条
+    #[serde(default)]
+    pub collapse_id: Option<String>,
+    /// 设备离线时的保留时长，超时后丢弃
+    #[serde(default)]
+    pub ttl_secs: Option<u64>,
+}
+
+impl PushPayload {
+    pub fn parse(body: &str, subject: &str) -> Self {
+        let mut payload = match serde_json::from_str::<PushPayload>(body) {
+            Ok(payload) => payload,
+            Err(_) => PushPayload { body: Some(body.to_string()), ..Default::default() },
+        };
+        if payload.title.is_none() && !subject.is_empty() {
+            payload.title = Some(subject.to_string());
+        }
+        payload
+    }
+
+    /// 没有标题和内容时作为静默推送，只透传 data / badge
+    pub(crate) fn is_silent(&self) -> bool {
+        self.title.is_none() && self.body.is_none()
+    }
+
+    pub(crate) fn validate(&self, platform: &str) -> FlareResult<()> {
+        if self.is_silent() && self.data.is_empty() && self.badge.is_none() && !self.content_available {
+            return Err(FlareError::Config(format!("{} 推送缺少标题、内容或 data", platform)));
+        }
+        Ok(())
+    }
+
+    /// 只支持通知栏消息的厂商要求标题和内容
+    pub(crate) fn require_alert(&self, platform: &str) -> FlareResult<()> {
+        if self.title.is_none() || self.body.is_none() {
+            return Err(FlareError::Config(format!("{} 推送不支持透传消息，需同时指定标题和内容", platform)));
+        }
+        Ok(())
+    }
+
+    /// data 序列化为 JSON 字符串，为空时返回 None
+    pub(crate) fn data_json(&self) -> Option<String> {
+        (!self.data.is_empty()).then(|| Value::Object(self.data.clone()).to_string())
+    }
+}
+
+/// 逐个设备的发送结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PushReport {
+    pub sent: usize,
+    /// 已卸载、过期或不属于本应用的设备令牌，应从业务库中删除
+    pub invalid_tokens: Vec<String>,
+}
+
+/// 推送通道，由 [`PushRouter`](crate::PushRouter) 按设备令牌的厂商标签选择
+#[async_trait::async_trait]
+pub trait PushProvider: Send + Sync {
+    /// 推送到多个设备，返回成功数与失效的设备令牌
+    async fn push(&self, tokens: &[&str], payload: &PushPayload) -> FlareResult<PushReport>;
+}
+
+/// 失效设备令牌的回调，用于清理业务库中的令牌
+#[async_trait::async_trait]
+pub trait InvalidTokenHandler: Send + Sync {
+    async fn handle(&self, platform: &'static str, tokens: &[String]);
+}
+
+/// 单个设备的发送结果
+pub(crate) enum DeviceOutcome {
+    Sent,
+    InvalidToken,
+}
+
+/// Notification.to 中的设备令牌，多个以 `,` 分隔
+pub(crate) fn device_tokens(to: &str) -> Vec<&str> {
+    to.split(',').map(str::trim).filter(|t| !t.is_empty()).collect()
+}
+
+/// 只接受字符串值的自定义数据（FCM 的 data、vivo 的 clientCustomMap），其他类型序列化为 JSON 字符串
+pub(crate) fn string_data(data: &Map<String, Value>) -> Map<String, Value> {
+    data.iter()
+        .map(|(k, v)| {
+            let v = match v {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            (k.clone(), Value::String(v))
+        })
+        .collect()
+}
+
+pub(crate) fn require_tokens(platform: &str, tokens: &[&str]) -> FlareResult<()> {
+    if tokens.is_empty() {
+        return Err(FlareError::Config(format!("{} 推送缺少设备令牌", platform)));
+    }
+    Ok(())
+}
+
+/// 并发发送到各设备并汇总：失效令牌交给回调（未设置时记录日志）；
+/// 其他错误返回第一个，全部令牌失效时返回不可重试的错误
+pub(crate) async fn push_to_devices<'a, F, Fut>(
+    platform: &'static str,
+    tokens: &[&'a str],
+    invalid_token_handler: Option<&Arc<dyn InvalidTokenHandler>>,
+    send: F,
+) -> FlareResult<PushReport>
+where
+    F: Fn(&'a str) -> Fut,
+    Fut: std::future::Future<Output = FlareResult<DeviceOutcome>>,
+{
+    require_tokens(platform, tokens)?;
+
+    let requests: Vec<_> = tokens
+        .iter()
+        .map(|&token| {
+            let fut = send(token);
+            async move { (token, fut.await) }
+        })
+        .collect();
+    let results: Vec<_> = stream::iter(requests).buffer_unordered(CONCURRENCY).collect().await;
+
+    let mut report = PushReport::default();
+    let mut first_error = None;
+    for (token, result) in results {
+        match result {
+            Ok(DeviceOutcome::Sent) => report.sent += 1,
+            Ok(DeviceOutcome::InvalidToken) => report.invalid_tokens.push(token.to_string()),
+            Err(e) => {
+                tracing::warn!("{} 推送失败 token={}: {}", platform, token, e);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    finish_report(platform, report, first_error, invalid_token_handler).await
+}
+
+/// 批量接口单次请求的结果
+#[derive(Debug, Default)]
+pub(crate) struct BatchOutcome {
+    pub invalid_tokens: Vec<String>,
+    /// 因令牌失效以外的原因发送失败的设备
+    pub failed_tokens: Vec<String>,
+}
+
+/// 按 `batch_limit` 分批调用厂商的批量接口并汇总，规则同 [`push_to_devices`]；
+/// 部分设备发送失败时返回可重试的错误
+pub(crate) async fn push_in_batches<'t, F, Fut>(
+    platform: &'static str,
+    tokens: &'t [&'t str],
+    batch_limit: usize,
+    invalid_token_handler: Option<&Arc<dyn InvalidTokenHandler>>,
+    send_batch: F,
+) -> FlareResult<PushReport>
+where
+    F: Fn(&'t [&'t str]) -> Fut,
+    Fut: std::future::Future<Output = FlareResult<BatchOutcome>>,
+{
+    require_tokens(platform, tokens)?;
+
+    let mut report = PushReport::default();
+    let mut first_error = None;
+    for chunk in tokens.chunks(batch_limit) {
+        match send_batch(chunk).await {
+            Ok(outcome) => {
+                let unsent = outcome.invalid_tokens.len() + outcome.failed_tokens.len();
+                report.sent += chunk.len().saturating_sub(unsent);
+                report.invalid_tokens.extend(outcome.invalid_tokens);
+                if !outcome.failed_tokens.is_empty() {
+                    tracing::warn!("{} 推送部分失败: {:?}", platform, outcome.failed_tokens);
+                    first_error.get_or_insert(FlareError::Platform {
+                        platform,
+                        code: 0,
+                        message: format!("{} 个设备发送失败", outcome.failed_tokens.len()),
+                        retryable: true,
+                    });
+                }
+            }
+            Err(e) => {
+                tracing::warn!("{} 推送失败 ({} 个设备): {}", platform, chunk.len(), e);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    finish_report(platform, report, first_error, invalid_token_handler).await
+}
+
+/// 令牌全部失效时返回的错误信息
+pub(crate) const ALL_TOKENS_INVALID: &str = "设备令牌均已失效";
+
+/// 是否为 [`finish_report`] 在令牌全部失效时返回的错误
+pub(crate) fn is_all_tokens_invalid(e: &FlareError) -> bool {
+    matches!(e, FlareError::Platform { code: 0, message, retryable: false, .. } if message == ALL_TOKENS_INVALID)
+}
+
+/// 汇总发送结果，规则同 [`push_to_devices`]
+pub(crate) async fn finish_report(
+    platform: &'static str,
+    report: PushReport,
+    first_error: Option<FlareError>,
+    invalid_token_handler: Option<&Arc<dyn InvalidTokenHandler>>,
+) -> FlareResult<PushReport> {
+    if !report.invalid_tokens.is_empty() {
+        match invalid_token_handler {
+            Some(handler) => handler.handle(platform, &report.invalid_tokens).await,
+            None => tracing::warn!("{} 设备令牌已失效: {:?}", platform, report.invalid_tokens),
+        }
+    }
+    if let Some(e) = first_error {
+        return Err(e);
+    }
+    if report.sent == 0 {
+        return Err(FlareError::Platform {
+            platform,
+            code: 0,
+            message: ALL_TOKENS_INVALID.into(),
+            retryable: false,
+        });
+    }
+    Ok(report)
+}
+
+/// 测试用的回调，记录收到的失效令牌
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct RecordingHandler {
+    pub tokens: std::sync::Mutex<Vec<(&'static str, String)>>,
+}
+
+#[cfg(test)]
+#[async_trait::async_trait]
+impl InvalidTokenHandler for RecordingHandler {
+    async fn handle(&self, platform: &'static str, tokens: &[String]) {
+        let mut recorded = self.tokens.lock().unwrap();
+        recorded.extend(tokens.iter().map(|t| (platform, t.clone())));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn plain_text_payload_uses_subject_as_title() {
+        let payload = PushPayload::parse("您的订单已发货", "物流通知");
+        assert_eq!(payload.title.as_deref(), Some("物流通知"));
+        assert_eq!(payload.body.as_deref(), Some("您的订单已发货"));
+
+        let body = json!({ "title": "自定义", "badge": 3, "data": { "order_id": 42 } });
+        let payload = PushPayload::parse(&body.to_string(), "物流通知");
+        assert_eq!(payload.title.as_deref(), Some("自定义"));
+        assert_eq!(payload.badge, Some(3));
+        assert_eq!(payload.data["order_id"], 42);
+    }
+
+    #[tokio::test]
+    async fn all_invalid_tokens_is_not_retryable() {
+        let handler = Arc::new(RecordingHandler::default());
+        let handler_dyn: Arc<dyn InvalidTokenHandler> = handler.clone();
+
+        let err = push_to_devices("apns", &["a", "b"], Some(&handler_dyn), |_| async {
+            Ok(DeviceOutcome::InvalidToken)
+        })
+        .await
+        .unwrap_err();
+
+        assert!(matches!(err, FlareError::Platform { retryable: false, .. }));
+        assert_eq!(handler.tokens.lock().unwrap().len(), 2);
+    }
+}
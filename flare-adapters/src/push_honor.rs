@@ -0,0 +1,257 @@
+use std::sync::Arc;
+
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use flare_common::{FlareError, FlareResult, HonorPushConfig};
+use flare_core::{Notification, Sender};
+
+use crate::push::{
+    device_tokens, push_in_batches, BatchOutcome, InvalidTokenHandler, PushPayload, PushProvider, PushReport,
+};
+use crate::token_cache::TokenCache;
+
+/// 单次请求的设备令牌上限
+const BATCH_LIMIT: usize = 1000;
+
+const CODE_SUCCESS: i64 = 200;
+/// access token 过期或无效
+const CODE_TOKEN_EXPIRED: i64 = 80200003;
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct OAuthErrorResponse {
+    error: String,
+    #[serde(default)]
+    error_description: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SendResponse {
+    code: i64,
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    data: Option<SendResult>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SendResult {
+    /// 发送失败的令牌
+    #[serde(default)]
+    fail_tokens: Vec<String>,
+    /// 已过期的令牌
+    #[serde(default)]
+    expire_tokens: Vec<String>,
+}
+
+/// 组装 sendMessage 请求体；没有标题和内容时发送透传消息，只带 data
+fn build_message(tokens: &[&str], payload: &PushPayload) -> Value {
+    let mut message = Map::new();
+    message.insert("token".into(), json!(tokens));
+    if let Some(data) = payload.data_json() {
+        message.insert("data".into(), json!(data));
+    }
+
+    let mut android = Map::new();
+    if let Some(ttl) = payload.ttl_secs {
+        android.insert("ttl".into(), json!(format!("{}s", ttl)));
+    }
+    if !payload.is_silent() {
+        let mut notification = Map::new();
+        if let Some(title) = &payload.title {
+            notification.insert("title".into(), json!(title));
+        }
+        if let Some(body) = &payload.body {
+            notification.insert("body".into(), json!(body));
+        }
+        // 点击后打开应用首页
+        notification.insert("clickAction".into(), json!({ "type": 3 }));
+        android.insert("notification".into(), Value::Object(notification));
+    }
+    if !android.is_empty() {
+        message.insert("android".into(), Value::Object(android));
+    }
+    Value::Object(message)
+}
+
+/// 荣耀推送发送器，使用客户端凭据换取 access token 并缓存；
+/// Notification.to 为设备 token，多个以 `,` 分隔
+pub struct HonorPushSender {
+    client: Client,
+    config: HonorPushConfig,
+    token: TokenCache,
+    invalid_token_handler: Option<Arc<dyn InvalidTokenHandler>>,
+}
+
+impl HonorPushSender {
+    pub fn new(config: HonorPushConfig) -> Self {
+        Self {
+            client: Client::new(),
+            config,
+            token: TokenCache::default(),
+            invalid_token_handler: None,
+        }
+    }
+
+    /// 设置失效设备令牌的回调
+    pub fn with_invalid_token_handler(mut self, handler: Arc<dyn InvalidTokenHandler>) -> Self {
+        self.invalid_token_handler = Some(handler);
+        self
+    }
+
+    async fn access_token(&self) -> FlareResult<String> {
+        self.token
+            .get_or_fetch(|| async {
+                let response = self
+                    .client
+                    .post(&self.config.token_url)
+                    .form(&[
+                        ("grant_type", "client_credentials"),
+                        ("client_id", self.config.client_id.as_str()),
+                        ("client_secret", self.config.client_secret.as_str()),
+                    ])
+                    .send()
+                    .await?;
+                let status = response.status();
+                let text = response.text().await.unwrap_or_default();
+                if !status.is_success() {
+                    let message = match serde_json::from_str::<OAuthErrorResponse>(&text) {
+                        Ok(err) => format!("{}: {}", err.error, err.error_description),
+                        Err(_) => text,
+                    };
+                    return Err(FlareError::Platform {
+                        platform: "honor",
+                        code: status.as_u16() as i64,
+                        message,
+                        retryable: status.is_server_error(),
+                    });
+                }
+                let token: TokenResponse = serde_json::from_str(&text)
+                    .map_err(|e| FlareError::Unknown(format!("荣耀推送令牌响应解析失败: {}", e)))?;
+                Ok((token.access_token, token.expires_in))
+            })
+            .await
+    }
+
+    /// 推送到多个设备，返回成功数与失效的设备令牌
+    pub async fn push(&self, tokens: &[&str], payload: &PushPayload) -> FlareResult<PushReport> {
+        payload.validate("荣耀")?;
+        push_in_batches("honor", tokens, BATCH_LIMIT, self.invalid_token_handler.as_ref(), |batch| {
+            self.send_batch(batch, payload)
+        })
+        .await
+    }
+
+    async fn send_batch(&self, tokens: &[&str], payload: &PushPayload) -> FlareResult<BatchOutcome> {
+        let url = format!("{}/api/v1/{}/sendMessage", self.config.api_base, self.config.app_id);
+        let body = build_message(tokens, payload);
+        let mut token_refreshed = false;
+
+        loop {
+            let access_token = self.access_token().await?;
+            let response = self
+                .client
+                .post(&url)
+                .bearer_auth(&access_token)
+                .header("timestamp", chrono::Utc::now().timestamp_millis().to_string())
+                .json(&body)
+                .send()
+                .await?;
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            let Ok(result) = serde_json::from_str::<SendResponse>(&text) else {
+                return Err(FlareError::HttpStatus { status: status.as_u16(), body: text });
+            };
+
+            match result.code {
+                CODE_SUCCESS => {
+                    let data = result.data.unwrap_or_default();
+                    return Ok(BatchOutcome { invalid_tokens: data.expire_tokens, failed_tokens: data.fail_tokens });
+                }
+                // 令牌被提前吊销时刷新后重试一次
+                CODE_TOKEN_EXPIRED if !token_refreshed => {
+                    self.token.invalidate().await;
+                    token_refreshed = true;
+                }
+                code => {
+                    return Err(FlareError::Platform {
+                        platform: "honor",
+                        code,
+                        message: result.message,
+                        retryable: status.as_u16() == 429 || status.is_server_error(),
+                    })
+                }
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl PushProvider for HonorPushSender {
+    async fn push(&self, tokens: &[&str], payload: &PushPayload) -> FlareResult<PushReport> {
+        HonorPushSender::push(self, tokens, payload).await
+    }
+}
+
+#[async_trait::async_trait]
+impl Sender for HonorPushSender {
+    async fn send(&self, notification: &Notification) -> FlareResult<()> {
+        let payload = PushPayload::parse(&notification.body, &notification.subject);
+        self.push(&device_tokens(&notification.to), &payload).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::MockServer;
+    use crate::push::RecordingHandler;
+
+    const TOKEN_PATH: &str = "/auth/token";
+    const SEND_PATH: &str = "/api/v1/104401/sendMessage";
+
+    #[tokio::test]
+    async fn sends_batch_and_reports_expired_tokens() {
+        let server = MockServer::start().await;
+        server.respond_json(TOKEN_PATH, json!({ "access_token": "hnr", "expires_in": 3600, "token_type": "Bearer" }));
+        server.respond_json(SEND_PATH, json!({
+            "code": 200,
+            "message": "success",
+            "data": { "sendResult": true, "requestId": "1", "failTokens": [], "expireTokens": ["stale"] }
+        }));
+        let handler = Arc::new(RecordingHandler::default());
+        let sender = HonorPushSender::new(HonorPushConfig {
+            api_base: server.url(""),
+            token_url: server.url(TOKEN_PATH),
+            app_id: "104401".into(),
+            client_id: "client".into(),
+            client_secret: "secret".into(),
+        })
+        .with_invalid_token_handler(handler.clone());
+
+        let payload = PushPayload::parse(&json!({ "title": "新消息", "body": "您有一条新评论" }).to_string(), "");
+        let report = sender.push(&["fresh", "stale"], &payload).await.unwrap();
+
+        assert_eq!(report, PushReport { sent: 1, invalid_tokens: vec!["stale".into()] });
+        assert_eq!(*handler.tokens.lock().unwrap(), [("honor", "stale".to_string())]);
+        assert_eq!(server.requests_to(TOKEN_PATH)[0].form_param("client_id").as_deref(), Some("client"));
+        let send = &server.requests_to(SEND_PATH)[0];
+        assert_eq!(send.header("authorization"), Some("Bearer hnr"));
+        assert!(send.header("timestamp").is_some());
+        assert_eq!(send.json(), json!({
+            "token": ["fresh", "stale"],
+            "android": {
+                "notification": { "title": "新消息", "body": "您有一条新评论", "clickAction": { "type": 3 } }
+            }
+        }));
+    }
+}
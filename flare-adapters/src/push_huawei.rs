@@ -0,0 +1,303 @@
+use std::sync::Arc;
+
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use flare_common::{FlareError, FlareResult, HuaweiPushConfig};
+use flare_core::{Notification, Sender};
+
+use crate::push::{
+    device_tokens, push_in_batches, BatchOutcome, InvalidTokenHandler, PushPayload, PushProvider, PushReport,
+};
+use crate::token_cache::TokenCache;
+
+/// 单次请求的设备令牌上限
+const BATCH_LIMIT: usize = 1000;
+
+const CODE_SUCCESS: &str = "80000000";
+/// 部分令牌无效，msg 中为 {"success", "failure", "illegal_tokens"}
+const CODE_PARTIAL_SUCCESS: &str = "80100000";
+/// 全部令牌无效
+const CODE_ALL_TOKENS_INVALID: &str = "80300007";
+/// access token 过期或无效
+const CODE_TOKEN_EXPIRED: &str = "80200003";
+/// 系统内部错误
+const CODE_INTERNAL_ERROR: &str = "81000001";
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+/// 如 {"error": 1101, "error_description": "invalid client"}
+#[derive(Debug, Deserialize)]
+struct OAuthErrorResponse {
+    error: i64,
+    #[serde(default)]
+    error_description: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SendResponse {
+    code: String,
+    #[serde(default)]
+    msg: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PartialResult {
+    #[serde(default)]
+    illegal_tokens: Vec<String>,
+}
+
+/// 组装 messages:send 请求体；没有标题和内容时发送透传消息，只带 data
+fn build_message(tokens: &[&str], payload: &PushPayload) -> Value {
+    let mut message = Map::new();
+    message.insert("token".into(), json!(tokens));
+    if let Some(data) = payload.data_json() {
+        message.insert("data".into(), json!(data));
+    }
+
+    let mut android = Map::new();
+    if let Some(ttl) = payload.ttl_secs {
+        android.insert("ttl".into(), json!(format!("{}s", ttl)));
+    }
+    if !payload.is_silent() {
+        let mut notification = Map::new();
+        if let Some(title) = &payload.title {
+            notification.insert("title".into(), json!(title));
+        }
+        if let Some(body) = &payload.body {
+            notification.insert("body".into(), json!(body));
+        }
+        // 点击后打开应用首页
+        notification.insert("click_action".into(), json!({ "type": 3 }));
+        match payload.sound.as_deref() {
+            Some("default") => {
+                notification.insert("default_sound".into(), json!(true));
+            }
+            Some(sound) => {
+                notification.insert("sound".into(), json!(sound));
+            }
+            None => {}
+        }
+        android.insert("notification".into(), Value::Object(notification));
+    }
+    if !android.is_empty() {
+        message.insert("android".into(), Value::Object(android));
+    }
+
+    json!({ "validate_only": false, "message": message })
+}
+
+/// 华为 Push Kit 发送器，使用 OAuth 客户端凭据换取 access token 并缓存；
+/// Notification.to 为设备 token，多个以 `,` 分隔
+pub struct HuaweiPushSender {
+    client: Client,
+    config: HuaweiPushConfig,
+    token: TokenCache,
+    invalid_token_handler: Option<Arc<dyn InvalidTokenHandler>>,
+}
+
+impl HuaweiPushSender {
+    pub fn new(config: HuaweiPushConfig) -> Self {
+        Self {
+            client: Client::new(),
+            config,
+            token: TokenCache::default(),
+            invalid_token_handler: None,
+        }
+    }
+
+    /// 设置失效设备令牌的回调
+    pub fn with_invalid_token_handler(mut self, handler: Arc<dyn InvalidTokenHandler>) -> Self {
+        self.invalid_token_handler = Some(handler);
+        self
+    }
+
+    async fn access_token(&self) -> FlareResult<String> {
+        self.token
+            .get_or_fetch(|| async {
+                let response = self
+                    .client
+                    .post(&self.config.token_url)
+                    .form(&[
+                        ("grant_type", "client_credentials"),
+                        ("client_id", self.config.app_id.as_str()),
+                        ("client_secret", self.config.app_secret.as_str()),
+                    ])
+                    .send()
+                    .await?;
+                let status = response.status();
+                let text = response.text().await.unwrap_or_default();
+                if !status.is_success() {
+                    return Err(match serde_json::from_str::<OAuthErrorResponse>(&text) {
+                        Ok(err) => FlareError::Platform {
+                            platform: "huawei",
+                            code: err.error,
+                            message: err.error_description,
+                            retryable: status.is_server_error(),
+                        },
+                        Err(_) => FlareError::HttpStatus { status: status.as_u16(), body: text },
+                    });
+                }
+                let token: TokenResponse = serde_json::from_str(&text)
+                    .map_err(|e| FlareError::Unknown(format!("华为推送令牌响应解析失败: {}", e)))?;
+                Ok((token.access_token, token.expires_in))
+            })
+            .await
+    }
+
+    /// 推送到多个设备，返回成功数与失效的设备令牌
+    pub async fn push(&self, tokens: &[&str], payload: &PushPayload) -> FlareResult<PushReport> {
+        payload.validate("华为")?;
+        push_in_batches("huawei", tokens, BATCH_LIMIT, self.invalid_token_handler.as_ref(), |batch| {
+            self.send_batch(batch, payload)
+        })
+        .await
+    }
+
+    async fn send_batch(&self, tokens: &[&str], payload: &PushPayload) -> FlareResult<BatchOutcome> {
+        let url = format!("{}/v1/{}/messages:send", self.config.api_base, self.config.app_id);
+        let body = build_message(tokens, payload);
+        let mut token_refreshed = false;
+
+        loop {
+            let access_token = self.access_token().await?;
+            let response = self.client.post(&url).bearer_auth(&access_token).json(&body).send().await?;
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            let Ok(result) = serde_json::from_str::<SendResponse>(&text) else {
+                return Err(FlareError::HttpStatus { status: status.as_u16(), body: text });
+            };
+
+            match result.code.as_str() {
+                CODE_SUCCESS => return Ok(BatchOutcome::default()),
+                CODE_PARTIAL_SUCCESS => {
+                    let partial: PartialResult = serde_json::from_str(&result.msg)
+                        .map_err(|e| FlareError::Unknown(format!("华为推送结果解析失败: {}", e)))?;
+                    return Ok(BatchOutcome { invalid_tokens: partial.illegal_tokens, ..Default::default() });
+                }
+                CODE_ALL_TOKENS_INVALID => {
+                    return Ok(BatchOutcome {
+                        invalid_tokens: tokens.iter().map(|t| t.to_string()).collect(),
+                        ..Default::default()
+                    })
+                }
+                // 令牌被提前吊销时刷新后重试一次
+                CODE_TOKEN_EXPIRED if !token_refreshed => {
+                    self.token.invalidate().await;
+                    token_refreshed = true;
+                }
+                code => {
+                    return Err(FlareError::Platform {
+                        platform: "huawei",
+                        code: code.parse().unwrap_or(0),
+                        message: result.msg,
+                        retryable: code == CODE_INTERNAL_ERROR
+                            || status.as_u16() == 429
+                            || status.is_server_error(),
+                    })
+                }
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl PushProvider for HuaweiPushSender {
+    async fn push(&self, tokens: &[&str], payload: &PushPayload) -> FlareResult<PushReport> {
+        HuaweiPushSender::push(self, tokens, payload).await
+    }
+}
+
+#[async_trait::async_trait]
+impl Sender for HuaweiPushSender {
+    async fn send(&self, notification: &Notification) -> FlareResult<()> {
+        let payload = PushPayload::parse(&notification.body, &notification.subject);
+        self.push(&device_tokens(&notification.to), &payload).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::{MockResponse, MockServer};
+    use crate::push::RecordingHandler;
+
+    const TOKEN_PATH: &str = "/oauth2/v3/token";
+    const SEND_PATH: &str = "/v1/10086/messages:send";
+
+    async fn build_sender() -> (MockServer, HuaweiPushSender) {
+        let server = MockServer::start().await;
+        server.respond_json(TOKEN_PATH, json!({ "access_token": "CV6k", "expires_in": 3600, "token_type": "Bearer" }));
+        let cfg = HuaweiPushConfig {
+            api_base: server.url(""),
+            token_url: server.url(TOKEN_PATH),
+            app_id: "10086".into(),
+            app_secret: "secret".into(),
+        };
+        (server, HuaweiPushSender::new(cfg))
+    }
+
+    #[tokio::test]
+    async fn sends_batch_with_cached_token() {
+        let (server, sender) = build_sender().await;
+        server.respond_json(SEND_PATH, json!({ "code": "80000000", "msg": "Success", "requestId": "1" }));
+        let payload = PushPayload::parse(
+            &json!({ "body": "您的订单已发货", "sound": "default", "data": { "order_id": 42 }, "ttl_secs": 600 }).to_string(),
+            "物流通知",
+        );
+
+        let report = sender.push(&["t1", "t2"], &payload).await.unwrap();
+        sender.push(&["t3"], &payload).await.unwrap();
+
+        assert_eq!(report.sent, 2);
+        let token_requests = server.requests_to(TOKEN_PATH);
+        assert_eq!(token_requests.len(), 1);
+        assert_eq!(token_requests[0].form_param("grant_type").as_deref(), Some("client_credentials"));
+        assert_eq!(token_requests[0].form_param("client_id").as_deref(), Some("10086"));
+
+        let sends = server.requests_to(SEND_PATH);
+        assert_eq!(sends.len(), 2);
+        assert_eq!(sends[0].header("authorization"), Some("Bearer CV6k"));
+        assert_eq!(sends[0].json(), json!({
+            "validate_only": false,
+            "message": {
+                "token": ["t1", "t2"],
+                "data": "{\"order_id\":42}",
+                "android": {
+                    "ttl": "600s",
+                    "notification": {
+                        "title": "物流通知",
+                        "body": "您的订单已发货",
+                        "click_action": { "type": 3 },
+                        "default_sound": true
+                    }
+                }
+            }
+        }));
+    }
+
+    #[tokio::test]
+    async fn reports_illegal_tokens_and_refreshes_expired_token() {
+        let (server, sender) = build_sender().await;
+        let handler = Arc::new(RecordingHandler::default());
+        let sender = sender.with_invalid_token_handler(handler.clone());
+        server.respond(SEND_PATH, MockResponse::json(401, json!({ "code": "80200003", "msg": "OAuth token expired" })));
+        server.respond(SEND_PATH, MockResponse::json(200, json!({
+            "code": "80100000",
+            "msg": "{\"success\":1,\"failure\":1,\"illegal_tokens\":[\"stale\"]}",
+            "requestId": "2"
+        })));
+
+        let report = sender.push(&["fresh", "stale"], &PushPayload::parse("hello", "")).await.unwrap();
+
+        assert_eq!(server.requests_to(TOKEN_PATH).len(), 2);
+        assert_eq!(report, PushReport { sent: 1, invalid_tokens: vec!["stale".into()] });
+        assert_eq!(*handler.tokens.lock().unwrap(), [("huawei", "stale".to_string())]);
+    }
+}
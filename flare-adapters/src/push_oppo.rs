@@ -0,0 +1,301 @@
+use std::sync::Arc;
+
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+
+use flare_common::{FlareError, FlareResult, OppoPushConfig};
+use flare_core::{Notification, Sender};
+
+use crate::push::{
+    device_tokens, push_in_batches, BatchOutcome, InvalidTokenHandler, PushPayload, PushProvider, PushReport,
+};
+use crate::token_cache::TokenCache;
+
+/// 单次批量单推的消息上限
+const BATCH_LIMIT: usize = 1000;
+
+/// auth_token 有效期 24 小时
+const AUTH_TOKEN_TTL_SECS: u64 = 24 * 3600;
+
+const CODE_SUCCESS: i64 = 0;
+/// 服务不可用、限流
+const RETRYABLE_CODES: [i64; 2] = [-1, -2];
+/// auth_token 无效或过期
+const CODE_INVALID_AUTH_TOKEN: i64 = 11;
+/// 逐条结果中的无效 registration_id
+const ERROR_INVALID_REGISTRATION_ID: i64 = 10000;
+
+#[derive(Debug, Deserialize)]
+struct ApiResponse<T> {
+    code: i64,
+    #[serde(default)]
+    message: String,
+    data: Option<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AuthData {
+    auth_token: String,
+}
+
+/// 批量单推的逐条结果
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MessageResult {
+    registration_id: String,
+    #[serde(default)]
+    error_code: Option<i64>,
+}
+
+/// sign = sha256(app_key + timestamp + master_secret)
+fn auth_sign(app_key: &str, timestamp: &str, master_secret: &str) -> String {
+    format!("{:x}", Sha256::digest(format!("{}{}{}", app_key, timestamp, master_secret)))
+}
+
+fn build_notification(payload: &PushPayload, channel_id: Option<&str>) -> Value {
+    let mut notification = Map::new();
+    notification.insert("title".into(), json!(payload.title));
+    notification.insert("content".into(), json!(payload.body));
+    // 点击后打开应用首页，data 作为启动参数
+    notification.insert("click_action_type".into(), json!(0));
+    if let Some(data) = payload.data_json() {
+        notification.insert("action_parameters".into(), json!(data));
+    }
+    if let Some(channel_id) = channel_id {
+        notification.insert("channel_id".into(), json!(channel_id));
+    }
+    if let Some(ttl) = payload.ttl_secs {
+        notification.insert("off_line_ttl".into(), json!(ttl));
+    }
+    Value::Object(notification)
+}
+
+/// OPPO 推送发送器，以 AppKey 与 MasterSecret 签名换取 auth_token 并缓存；
+/// Notification.to 为设备 registration_id，多个以 `,` 分隔。OPPO 只支持通知栏消息
+pub struct OppoPushSender {
+    client: Client,
+    config: OppoPushConfig,
+    token: TokenCache,
+    invalid_token_handler: Option<Arc<dyn InvalidTokenHandler>>,
+}
+
+impl OppoPushSender {
+    pub fn new(config: OppoPushConfig) -> Self {
+        Self {
+            client: Client::new(),
+            config,
+            token: TokenCache::default(),
+            invalid_token_handler: None,
+        }
+    }
+
+    /// 设置失效设备令牌的回调
+    pub fn with_invalid_token_handler(mut self, handler: Arc<dyn InvalidTokenHandler>) -> Self {
+        self.invalid_token_handler = Some(handler);
+        self
+    }
+
+    async fn auth_token(&self) -> FlareResult<String> {
+        self.token
+            .get_or_fetch(|| async {
+                let timestamp = chrono::Utc::now().timestamp_millis().to_string();
+                let sign = auth_sign(&self.config.app_key, &timestamp, &self.config.master_secret);
+                let response = self
+                    .client
+                    .post(format!("{}/server/v1/auth", self.config.api_base))
+                    .form(&[
+                        ("app_key", self.config.app_key.as_str()),
+                        ("timestamp", timestamp.as_str()),
+                        ("sign", sign.as_str()),
+                    ])
+                    .send()
+                    .await?;
+                let data: AuthData = parse_response(response).await?;
+                Ok((data.auth_token, AUTH_TOKEN_TTL_SECS))
+            })
+            .await
+    }
+
+    /// 推送到多个设备，返回成功数与失效的设备令牌
+    pub async fn push(&self, tokens: &[&str], payload: &PushPayload) -> FlareResult<PushReport> {
+        payload.require_alert("OPPO")?;
+        push_in_batches("oppo", tokens, BATCH_LIMIT, self.invalid_token_handler.as_ref(), |batch| {
+            self.send_batch(batch, payload)
+        })
+        .await
+    }
+
+    async fn send_batch(&self, tokens: &[&str], payload: &PushPayload) -> FlareResult<BatchOutcome> {
+        let url = format!("{}/server/v1/message/notification/unicast_batch", self.config.api_base);
+        let notification = build_notification(payload, self.config.channel_id.as_deref());
+        let messages: Vec<Value> = tokens
+            .iter()
+            .map(|token| json!({ "target_type": 2, "target_value": token, "notification": notification }))
+            .collect();
+        let messages = Value::Array(messages).to_string();
+        let mut token_refreshed = false;
+
+        loop {
+            let auth_token = self.auth_token().await?;
+            let response = self
+                .client
+                .post(&url)
+                .header("auth_token", &auth_token)
+                .form(&[("messages", messages.as_str())])
+                .send()
+                .await?;
+            let results: Vec<MessageResult> = match parse_response(response).await {
+                Ok(results) => results,
+                // auth_token 被提前吊销时刷新后重试一次
+                Err(FlareError::Platform { code: CODE_INVALID_AUTH_TOKEN, .. }) if !token_refreshed => {
+                    self.token.invalidate().await;
+                    token_refreshed = true;
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+
+            let mut outcome = BatchOutcome::default();
+            for result in results {
+                match result.error_code {
+                    None => {}
+                    Some(ERROR_INVALID_REGISTRATION_ID) => outcome.invalid_tokens.push(result.registration_id),
+                    Some(_) => outcome.failed_tokens.push(result.registration_id),
+                }
+            }
+            return Ok(outcome);
+        }
+    }
+}
+
+/// 解析 {"code", "message", "data"} 响应，code 非 0 时返回平台错误
+async fn parse_response<T: DeserializeOwned>(response: reqwest::Response) -> FlareResult<T> {
+    let status = response.status();
+    let text = response.text().await.unwrap_or_default();
+    let Ok(result) = serde_json::from_str::<ApiResponse<T>>(&text) else {
+        return Err(FlareError::HttpStatus { status: status.as_u16(), body: text });
+    };
+    match (result.code, result.data) {
+        (CODE_SUCCESS, Some(data)) => Ok(data),
+        (code, _) => Err(FlareError::Platform {
+            platform: "oppo",
+            code,
+            message: result.message,
+            retryable: RETRYABLE_CODES.contains(&code) || status.is_server_error(),
+        }),
+    }
+}
+
+#[async_trait::async_trait]
+impl PushProvider for OppoPushSender {
+    async fn push(&self, tokens: &[&str], payload: &PushPayload) -> FlareResult<PushReport> {
+        OppoPushSender::push(self, tokens, payload).await
+    }
+}
+
+#[async_trait::async_trait]
+impl Sender for OppoPushSender {
+    async fn send(&self, notification: &Notification) -> FlareResult<()> {
+        let payload = PushPayload::parse(&notification.body, &notification.subject);
+        self.push(&device_tokens(&notification.to), &payload).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::{MockResponse, MockServer};
+    use crate::push::RecordingHandler;
+
+    const AUTH_PATH: &str = "/server/v1/auth";
+    const SEND_PATH: &str = "/server/v1/message/notification/unicast_batch";
+
+    fn build_sender(server: &MockServer) -> OppoPushSender {
+        server.respond_json(AUTH_PATH, json!({
+            "code": 0,
+            "message": "Success",
+            "data": { "auth_token": "oppo-token", "create_time": 1700000000000u64 }
+        }));
+        OppoPushSender::new(OppoPushConfig {
+            api_base: server.url(""),
+            app_key: "app-key".into(),
+            master_secret: "master".into(),
+            channel_id: Some("order".into()),
+        })
+    }
+
+    #[tokio::test]
+    async fn signs_auth_and_sends_batch() {
+        let server = MockServer::start().await;
+        let handler = Arc::new(RecordingHandler::default());
+        let sender = build_sender(&server).with_invalid_token_handler(handler.clone());
+        server.respond_json(SEND_PATH, json!({
+            "code": 0,
+            "message": "Success",
+            "data": [
+                { "messageId": "m1", "registrationId": "fresh" },
+                { "registrationId": "stale", "errorCode": 10000, "errorMessage": "Invalid RegistrationId" }
+            ]
+        }));
+        let payload = PushPayload::parse(
+            &json!({ "title": "新消息", "body": "您有一条新评论", "data": { "post_id": 7 } }).to_string(),
+            "",
+        );
+
+        let report = sender.push(&["fresh", "stale"], &payload).await.unwrap();
+        sender.push(&["fresh", "stale"], &payload).await.unwrap();
+
+        assert_eq!(report, PushReport { sent: 1, invalid_tokens: vec!["stale".into()] });
+        assert_eq!(handler.tokens.lock().unwrap()[0], ("oppo", "stale".to_string()));
+
+        let auth_requests = server.requests_to(AUTH_PATH);
+        assert_eq!(auth_requests.len(), 1);
+        let auth = &auth_requests[0];
+        let timestamp = auth.form_param("timestamp").unwrap();
+        assert_eq!(auth.form_param("sign").unwrap(), auth_sign("app-key", &timestamp, "master"));
+
+        let send = &server.requests_to(SEND_PATH)[0];
+        assert_eq!(send.header("auth_token"), Some("oppo-token"));
+        let messages: Value = serde_json::from_str(&send.form_param("messages").unwrap()).unwrap();
+        assert_eq!(messages[1], json!({
+            "target_type": 2,
+            "target_value": "stale",
+            "notification": {
+                "title": "新消息",
+                "content": "您有一条新评论",
+                "click_action_type": 0,
+                "action_parameters": "{\"post_id\":7}",
+                "channel_id": "order"
+            }
+        }));
+    }
+
+    #[tokio::test]
+    async fn refreshes_invalid_auth_token_once() {
+        let server = MockServer::start().await;
+        let sender = build_sender(&server);
+        server.respond(SEND_PATH, MockResponse::json(200, json!({ "code": 11, "message": "Invalid AuthToken" })));
+        server.respond(SEND_PATH, MockResponse::json(200, json!({
+            "code": 0,
+            "message": "Success",
+            "data": [{ "messageId": "m1", "registrationId": "t1" }]
+        })));
+
+        sender.push(&["t1"], &PushPayload::parse("hello", "通知")).await.unwrap();
+
+        assert_eq!(server.requests_to(AUTH_PATH).len(), 2);
+        assert_eq!(server.requests_to(SEND_PATH).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn silent_push_is_rejected() {
+        let server = MockServer::start().await;
+        let payload = PushPayload::parse(&json!({ "data": { "sync": true } }).to_string(), "");
+        let err = build_sender(&server).push(&["t1"], &payload).await.unwrap_err();
+        assert!(matches!(err, FlareError::Config(_)));
+    }
+}
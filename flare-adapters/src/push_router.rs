@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use flare_common::{FlareError, FlareResult, PushPlatform};
+use flare_core::{Notification, Sender};
+
+use crate::push::{
+    device_tokens, is_all_tokens_invalid, require_tokens, InvalidTokenHandler, PushPayload, PushProvider, PushReport,
+};
+
+/// 拆出设备令牌的厂商标签，如 `huawei:xxx` 返回 `(Some(Huawei), "xxx")`。
+/// FCM 令牌本身含有 `:`，只有已知的平台名才视为标签
+pub fn split_vendor_tag(token: &str) -> (Option<PushPlatform>, &str) {
+    match token.split_once(':') {
+        Some((tag, rest)) => match PushPlatform::parse(tag) {
+            Some(platform) => (Some(platform), rest),
+            None => (None, token),
+        },
+        None => (None, token),
+    }
+}
+
+/// 一个通道上待发送的令牌，保留原始写法以便按原样返回失效令牌
+struct Group<'a> {
+    platform: PushPlatform,
+    tokens: Vec<&'a str>,
+    originals: Vec<&'a str>,
+}
+
+/// 多厂商推送：设备令牌写作 `{平台}:{令牌}`（如 `huawei:xxx`、`apns:xxx`），
+/// 按标签分组发到对应通道；不带标签的令牌发到默认通道。
+/// 国内安卓设备无法使用 FCM，客户端按机型注册厂商推送并上报带标签的令牌
+#[derive(Default)]
+pub struct PushRouter {
+    providers: HashMap<PushPlatform, Arc<dyn PushProvider>>,
+    invalid_token_handler: Option<Arc<dyn InvalidTokenHandler>>,
+}
+
+impl PushRouter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_provider(mut self, platform: PushPlatform, provider: Arc<dyn PushProvider>) -> Self {
+        self.providers.insert(platform, provider);
+        self
+    }
+
+    /// 失效令牌按平台回调，收到的是调用方传入的原始写法（含厂商标签），可直接用于清理设备记录
+    pub fn with_invalid_token_handler(mut self, handler: Arc<dyn InvalidTokenHandler>) -> Self {
+        self.invalid_token_handler = Some(handler);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+
+    /// 按厂商标签分组推送。`default` 为不带标签令牌的通道，
+    /// 未指定时若只配置了一个通道则使用该通道，否则跳过这些令牌。
+    /// 任一通道返回可重试的错误时返回该错误以便整体重试；其他错误只在没有设备发送成功时返回
+    pub async fn push(
+        &self,
+        tokens: &[&str],
+        payload: &PushPayload,
+        default: Option<PushPlatform>,
+    ) -> FlareResult<PushReport> {
+        require_tokens("push", tokens)?;
+        let default = default.or_else(|| match self.providers.len() {
+            1 => self.providers.keys().next().copied(),
+            _ => None,
+        });
+
+        let mut groups: Vec<Group> = Vec::new();
+        // 不带标签又没有默认通道的令牌无法发送，不影响其他令牌
+        let mut unrouted: Vec<&str> = Vec::new();
+        for &original in tokens {
+            let (platform, token) = match split_vendor_tag(original) {
+                (Some(platform), token) => (platform, token),
+                (None, token) => match default {
+                    Some(platform) => (platform, token),
+                    None => {
+                        unrouted.push(original);
+                        continue;
+                    }
+                },
+            };
+            match groups.iter_mut().find(|g| g.platform == platform) {
+                Some(group) => {
+                    group.tokens.push(token);
+                    group.originals.push(original);
+                }
+                None => groups.push(Group { platform, tokens: vec![token], originals: vec![original] }),
+            }
+        }
+
+        let mut requests = Vec::with_capacity(groups.len());
+        for group in &groups {
+            requests.push(self.push_group(group, payload));
+        }
+        let results = futures_util::future::join_all(requests).await;
+
+        let mut report = PushReport::default();
+        let mut retryable_error = None;
+        let mut other_error = None;
+        let mut invalid_by_platform: Vec<(&'static str, Vec<String>)> = Vec::new();
+        for (group, result) in groups.iter().zip(results) {
+            let name: &'static str = group.platform.into();
+            let invalid: Vec<String> = match result {
+                // 按调用方传入的写法（含标签）返回失效令牌
+                Ok(group_report) => {
+                    report.sent += group_report.sent;
+                    group_report
+                        .invalid_tokens
+                        .iter()
+                        .map(|invalid| {
+                            group
+                                .tokens
+                                .iter()
+                                .position(|t| t == invalid)
+                                .map_or_else(|| invalid.clone(), |i| group.originals[i].to_string())
+                        })
+                        .collect()
+                }
+                Err(e) if e.is_retryable() => {
+                    retryable_error.get_or_insert(e);
+                    Vec::new()
+                }
+                // 该通道的令牌全部失效
+                Err(e) if is_all_tokens_invalid(&e) => {
+                    other_error.get_or_insert(e);
+                    group.originals.iter().map(|t| t.to_string()).collect()
+                }
+                Err(e) => {
+                    tracing::warn!("{} 推送失败 ({} 个设备): {}", name, group.tokens.len(), e);
+                    other_error.get_or_insert(e);
+                    Vec::new()
+                }
+            };
+            if !invalid.is_empty() {
+                report.invalid_tokens.extend(invalid.iter().cloned());
+                invalid_by_platform.push((name, invalid));
+            }
+        }
+        if !unrouted.is_empty() {
+            tracing::warn!("{} 个设备令牌缺少厂商标签且未指定 platform: {:?}", unrouted.len(), unrouted);
+            other_error.get_or_insert(FlareError::Config(format!(
+                "设备令牌缺少厂商标签且未指定 platform: {}",
+                unrouted.join(",")
+            )));
+        }
+        if let Some(handler) = &self.invalid_token_handler {
+            for (name, invalid) in &invalid_by_platform {
+                handler.handle(name, invalid).await;
+            }
+        }
+
+        if let Some(e) = retryable_error {
+            return Err(e);
+        }
+        match other_error {
+            Some(e) if report.sent == 0 => Err(e),
+            _ => Ok(report),
+        }
+    }
+
+    async fn push_group(&self, group: &Group<'_>, payload: &PushPayload) -> FlareResult<PushReport> {
+        match self.providers.get(&group.platform) {
+            Some(provider) => provider.push(&group.tokens, payload).await,
+            None => {
+                let name: &'static str = group.platform.into();
+                Err(FlareError::Config(format!("未配置 {} 推送", name)))
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Sender for PushRouter {
+    async fn send(&self, notification: &Notification) -> FlareResult<()> {
+        let payload = PushPayload::parse(&notification.body, &notification.subject);
+        self.push(&device_tokens(&notification.to), &payload, None).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    /// 记录收到的令牌，以 `bad` 开头的令牌视为失效
+    #[derive(Default)]
+    struct FakeProvider {
+        received: Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl PushProvider for FakeProvider {
+        async fn push(&self, tokens: &[&str], _payload: &PushPayload) -> FlareResult<PushReport> {
+            self.received.lock().unwrap().extend(tokens.iter().map(|t| t.to_string()));
+            let invalid_tokens: Vec<String> =
+                tokens.iter().filter(|t| t.starts_with("bad")).map(|t| t.to_string()).collect();
+            Ok(PushReport { sent: tokens.len() - invalid_tokens.len(), invalid_tokens })
+        }
+    }
+
+    /// 通道繁忙，返回可重试的错误
+    struct BusyProvider;
+
+    #[async_trait::async_trait]
+    impl PushProvider for BusyProvider {
+        async fn push(&self, _tokens: &[&str], _payload: &PushPayload) -> FlareResult<PushReport> {
+            Err(FlareError::Platform { platform: "honor", code: 503, message: "busy".into(), retryable: true })
+        }
+    }
+
+    /// 令牌全部失效，按厂商实现的方式返回错误
+    struct ExpiredProvider;
+
+    #[async_trait::async_trait]
+    impl PushProvider for ExpiredProvider {
+        async fn push(&self, tokens: &[&str], _payload: &PushPayload) -> FlareResult<PushReport> {
+            let report = PushReport { sent: 0, invalid_tokens: tokens.iter().map(|t| t.to_string()).collect() };
+            crate::push::finish_report("oppo", report, None, None).await
+        }
+    }
+
+    fn payload() -> PushPayload {
+        PushPayload::parse("您有新消息", "通知")
+    }
+
+    #[test]
+    fn only_known_vendors_are_tags() {
+        assert_eq!(split_vendor_tag("huawei:IQAAAAC"), (Some(PushPlatform::Huawei), "IQAAAAC"));
+        assert_eq!(split_vendor_tag("Vivo:1234"), (Some(PushPlatform::Vivo), "1234"));
+        assert_eq!(split_vendor_tag("dQw4w9WgXcQ:APA91bH"), (None, "dQw4w9WgXcQ:APA91bH"));
+        assert_eq!(split_vendor_tag("a1b2c3"), (None, "a1b2c3"));
+    }
+
+    #[tokio::test]
+    async fn routes_tokens_by_vendor_tag() {
+        let huawei = Arc::new(FakeProvider::default());
+        let xiaomi = Arc::new(FakeProvider::default());
+        let fcm = Arc::new(FakeProvider::default());
+        let router = PushRouter::new()
+            .with_provider(PushPlatform::Huawei, huawei.clone())
+            .with_provider(PushPlatform::Xiaomi, xiaomi.clone())
+            .with_provider(PushPlatform::Fcm, fcm.clone());
+
+        let report = router
+            .push(
+                &["huawei:h1", "xiaomi:m1", "huawei:bad-h2", "dQw4w9WgXcQ:APA91bH"],
+                &payload(),
+                Some(PushPlatform::Fcm),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(*huawei.received.lock().unwrap(), ["h1", "bad-h2"]);
+        assert_eq!(*xiaomi.received.lock().unwrap(), ["m1"]);
+        assert_eq!(*fcm.received.lock().unwrap(), ["dQw4w9WgXcQ:APA91bH"]);
+        assert_eq!(report.sent, 3);
+        assert_eq!(report.invalid_tokens, ["huawei:bad-h2"]);
+    }
+
+    #[tokio::test]
+    async fn untagged_tokens_need_a_default() {
+        let router = PushRouter::new()
+            .with_provider(PushPlatform::Huawei, Arc::new(FakeProvider::default()))
+            .with_provider(PushPlatform::Oppo, Arc::new(FakeProvider::default()));
+        let err = router.push(&["t1"], &payload(), None).await.unwrap_err();
+        assert!(matches!(err, FlareError::Config(_)));
+
+        // 其他令牌照常发送
+        let huawei = Arc::new(FakeProvider::default());
+        let router = router.with_provider(PushPlatform::Huawei, huawei.clone());
+        let report = router.push(&["t1", "huawei:h1"], &payload(), None).await.unwrap();
+        assert_eq!(report.sent, 1);
+        assert_eq!(*huawei.received.lock().unwrap(), ["h1"]);
+
+        // 只配置了一个通道时作为默认通道
+        let only = Arc::new(FakeProvider::default());
+        let router = PushRouter::new().with_provider(PushPlatform::Vivo, only.clone());
+        router.push(&["t1"], &payload(), None).await.unwrap();
+        assert_eq!(*only.received.lock().unwrap(), ["t1"]);
+    }
+
+    #[tokio::test]
+    async fn unconfigured_vendor_only_fails_when_nothing_sent() {
+        let router = PushRouter::new().with_provider(PushPlatform::Huawei, Arc::new(FakeProvider::default()));
+        let report = router.push(&["huawei:h1", "honor:r1"], &payload(), None).await.unwrap();
+        assert_eq!(report.sent, 1);
+
+        let err = router.push(&["honor:r1"], &payload(), None).await.unwrap_err();
+        assert!(matches!(err, FlareError::Config(_)));
+
+        // 可重试的错误总是返回，由上游整体重试
+        let router = router.with_provider(PushPlatform::Honor, Arc::new(BusyProvider));
+        let err = router.push(&["huawei:h1", "honor:r1"], &payload(), None).await.unwrap_err();
+        assert!(err.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn invalid_token_handler_receives_tagged_tokens() {
+        let handler = Arc::new(crate::push::RecordingHandler::default());
+        let router = PushRouter::new()
+            .with_provider(PushPlatform::Huawei, Arc::new(FakeProvider::default()))
+            .with_provider(PushPlatform::Oppo, Arc::new(ExpiredProvider))
+            .with_invalid_token_handler(handler.clone());
+
+        let report = router.push(&["huawei:h1", "huawei:bad-h2", "oppo:o1"], &payload(), None).await.unwrap();
+        assert_eq!(report.sent, 1);
+        assert_eq!(report.invalid_tokens, ["huawei:bad-h2", "oppo:o1"]);
+        assert_eq!(*handler.tokens.lock().unwrap(), [("huawei", "huawei:bad-h2".to_string()), ("oppo", "oppo:o1".to_string())]);
+
+        // 全部失效时仍返回错误，但失效令牌已交给回调
+        handler.tokens.lock().unwrap().clear();
+        router.push(&["oppo:o2"], &payload(), None).await.unwrap_err();
+        assert_eq!(*handler.tokens.lock().unwrap(), [("oppo", "oppo:o2".to_string())]);
+    }
+}
@@ -0,0 +1,237 @@
+use std::sync::Arc;
+
+use md5::{Digest, Md5};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use flare_common::{FlareError, FlareResult, VivoPushConfig};
+use flare_core::{Notification, Sender};
+
+use crate::push::{
+    device_tokens, push_to_devices, string_data, DeviceOutcome, InvalidTokenHandler, PushPayload, PushProvider,
+    PushReport,
+};
+use crate::token_cache::TokenCache;
+
+/// authToken 有效期 24 小时
+const AUTH_TOKEN_TTL_SECS: u64 = 24 * 3600;
+
+const RESULT_SUCCESS: i64 = 0;
+/// authToken 无效或过期
+const RESULT_INVALID_AUTH_TOKEN: i64 = 10000;
+/// regId 不存在或已失效
+const RESULT_INVALID_REG_ID: i64 = 10302;
+/// 系统繁忙
+const RESULT_BUSY: i64 = 10500;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiResponse {
+    result: i64,
+    #[serde(default)]
+    desc: String,
+    #[serde(default)]
+    auth_token: Option<String>,
+}
+
+/// sign = md5(appId + appKey + timestamp + appSecret)
+fn auth_sign(app_id: &str, app_key: &str, timestamp: &str, app_secret: &str) -> String {
+    format!("{:x}", Md5::digest(format!("{}{}{}{}", app_id, app_key, timestamp, app_secret)))
+}
+
+/// vivo 推送发送器，以 AppId、AppKey 与 AppSecret 签名换取 authToken 并缓存；
+/// Notification.to 为设备 regId，多个以 `,` 分隔。vivo 只支持通知栏消息
+pub struct VivoPushSender {
+    client: Client,
+    config: VivoPushConfig,
+    token: TokenCache,
+    invalid_token_handler: Option<Arc<dyn InvalidTokenHandler>>,
+}
+
+impl VivoPushSender {
+    pub fn new(config: VivoPushConfig) -> Self {
+        Self {
+            client: Client::new(),
+            config,
+            token: TokenCache::default(),
+            invalid_token_handler: None,
+        }
+    }
+
+    /// 设置失效设备令牌的回调
+    pub fn with_invalid_token_handler(mut self, handler: Arc<dyn InvalidTokenHandler>) -> Self {
+        self.invalid_token_handler = Some(handler);
+        self
+    }
+
+    async fn auth_token(&self) -> FlareResult<String> {
+        self.token
+            .get_or_fetch(|| async {
+                let timestamp = chrono::Utc::now().timestamp_millis().to_string();
+                let body = json!({
+                    "appId": self.config.app_id,
+                    "appKey": self.config.app_key,
+                    "timestamp": timestamp,
+                    "sign": auth_sign(&self.config.app_id, &self.config.app_key, &timestamp, &self.config.app_secret),
+                });
+                let response = self
+                    .client
+                    .post(format!("{}/message/auth", self.config.api_base))
+                    .json(&body)
+                    .send()
+                    .await?;
+                let result = parse_response(response).await?;
+                let token = result
+                    .auth_token
+                    .ok_or_else(|| FlareError::Unknown("vivo 鉴权响应缺少 authToken".into()))?;
+                Ok((token, AUTH_TOKEN_TTL_SECS))
+            })
+            .await
+    }
+
+    /// 推送到多个设备，返回成功数与失效的设备令牌
+    pub async fn push(&self, tokens: &[&str], payload: &PushPayload) -> FlareResult<PushReport> {
+        payload.require_alert("vivo")?;
+        push_to_devices("vivo", tokens, self.invalid_token_handler.as_ref(), |token| {
+            self.send_to_device(token, payload)
+        })
+        .await
+    }
+
+    async fn send_to_device(&self, reg_id: &str, payload: &PushPayload) -> FlareResult<DeviceOutcome> {
+        let mut body = json!({
+            "regId": reg_id,
+            "title": payload.title,
+            "content": payload.body,
+            // 响铃并振动
+            "notifyType": 4,
+            // 点击后打开应用首页
+            "skipType": 1,
+            // 用于平台去重，重试时沿用同一请求 ID
+            "requestId": uuid::Uuid::new_v4().to_string(),
+        });
+        if !payload.data.is_empty() {
+            body["clientCustomMap"] = Value::Object(string_data(&payload.data));
+        }
+        if let Some(ttl) = payload.ttl_secs {
+            body["timeToLive"] = json!(ttl);
+        }
+        let url = format!("{}/message/send", self.config.api_base);
+        let mut token_refreshed = false;
+
+        loop {
+            let auth_token = self.auth_token().await?;
+            let response = self.client.post(&url).header("authToken", &auth_token).json(&body).send().await?;
+            match parse_response(response).await {
+                Ok(_) => return Ok(DeviceOutcome::Sent),
+                Err(FlareError::Platform { code: RESULT_INVALID_REG_ID, .. }) => return Ok(DeviceOutcome::InvalidToken),
+                // authToken 被提前吊销时刷新后重试一次
+                Err(FlareError::Platform { code: RESULT_INVALID_AUTH_TOKEN, .. }) if !token_refreshed => {
+                    self.token.invalidate().await;
+                    token_refreshed = true;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// 解析 {"result", "desc"} 响应，result 非 0 时返回平台错误
+async fn parse_response(response: reqwest::Response) -> FlareResult<ApiResponse> {
+    let status = response.status();
+    let text = response.text().await.unwrap_or_default();
+    let Ok(result) = serde_json::from_str::<ApiResponse>(&text) else {
+        return Err(FlareError::HttpStatus { status: status.as_u16(), body: text });
+    };
+    if result.result != RESULT_SUCCESS {
+        return Err(FlareError::Platform {
+            platform: "vivo",
+            code: result.result,
+            message: result.desc,
+            retryable: result.result == RESULT_BUSY || status.is_server_error(),
+        });
+    }
+    Ok(result)
+}
+
+#[async_trait::async_trait]
+impl PushProvider for VivoPushSender {
+    async fn push(&self, tokens: &[&str], payload: &PushPayload) -> FlareResult<PushReport> {
+        VivoPushSender::push(self, tokens, payload).await
+    }
+}
+
+#[async_trait::async_trait]
+impl Sender for VivoPushSender {
+    async fn send(&self, notification: &Notification) -> FlareResult<()> {
+        let payload = PushPayload::parse(&notification.body, &notification.subject);
+        self.push(&device_tokens(&notification.to), &payload).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::{MockResponse, MockServer};
+    use crate::push::RecordingHandler;
+
+    const AUTH_PATH: &str = "/message/auth";
+    const SEND_PATH: &str = "/message/send";
+
+    fn build_sender(server: &MockServer) -> VivoPushSender {
+        server.respond_json(AUTH_PATH, json!({ "result": 0, "desc": "请求成功", "authToken": "vivo-token" }));
+        VivoPushSender::new(VivoPushConfig {
+            api_base: server.url(""),
+            app_id: "10004".into(),
+            app_key: "app-key".into(),
+            app_secret: "app-secret".into(),
+        })
+    }
+
+    #[tokio::test]
+    async fn signs_auth_and_sends_each_device() {
+        let server = MockServer::start().await;
+        let sender = build_sender(&server);
+        server.respond_json(SEND_PATH, json!({ "result": 0, "desc": "请求成功", "taskId": "t-1" }));
+        let payload = PushPayload::parse(
+            &json!({ "body": "您有一条新评论", "data": { "post_id": 7 }, "ttl_secs": 3600 }).to_string(),
+            "新消息",
+        );
+
+        let report = sender.push(&["r1", "r2"], &payload).await.unwrap();
+
+        assert_eq!(report.sent, 2);
+        let auth_requests = server.requests_to(AUTH_PATH);
+        assert_eq!(auth_requests.len(), 1);
+        let auth = auth_requests[0].json();
+        let timestamp = auth["timestamp"].as_str().unwrap();
+        assert_eq!(auth["sign"], auth_sign("10004", "app-key", timestamp, "app-secret"));
+
+        let sends = server.requests_to(SEND_PATH);
+        assert_eq!(sends.len(), 2);
+        assert_eq!(sends[0].header("authtoken"), Some("vivo-token"));
+        let body = sends[0].json();
+        assert_eq!(body["title"], "新消息");
+        assert_eq!(body["content"], "您有一条新评论");
+        assert_eq!(body["clientCustomMap"], json!({ "post_id": "7" }));
+        assert_eq!(body["timeToLive"], 3600);
+        assert_ne!(body["requestId"], sends[1].json()["requestId"]);
+    }
+
+    #[tokio::test]
+    async fn reports_invalid_reg_ids_and_refreshes_auth_token() {
+        let server = MockServer::start().await;
+        let handler = Arc::new(RecordingHandler::default());
+        let sender = build_sender(&server).with_invalid_token_handler(handler.clone());
+        server.respond(SEND_PATH, MockResponse::json(200, json!({ "result": 10000, "desc": "authToken 无效" })));
+        server.respond(SEND_PATH, MockResponse::json(200, json!({ "result": 10302, "desc": "regId 不存在" })));
+
+        let err = sender.push(&["stale"], &PushPayload::parse("hello", "通知")).await.unwrap_err();
+
+        assert!(matches!(err, FlareError::Platform { retryable: false, .. }));
+        assert_eq!(server.requests_to(AUTH_PATH).len(), 2);
+        assert_eq!(*handler.tokens.lock().unwrap(), [("vivo", "stale".to_string())]);
+    }
+}
@@ -0,0 +1,201 @@
+use std::sync::Arc;
+
+use reqwest::Client;
+use serde::Deserialize;
+
+use flare_common::{FlareError, FlareResult, XiaomiPushConfig};
+use flare_core::{Notification, Sender};
+
+use crate::push::{
+    device_tokens, push_in_batches, BatchOutcome, InvalidTokenHandler, PushPayload, PushProvider, PushReport,
+};
+
+/// 单次请求的 regId 上限
+const BATCH_LIMIT: usize = 1000;
+
+#[derive(Debug, Deserialize)]
+struct SendResponse {
+    result: String,
+    #[serde(default)]
+    code: i64,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    reason: Option<String>,
+    #[serde(default)]
+    data: Option<SendResult>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SendResult {
+    /// 无效的 regId，以 `,` 分隔
+    #[serde(default)]
+    bad_regids: Option<String>,
+}
+
+/// 小米推送发送器。小米直接以 AppSecret 鉴权，无需换取令牌；
+/// Notification.to 为设备 regId，多个以 `,` 分隔
+pub struct XiaomiPushSender {
+    client: Client,
+    config: XiaomiPushConfig,
+    invalid_token_handler: Option<Arc<dyn InvalidTokenHandler>>,
+}
+
+impl XiaomiPushSender {
+    pub fn new(config: XiaomiPushConfig) -> Self {
+        Self {
+            client: Client::new(),
+            config,
+            invalid_token_handler: None,
+        }
+    }
+
+    /// 设置失效设备令牌的回调
+    pub fn with_invalid_token_handler(mut self, handler: Arc<dyn InvalidTokenHandler>) -> Self {
+        self.invalid_token_handler = Some(handler);
+        self
+    }
+
+    /// 推送到多个设备，返回成功数与失效的设备令牌
+    pub async fn push(&self, tokens: &[&str], payload: &PushPayload) -> FlareResult<PushReport> {
+        payload.validate("小米")?;
+        push_in_batches("xiaomi", tokens, BATCH_LIMIT, self.invalid_token_handler.as_ref(), |batch| {
+            self.send_batch(batch, payload)
+        })
+        .await
+    }
+
+    async fn send_batch(&self, tokens: &[&str], payload: &PushPayload) -> FlareResult<BatchOutcome> {
+        let url = format!("{}/v3/message/regid", self.config.api_base);
+        let mut form = vec![
+            ("registration_id", tokens.join(",")),
+            ("restricted_package_name", self.config.package_name.clone()),
+            // 没有标题和内容时作为透传消息交给应用处理
+            ("pass_through", if payload.is_silent() { "1" } else { "0" }.to_string()),
+            // 使用默认的提示音、振动和指示灯
+            ("notify_type", "-1".to_string()),
+            // 点击后打开应用首页
+            ("extra.notify_effect", "1".to_string()),
+        ];
+        if let Some(title) = &payload.title {
+            form.push(("title", title.clone()));
+        }
+        if let Some(body) = &payload.body {
+            form.push(("description", body.clone()));
+        }
+        if let Some(data) = payload.data_json() {
+            form.push(("payload", data));
+        }
+        if let Some(ttl) = payload.ttl_secs {
+            form.push(("time_to_live", (ttl * 1000).to_string()));
+        }
+
+        let response = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("key={}", self.config.app_secret))
+            .form(&form)
+            .send()
+            .await?;
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        let Ok(result) = serde_json::from_str::<SendResponse>(&text) else {
+            return Err(FlareError::HttpStatus { status: status.as_u16(), body: text });
+        };
+        if result.result != "ok" {
+            return Err(FlareError::Platform {
+                platform: "xiaomi",
+                code: result.code,
+                message: result.reason.unwrap_or(result.description),
+                retryable: status.as_u16() == 429 || status.is_server_error(),
+            });
+        }
+
+        let invalid_tokens = result
+            .data
+            .and_then(|d| d.bad_regids)
+            .map(|ids| device_tokens(&ids).into_iter().map(str::to_string).collect())
+            .unwrap_or_default();
+        Ok(BatchOutcome { invalid_tokens, ..Default::default() })
+    }
+}
+
+#[async_trait::async_trait]
+impl PushProvider for XiaomiPushSender {
+    async fn push(&self, tokens: &[&str], payload: &PushPayload) -> FlareResult<PushReport> {
+        XiaomiPushSender::push(self, tokens, payload).await
+    }
+}
+
+#[async_trait::async_trait]
+impl Sender for XiaomiPushSender {
+    async fn send(&self, notification: &Notification) -> FlareResult<()> {
+        let payload = PushPayload::parse(&notification.body, &notification.subject);
+        self.push(&device_tokens(&notification.to), &payload).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::MockServer;
+    use serde_json::json;
+
+    const SEND_PATH: &str = "/v3/message/regid";
+
+    fn build_sender(server: &MockServer) -> XiaomiPushSender {
+        XiaomiPushSender::new(XiaomiPushConfig {
+            api_base: server.url(""),
+            app_secret: "mi-secret".into(),
+            package_name: "com.example.app".into(),
+        })
+    }
+
+    #[tokio::test]
+    async fn sends_regid_batch_and_reports_bad_regids() {
+        let server = MockServer::start().await;
+        server.respond_json(SEND_PATH, json!({
+            "result": "ok",
+            "code": 0,
+            "description": "成功",
+            "data": { "id": "slm001", "bad_regids": "stale" }
+        }));
+        let sender = build_sender(&server);
+        let payload = PushPayload::parse(
+            &json!({ "title": "新消息", "body": "您有一条新评论", "data": { "post_id": 7 }, "ttl_secs": 60 }).to_string(),
+            "",
+        );
+
+        let report = sender.push(&["fresh", "stale"], &payload).await.unwrap();
+
+        assert_eq!(report, PushReport { sent: 1, invalid_tokens: vec!["stale".into()] });
+        let req = &server.requests_to(SEND_PATH)[0];
+        assert_eq!(req.header("authorization"), Some("key=mi-secret"));
+        assert_eq!(req.form_param("registration_id").as_deref(), Some("fresh,stale"));
+        assert_eq!(req.form_param("restricted_package_name").as_deref(), Some("com.example.app"));
+        assert_eq!(req.form_param("title").as_deref(), Some("新消息"));
+        assert_eq!(req.form_param("description").as_deref(), Some("您有一条新评论"));
+        assert_eq!(req.form_param("pass_through").as_deref(), Some("0"));
+        assert_eq!(req.form_param("payload").as_deref(), Some("{\"post_id\":7}"));
+        assert_eq!(req.form_param("time_to_live").as_deref(), Some("60000"));
+    }
+
+    #[tokio::test]
+    async fn platform_error_is_not_retryable() {
+        let server = MockServer::start().await;
+        server.respond_json(SEND_PATH, json!({
+            "result": "error",
+            "code": 22001,
+            "description": "应用不存在",
+            "reason": "Invalid application package name: com.example.app"
+        }));
+
+        let err = build_sender(&server)
+            .push(&["t1"], &PushPayload::parse("hello", "通知"))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, FlareError::Platform { platform: "xiaomi", code: 22001, retryable: false, .. }));
+    }
+}
@@ -0,0 +1,114 @@
+use std::time::{Duration, Instant};
+
+use reqwest::header::HeaderMap;
+use reqwest::{RequestBuilder, Response, StatusCode};
+use tokio::sync::Mutex;
+
+use flare_common::{FlareError, FlareResult};
+
+/// 收到 429 后按 Retry-After 等待重试的次数
+const RATE_LIMIT_RETRIES: u32 = 2;
+
+/// Retry-After 超过该值时不在进程内等待，直接返回可重试错误交由上层调度
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+/// 解析以秒为单位的头部值，Discord 等平台会返回小数
+fn header_secs(headers: &HeaderMap, name: &str) -> Option<Duration> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+        .map(Duration::from_secs_f64)
+}
+
+/// 发送请求，遇到 429 时按 Retry-After 等待后重试
+pub(crate) async fn send_with_rate_limit(
+    platform: &str,
+    build: impl Fn() -> RequestBuilder,
+) -> FlareResult<Response> {
+    let mut attempt = 0;
+    loop {
+        let response = build().send().await?;
+        if response.status() != StatusCode::TOO_MANY_REQUESTS {
+            return Ok(response);
+        }
+
+        let wait = header_secs(response.headers(), "retry-after").unwrap_or(Duration::from_secs(1));
+        if attempt >= RATE_LIMIT_RETRIES || wait > MAX_RETRY_AFTER {
+            let body = response.text().await.unwrap_or_default();
+            return Err(FlareError::HttpStatus { status: 429, body });
+        }
+
+        tracing::warn!("{} 触发限流，{:.1} 秒后重试", platform, wait.as_secs_f64());
+        tokio::time::sleep(wait).await;
+        attempt += 1;
+    }
+}
+
+/// 按 X-RateLimit-Remaining / X-RateLimit-Reset-After 记录的限流窗口：
+/// 额度用尽后，下一次请求等到窗口重置再发出，避免触发 429
+#[derive(Default)]
+pub(crate) struct RateLimitBucket {
+    blocked_until: Mutex<Option<Instant>>,
+}
+
+impl RateLimitBucket {
+    /// 额度用尽时等待窗口重置
+    pub async fn wait(&self) {
+        let until = *self.blocked_until.lock().await;
+        if let Some(until) = until {
+            let now = Instant::now();
+            if until > now {
+                tokio::time::sleep(until - now).await;
+            }
+        }
+    }
+
+    /// 根据响应头更新窗口
+    pub async fn update(&self, headers: &HeaderMap) {
+        let remaining = headers
+            .get("x-ratelimit-remaining")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok());
+        let blocked_until = match (remaining, header_secs(headers, "x-ratelimit-reset-after")) {
+            (Some(0), Some(reset_after)) => Some(Instant::now() + reset_after.min(MAX_RETRY_AFTER)),
+            _ => None,
+        };
+        *self.blocked_until.lock().await = blocked_until;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn parses_fractional_seconds() {
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", HeaderValue::from_static("0.25"));
+        assert_eq!(header_secs(&headers, "retry-after"), Some(Duration::from_millis(250)));
+        headers.insert("retry-after", HeaderValue::from_static("soon"));
+        assert_eq!(header_secs(&headers, "retry-after"), None);
+    }
+
+    #[tokio::test]
+    async fn bucket_blocks_until_reset_when_exhausted() {
+        let bucket = RateLimitBucket::default();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static("0"));
+        headers.insert("x-ratelimit-reset-after", HeaderValue::from_static("0.05"));
+        bucket.update(&headers).await;
+
+        let start = Instant::now();
+        bucket.wait().await;
+        assert!(start.elapsed() >= Duration::from_millis(40));
+
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static("4"));
+        bucket.update(&headers).await;
+        let start = Instant::now();
+        bucket.wait().await;
+        assert!(start.elapsed() < Duration::from_millis(40));
+    }
+}
@@ -0,0 +1,30 @@
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+/// SHA-256 摘要的小写十六进制，阿里云 ACS3 与腾讯云 TC3 签名共用
+pub(crate) fn sha256_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+/// HMAC-SHA256 原始摘要，腾讯云 TC3 逐级派生签名密钥时使用
+pub(crate) fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC 接受任意长度的密钥");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// HMAC-SHA256 后小写十六进制编码，阿里云 ACS3 与腾讯云 TC3 签名共用
+pub(crate) fn hmac_sha256_hex(key: &[u8], data: &[u8]) -> String {
+    hmac_sha256(key, data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// HMAC-SHA256 后 base64 编码，飞书/钉钉机器人与通用 webhook 共用
+pub(crate) fn hmac_sha256_base64(key: &[u8], data: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(hmac_sha256(key, data))
+}
+
+/// 飞书/钉钉机器人加签：以 secret 为密钥对 `timestamp + "\n" + secret` 签名
+pub(crate) fn robot_sign(timestamp: i64, secret: &str) -> String {
+    hmac_sha256_base64(secret.as_bytes(), format!("{}\n{}", timestamp, secret).as_bytes())
+}
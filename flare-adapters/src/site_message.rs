@@ -0,0 +1,180 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+
+use flare_common::{FlareError, FlareResult};
+use flare_core::{Notification, Sender};
+use flare_storage::{InboxMessage, InboxStore, DEFAULT_INBOX_CATEGORY};
+
+#[derive(Debug, Default, Deserialize)]
+struct SiteMessageIncoming {
+    #[serde(default)]
+    title: Option<String>,
+    content: String,
+    #[serde(default)]
+    category: Option<String>,
+    #[serde(default)]
+    data: Option<serde_json::Value>,
+    /// 过期时间（RFC 3339），与 ttl_secs 二选一
+    #[serde(default)]
+    expires_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    ttl_secs: Option<i64>,
+    /// 上游消息 ID，设置后站内信 ID 由其与用户 ID 派生
+    #[serde(default)]
+    message_id: Option<String>,
+}
+
+/// 由上游消息 ID 与用户 ID 派生站内信 ID（UUID v5）：消息重投时 ID 不变，写入按 ID 去重
+fn derived_id(message_id: &str, user_id: &str) -> String {
+    let name = format!("{}\0{}", message_id, user_id);
+    uuid::Uuid::new_v5(&uuid::Uuid::NAMESPACE_OID, name.as_bytes()).to_string()
+}
+
+/// 站内信发送器：为每个接收用户写入一条收件箱消息。
+///
+/// Notification.to 为用户 ID，多个以 `,` 或 `|` 分隔；subject 为标题；
+/// body 为纯文本内容，或 `{"content","title","category","data","expires_at","ttl_secs","message_id"}`
+pub struct SiteMessageSender {
+    store: Arc<dyn InboxStore>,
+}
+
+impl SiteMessageSender {
+    pub fn new(store: Arc<dyn InboxStore>) -> Self {
+        Self { store }
+    }
+
+    fn build_messages(notification: &Notification) -> FlareResult<Vec<InboxMessage>> {
+        let incoming = match serde_json::from_str::<SiteMessageIncoming>(&notification.body) {
+            Ok(incoming) => incoming,
+            Err(_) => SiteMessageIncoming { content: notification.body.clone(), ..Default::default() },
+        };
+
+        let title = match incoming.title.as_deref() {
+            Some(title) if !title.is_empty() => title,
+            _ => notification.subject.as_str(),
+        };
+        if title.is_empty() {
+            return Err(FlareError::Config("站内信缺少标题".into()));
+        }
+        let category = incoming.category.as_deref().unwrap_or(DEFAULT_INBOX_CATEGORY);
+        let expires_at = incoming.expires_at.or_else(|| {
+            incoming
+                .ttl_secs
+                .and_then(Duration::try_seconds)
+                .and_then(|ttl| Utc::now().checked_add_signed(ttl))
+        });
+
+        let messages: Vec<_> = notification
+            .to
+            .split([',', '|'])
+            .map(str::trim)
+            .filter(|user_id| !user_id.is_empty())
+            .map(|user_id| {
+                let mut message = InboxMessage::new(user_id, category, title, &incoming.content);
+                if let Some(message_id) = &incoming.message_id {
+                    message.id = derived_id(message_id, user_id);
+                }
+                message.data = incoming.data.clone();
+                message.expires_at = expires_at;
+                message
+            })
+            .collect();
+        if messages.is_empty() {
+            return Err(FlareError::Config("站内信缺少接收用户".into()));
+        }
+        Ok(messages)
+    }
+}
+
+#[async_trait::async_trait]
+impl Sender for SiteMessageSender {
+    async fn send(&self, notification: &Notification) -> FlareResult<()> {
+        let messages = Self::build_messages(notification)?;
+        let inserted = self
+            .store
+            .insert(&messages)
+            .await
+            .map_err(|e| FlareError::Storage(format!("保存站内信失败: {:#}", e)))?;
+        tracing::debug!("站内信已写入 {} 个用户的收件箱", inserted.len());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flare_common::ChannelType;
+    use flare_storage::{InboxQuery, MemoryInboxStore};
+    use serde_json::json;
+
+    fn notification(to: &str, subject: &str, body: String) -> Notification {
+        Notification {
+            from: String::new(),
+            to: to.into(),
+            subject: subject.into(),
+            body,
+            channel: ChannelType::SiteMessage,
+        }
+    }
+
+    #[tokio::test]
+    async fn writes_one_message_per_user() {
+        let store = Arc::new(MemoryInboxStore::new());
+        let sender = SiteMessageSender::new(store.clone());
+        let body = json!({
+            "content": "您的订单 42 已发货",
+            "category": "order",
+            "data": { "order_id": 42 },
+            "ttl_secs": 3600
+        });
+
+        sender.send(&notification("u1, u2", "订单已发货", body.to_string())).await.unwrap();
+
+        for user_id in ["u1", "u2"] {
+            let messages = store.list(user_id, &InboxQuery::default()).await.unwrap();
+            assert_eq!(messages.len(), 1);
+            let m = &messages[0];
+            assert_eq!((m.title.as_str(), m.category.as_str()), ("订单已发货", "order"));
+            assert_eq!(m.data, Some(json!({ "order_id": 42 })));
+            assert!(m.expires_at.unwrap() > Utc::now() + Duration::minutes(59));
+            assert!(m.read_at.is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn redelivered_message_is_not_duplicated() {
+        let store = Arc::new(MemoryInboxStore::new());
+        let sender = SiteMessageSender::new(store.clone());
+        let body = json!({ "content": "您的订单 42 已发货", "message_id": "kafka-1" }).to_string();
+
+        sender.send(&notification("u1,u2", "订单已发货", body.clone())).await.unwrap();
+        sender.send(&notification("u1,u2", "订单已发货", body)).await.unwrap();
+
+        let u1 = store.list("u1", &InboxQuery::default()).await.unwrap();
+        let u2 = store.list("u2", &InboxQuery::default()).await.unwrap();
+        assert_eq!((u1.len(), u2.len()), (1, 1));
+        assert_ne!(u1[0].id, u2[0].id);
+    }
+
+    #[tokio::test]
+    async fn plain_text_uses_default_category() {
+        let store = Arc::new(MemoryInboxStore::new());
+        let sender = SiteMessageSender::new(store.clone());
+
+        sender.send(&notification("u1", "系统维护", "今晚 22:00 维护".into())).await.unwrap();
+
+        let counts = store.unread_counts("u1").await.unwrap();
+        assert_eq!(counts.categories[DEFAULT_INBOX_CATEGORY], 1);
+    }
+
+    #[tokio::test]
+    async fn requires_title_and_recipient() {
+        let sender = SiteMessageSender::new(Arc::new(MemoryInboxStore::new()));
+        let err = sender.send(&notification("u1", "", "内容".into())).await.unwrap_err();
+        assert!(matches!(err, FlareError::Config(_)));
+        let err = sender.send(&notification(" , ", "标题", "内容".into())).await.unwrap_err();
+        assert!(matches!(err, FlareError::Config(_)));
+    }
+}
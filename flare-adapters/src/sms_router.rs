@@ -0,0 +1,376 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use async_trait::async_trait;
+use flare_common::{FlareError, FlareResult};
+use flare_core::{Notification, Sender};
+use serde_json::Value;
+
+use crate::tencent_sms::to_e164;
+use crate::SmsTemplate;
+
+/// 可经路由发送的短信服务商
+#[async_trait]
+pub trait SmsGateway: Send + Sync {
+    /// 向单个号码发送模板短信，返回服务商的回执 ID
+    async fn send_templated(&self, phone: &str, param: &str, template: &SmsTemplate) -> FlareResult<Option<String>>;
+}
+
+/// 路由中的一个短信服务商
+pub struct SmsProvider {
+    name: String,
+    gateway: Box<dyn SmsGateway>,
+    weight: u32,
+    prefixes: Vec<String>,
+}
+
+impl SmsProvider {
+    /// 默认权重 1，不限号码
+    pub fn new(name: impl Into<String>, gateway: impl SmsGateway + 'static) -> Self {
+        Self {
+            name: name.into(),
+            gateway: Box::new(gateway),
+            weight: 1,
+            prefixes: Vec::new(),
+        }
+    }
+
+    /// 首选权重；为 0 时只作为故障转移的备选
+    pub fn weight(mut self, weight: u32) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// 只服务这些 E.164 前缀的号码，如 `+852`（国家码）或 `+86170`（号段）
+    pub fn prefixes<I, S>(mut self, prefixes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.prefixes = prefixes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// 号码匹配的最长前缀长度；未限制号码时为 0，不可服务时为 None
+    fn match_len(&self, phone: &str) -> Option<usize> {
+        if self.prefixes.is_empty() {
+            return Some(0);
+        }
+        self.prefixes
+            .iter()
+            .filter(|prefix| phone.starts_with(prefix.as_str()))
+            .map(String::len)
+            .max()
+    }
+}
+
+/// 一次发送尝试
+#[derive(Debug, Clone)]
+pub struct SmsAttempt {
+    pub provider: String,
+    /// 失败原因，成功时为 None
+    pub error: Option<String>,
+    /// 服务商返回的错误码，非业务错误时为 None
+    pub err_code: Option<String>,
+    pub retryable: bool,
+}
+
+/// 路由发送的结果，按顺序记录每次尝试
+#[derive(Debug)]
+pub struct SmsDispatch {
+    pub attempts: Vec<SmsAttempt>,
+    biz_id: Option<String>,
+    result: FlareResult<()>,
+}
+
+impl SmsDispatch {
+    /// 发送成功的服务商返回的回执 ID
+    pub fn biz_id(&self) -> Option<&str> {
+        self.biz_id.as_deref()
+    }
+
+    /// 最终发送成功的服务商
+    pub fn delivered_by(&self) -> Option<&str> {
+        self.attempts
+            .last()
+            .filter(|attempt| attempt.error.is_none())
+            .map(|attempt| attempt.provider.as_str())
+    }
+
+    /// 最后一次尝试的结果
+    pub fn into_result(self) -> FlareResult<()> {
+        self.result
+    }
+}
+
+/// 多服务商短信路由：按号码前缀与权重选择服务商，遇到可重试错误时切换到下一家。
+///
+/// 各服务商模板参数格式不同时，消息体可按服务商名分别给出，如
+/// `{"aliyun":{"code":"1234"},"tencent":["1234"]}`；`subject` 作为模板别名传给各服务商
+#[derive(Default)]
+pub struct SmsRouter {
+    providers: Vec<SmsProvider>,
+    counter: AtomicU64,
+}
+
+impl SmsRouter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_provider(mut self, provider: SmsProvider) -> Self {
+        self.providers.push(provider);
+        self
+    }
+
+    /// 号码的候选服务商，按尝试顺序排列：前缀匹配越长越优先；
+    /// 最优的一组按权重轮转决定首选，其余保持配置顺序
+    fn candidates(&self, phone: &str) -> Vec<&SmsProvider> {
+        let mut matched: Vec<(usize, &SmsProvider)> = self
+            .providers
+            .iter()
+            .filter_map(|p| p.match_len(phone).map(|len| (len, p)))
+            .collect();
+        matched.sort_by_key(|(len, _)| std::cmp::Reverse(*len));
+
+        let best = matched.first().map(|(len, _)| *len).unwrap_or_default();
+        let group_len = matched.iter().take_while(|(len, _)| *len == best).count();
+        let mut ordered: Vec<&SmsProvider> = matched.into_iter().map(|(_, p)| p).collect();
+
+        let total: u64 = ordered[..group_len].iter().map(|p| u64::from(p.weight)).sum();
+        if total > 0 {
+            let mut tick = self.counter.fetch_add(1, Ordering::Relaxed) % total;
+            let first = ordered[..group_len]
+                .iter()
+                .position(|p| {
+                    let hit = tick < u64::from(p.weight);
+                    tick = tick.saturating_sub(u64::from(p.weight));
+                    hit
+                })
+                .unwrap_or_default();
+            let primary = ordered.remove(first);
+            ordered.insert(0, primary);
+        }
+        ordered
+    }
+
+    /// 消息体为按服务商名分组的对象时取出对应部分，否则原样使用
+    fn body_for(&self, provider: &str, body: &str) -> String {
+        let Ok(Value::Object(map)) = serde_json::from_str::<Value>(body) else {
+            return body.to_string();
+        };
+        let by_provider = !map.is_empty()
+            && map.keys().all(|key| self.providers.iter().any(|p| &p.name == key));
+        match map.get(provider) {
+            Some(Value::String(s)) if by_provider => s.clone(),
+            Some(value) if by_provider => value.to_string(),
+            _ => body.to_string(),
+        }
+    }
+
+    /// 以 `subject` 为模板别名发送，签名使用各服务商的默认签名
+    pub async fn dispatch(&self, notification: &Notification) -> SmsDispatch {
+        let template = SmsTemplate::from_subject(&notification.subject);
+        self.dispatch_with(&notification.to, &notification.body, &template).await
+    }
+
+    /// 依次尝试候选服务商，直到成功或遇到不可重试的错误；
+    /// `template` 的别名与签名原样交给每个服务商
+    pub async fn dispatch_with(&self, phone: &str, param: &str, template: &SmsTemplate) -> SmsDispatch {
+        let e164 = to_e164(phone);
+        let mut attempts = Vec::new();
+        let mut result = Err(FlareError::Config(format!("没有可发送号码 {} 的短信服务商", e164)));
+
+        for provider in self.candidates(&e164) {
+            let body = self.body_for(&provider.name, param);
+            result = provider.gateway.send_templated(phone, &body, template).await;
+            let error = result.as_ref().err();
+            let retryable = error.is_some_and(FlareError::is_retryable);
+            attempts.push(SmsAttempt {
+                provider: provider.name.clone(),
+                error: error.map(ToString::to_string),
+                err_code: error.and_then(|e| match e {
+                    FlareError::Sms { code, .. } => Some(code.clone()),
+                    FlareError::Platform { code, .. } => Some(code.to_string()),
+                    _ => None,
+                }),
+                retryable,
+            });
+            if !retryable {
+                break;
+            }
+        }
+        match result {
+            Ok(biz_id) => SmsDispatch { attempts, biz_id, result: Ok(()) },
+            Err(e) => SmsDispatch { attempts, biz_id: None, result: Err(e) },
+        }
+    }
+}
+
+#[async_trait]
+impl Sender for SmsRouter {
+    async fn send(&self, notification: &Notification) -> FlareResult<()> {
+        let dispatch = self.dispatch(notification).await;
+        for attempt in &dispatch.attempts {
+            match &attempt.error {
+                None => tracing::info!("短信经 {} 发送成功 phone={}", attempt.provider, notification.to),
+                Some(error) => tracing::warn!(
+                    "短信经 {} 发送失败 phone={} retryable={} error={}",
+                    attempt.provider,
+                    notification.to,
+                    attempt.retryable,
+                    error
+                ),
+            }
+        }
+        dispatch.into_result()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flare_common::ChannelType;
+    use std::sync::{Arc, Mutex};
+
+    /// 按预设结果依次返回，并记录收到的模板参数与签名
+    #[derive(Clone, Default)]
+    struct FakeSender {
+        outcomes: Arc<Mutex<Vec<FlareResult<Option<String>>>>>,
+        bodies: Arc<Mutex<Vec<String>>>,
+        signs: Arc<Mutex<Vec<Option<String>>>>,
+    }
+
+    impl FakeSender {
+        fn failing(code: &str, retryable: bool) -> Self {
+            let fake = Self::default();
+            fake.outcomes.lock().unwrap().push(Err(FlareError::Sms {
+                provider: "fake",
+                code: code.into(),
+                message: String::new(),
+                request_id: None,
+                retryable,
+            }));
+            fake
+        }
+
+        fn calls(&self) -> Vec<String> {
+            self.bodies.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl SmsGateway for FakeSender {
+        async fn send_templated(&self, _phone: &str, param: &str, template: &SmsTemplate) -> FlareResult<Option<String>> {
+            self.bodies.lock().unwrap().push(param.to_string());
+            self.signs.lock().unwrap().push(template.sign_name.clone());
+            self.outcomes.lock().unwrap().pop().unwrap_or(Ok(Some("biz-1".into())))
+        }
+    }
+
+    fn sms(to: &str, body: &str) -> Notification {
+        Notification {
+            from: String::new(),
+            to: to.into(),
+            subject: String::new(),
+            body: body.into(),
+            channel: ChannelType::Sms,
+        }
+    }
+
+    #[tokio::test]
+    async fn fails_over_on_retryable_error() {
+        let aliyun = FakeSender::failing("isv.BUSINESS_LIMIT_CONTROL", true);
+        let tencent = FakeSender::default();
+        let router = SmsRouter::new()
+            .with_provider(SmsProvider::new("aliyun", aliyun.clone()))
+            .with_provider(SmsProvider::new("tencent", tencent.clone()).weight(0));
+
+        let dispatch = router.dispatch(&sms("13800000000", "{}")).await;
+        assert_eq!(dispatch.attempts.len(), 2);
+        assert!(dispatch.attempts[0].retryable);
+        assert_eq!(dispatch.attempts[0].err_code.as_deref(), Some("isv.BUSINESS_LIMIT_CONTROL"));
+        assert_eq!(dispatch.delivered_by(), Some("tencent"));
+        assert_eq!(dispatch.biz_id(), Some("biz-1"));
+        assert!(dispatch.into_result().is_ok());
+    }
+
+    #[tokio::test]
+    async fn sign_name_is_passed_to_every_attempt() {
+        let aliyun = FakeSender::failing("Throttling.User", true);
+        let tencent = FakeSender::default();
+        let router = SmsRouter::new()
+            .with_provider(SmsProvider::new("aliyun", aliyun.clone()))
+            .with_provider(SmsProvider::new("tencent", tencent.clone()).weight(0));
+
+        let template = SmsTemplate { template_code: Some("login_code".into()), sign_name: Some("活动签名".into()) };
+        let dispatch = router.dispatch_with("13800000000", "{}", &template).await;
+        assert_eq!(dispatch.delivered_by(), Some("tencent"));
+        assert_eq!(aliyun.signs.lock().unwrap().clone(), vec![Some("活动签名".to_string())]);
+        assert_eq!(tencent.signs.lock().unwrap().clone(), vec![Some("活动签名".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn stops_on_non_retryable_error() {
+        let aliyun = FakeSender::failing("isv.MOBILE_NUMBER_ILLEGAL", false);
+        let tencent = FakeSender::default();
+        let router = SmsRouter::new()
+            .with_provider(SmsProvider::new("aliyun", aliyun))
+            .with_provider(SmsProvider::new("tencent", tencent.clone()).weight(0));
+
+        let dispatch = router.dispatch(&sms("13800000000", "{}")).await;
+        assert_eq!(dispatch.attempts.len(), 1);
+        assert_eq!(dispatch.delivered_by(), None);
+        assert_eq!(dispatch.biz_id(), None);
+        assert!(dispatch.into_result().is_err());
+        assert!(tencent.calls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn routes_by_prefix() {
+        let aliyun = FakeSender::default();
+        let tencent = FakeSender::default();
+        let router = SmsRouter::new()
+            .with_provider(SmsProvider::new("aliyun", aliyun.clone()).prefixes(["+86"]))
+            .with_provider(SmsProvider::new("tencent", tencent.clone()).prefixes(["+852", "+86170"]));
+
+        assert_eq!(router.dispatch(&sms("13800000000", "{}")).await.delivered_by(), Some("aliyun"));
+        assert_eq!(router.dispatch(&sms("17012345678", "{}")).await.delivered_by(), Some("tencent"));
+        assert_eq!(router.dispatch(&sms("+85291234567", "{}")).await.delivered_by(), Some("tencent"));
+
+        let dispatch = router.dispatch(&sms("+6591234567", "{}")).await;
+        assert!(dispatch.attempts.is_empty());
+        assert!(matches!(dispatch.into_result(), Err(FlareError::Config(_))));
+    }
+
+    #[tokio::test]
+    async fn spreads_by_weight() {
+        let aliyun = FakeSender::default();
+        let tencent = FakeSender::default();
+        let router = SmsRouter::new()
+            .with_provider(SmsProvider::new("aliyun", aliyun.clone()).weight(3))
+            .with_provider(SmsProvider::new("tencent", tencent.clone()).weight(1));
+
+        for _ in 0..8 {
+            router.send(&sms("13800000000", "{}")).await.unwrap();
+        }
+        assert_eq!((aliyun.calls().len(), tencent.calls().len()), (6, 2));
+    }
+
+    #[tokio::test]
+    async fn body_split_by_provider() {
+        let aliyun = FakeSender::failing("Throttling.User", true);
+        let tencent = FakeSender::default();
+        let router = SmsRouter::new()
+            .with_provider(SmsProvider::new("aliyun", aliyun.clone()))
+            .with_provider(SmsProvider::new("tencent", tencent.clone()).weight(0));
+
+        let body = r#"{"aliyun":{"code":"1234"},"tencent":["1234"]}"#;
+        router.send(&sms("13800000000", body)).await.unwrap();
+        assert_eq!(aliyun.calls(), vec![r#"{"code":"1234"}"#.to_string()]);
+        assert_eq!(tencent.calls(), vec![r#"["1234"]"#.to_string()]);
+
+        // 普通模板参数原样传递
+        router.send(&sms("13800000000", r#"{"code":"1"}"#)).await.unwrap();
+        assert_eq!(aliyun.calls()[1], r#"{"code":"1"}"#);
+    }
+}
@@ -0,0 +1,113 @@
+use crate::signing::{hmac_sha256, hmac_sha256_hex, sha256_hex};
+
+/// TC3-HMAC-SHA256 待签名的请求（POST，无查询参数）
+pub(crate) struct Tc3Request<'a> {
+    /// 产品名，如 sms，参与派生签名密钥
+    pub service: &'a str,
+    /// 参与签名的请求头，名称须为小写且按名称排序
+    pub headers: &'a [(&'a str, &'a str)],
+    pub payload: &'a [u8],
+    /// 请求时间戳（秒），与 X-TC-Timestamp 一致
+    pub timestamp: i64,
+}
+
+impl Tc3Request<'_> {
+    fn canonical_request(&self) -> String {
+        let canonical_headers: String = self
+            .headers
+            .iter()
+            .map(|(k, v)| format!("{}:{}\n", k, v.trim().to_lowercase()))
+            .collect();
+        format!(
+            "POST\n/\n\n{}\n{}\n{}",
+            canonical_headers,
+            self.signed_headers(),
+            sha256_hex(self.payload)
+        )
+    }
+
+    fn signed_headers(&self) -> String {
+        self.headers.iter().map(|(k, _)| *k).collect::<Vec<_>>().join(";")
+    }
+
+    /// 计算签名，返回 Authorization 请求头的值
+    pub fn authorization(&self, secret_id: &str, secret_key: &str) -> String {
+        // 凭证范围中的日期为 UTC 日期
+        let date = chrono::DateTime::from_timestamp(self.timestamp, 0)
+            .unwrap_or_default()
+            .format("%Y-%m-%d")
+            .to_string();
+        let scope = format!("{}/{}/tc3_request", date, self.service);
+        let string_to_sign = format!(
+            "TC3-HMAC-SHA256\n{}\n{}\n{}",
+            self.timestamp,
+            scope,
+            sha256_hex(self.canonical_request().as_bytes())
+        );
+
+        let secret_date = hmac_sha256(format!("TC3{}", secret_key).as_bytes(), date.as_bytes());
+        let secret_service = hmac_sha256(&secret_date, self.service.as_bytes());
+        let secret_signing = hmac_sha256(&secret_service, b"tc3_request");
+        let signature = hmac_sha256_hex(&secret_signing, string_to_sign.as_bytes());
+
+        format!(
+            "TC3-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            secret_id,
+            scope,
+            self.signed_headers(),
+            signature
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 腾讯云《签名方法 v3》文档示例：云服务器 DescribeInstances
+    const PAYLOAD: &str =
+        r#"{"Limit": 1, "Filters": [{"Values": ["\u672a\u547d\u540d"], "Name": "instance-name"}]}"#;
+    const SECRET_ID: &str = "AKIDz8krbsJ5yKBZQpn74WFkmLPx3*******";
+    const SECRET_KEY: &str = "Gu5t9xGARNpq86cd98joQYCN3*******";
+
+    #[test]
+    fn documented_payload_and_canonical_request() {
+        assert_eq!(
+            sha256_hex(PAYLOAD.as_bytes()),
+            "35e9c5b0e3ae67532d3c9f17ead6c90222632e5b1ff7f6e89887f1398934f064"
+        );
+        let request = Tc3Request {
+            service: "cvm",
+            headers: &[
+                ("content-type", "application/json; charset=utf-8"),
+                ("host", "cvm.tencentcloudapi.com"),
+                ("x-tc-action", "DescribeInstances"),
+            ],
+            payload: PAYLOAD.as_bytes(),
+            timestamp: 1551113065,
+        };
+        assert_eq!(
+            sha256_hex(request.canonical_request().as_bytes()),
+            "7019a55be8395899b900fb5564e4200d984910f34794a27cb3fb7d10ff6a1e84"
+        );
+    }
+
+    #[test]
+    fn documented_signature() {
+        let request = Tc3Request {
+            service: "cvm",
+            headers: &[
+                ("content-type", "application/json; charset=utf-8"),
+                ("host", "cvm.tencentcloudapi.com"),
+            ],
+            payload: PAYLOAD.as_bytes(),
+            timestamp: 1551113065,
+        };
+        assert_eq!(
+            request.authorization(SECRET_ID, SECRET_KEY),
+            "TC3-HMAC-SHA256 Credential=AKIDz8krbsJ5yKBZQpn74WFkmLPx3*******/2019-02-25/cvm/tc3_request, \
+             SignedHeaders=content-type;host, \
+             Signature=2230eefd229f582d8b1b891af7107b91597240707d778ab3738f756258d7652c"
+        );
+    }
+}
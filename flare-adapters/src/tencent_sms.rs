@@ -0,0 +1,377 @@
+use async_trait::async_trait;
+use flare_common::{FlareError, FlareResult, TencentSmsConfig};
+use flare_core::{Notification, Sender};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::tencent_sign::Tc3Request;
+use crate::{BatchSmsResult, SmsGateway, SmsTemplate};
+
+const API_VERSION: &str = "2021-01-11";
+
+/// SendSms 单次请求最多的号码数
+const BATCH_LIMIT: usize = 200;
+
+/// 可稍后重试的错误码：内部错误与频率限制
+fn is_retryable(code: &str) -> bool {
+    code.starts_with("InternalError")
+        || matches!(
+            code,
+            "RequestLimitExceeded"
+                | "LimitExceeded.DeliveryFrequencyLimit"
+                | "LimitExceeded.PhoneNumberThirtySecondLimit"
+                | "LimitExceeded.PhoneNumberOneHourLimit"
+        )
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ApiError {
+    code: String,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct SendSmsResponse {
+    #[serde(default)]
+    error: Option<ApiError>,
+    #[serde(default)]
+    request_id: Option<String>,
+    #[serde(default)]
+    send_status_set: Vec<SendStatus>,
+}
+
+/// SendStatusSet 中单个号码的发送状态
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct SendStatus {
+    phone_number: String,
+    code: String,
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    serial_no: Option<String>,
+}
+
+impl From<SendStatus> for BatchSmsResult {
+    fn from(status: SendStatus) -> Self {
+        let success = status.code == "Ok";
+        BatchSmsResult {
+            phone: status.phone_number,
+            biz_id: status.serial_no.filter(|_| success),
+            retryable: !success && is_retryable(&status.code),
+            error: (!success).then_some((status.code, status.message)),
+        }
+    }
+}
+
+/// 号码转为 E.164 格式，不带国家码的按中国内地号码处理
+pub(crate) fn to_e164(phone: &str) -> String {
+    let phone = phone.trim();
+    if phone.starts_with('+') {
+        phone.to_string()
+    } else {
+        format!("+86{}", phone)
+    }
+}
+
+/// 模板参数：腾讯云模板变量按位置填充，消息体为 JSON 数组，如 `["1234","5"]`
+pub fn parse_template_params(body: &str) -> FlareResult<Vec<String>> {
+    if body.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let invalid = || FlareError::Config(format!("腾讯云短信模板参数应为 JSON 数组: {}", body));
+    let values: Vec<Value> = serde_json::from_str(body).map_err(|_| invalid())?;
+    values
+        .into_iter()
+        .map(|v| match v {
+            Value::String(s) => Ok(s),
+            Value::Number(n) => Ok(n.to_string()),
+            _ => Err(invalid()),
+        })
+        .collect()
+}
+
+/// 腾讯云短信发送器
+pub struct TencentSmsSender {
+    client: Client,
+    config: TencentSmsConfig,
+}
+
+impl TencentSmsSender {
+    pub fn new(config: TencentSmsConfig) -> Self {
+        Self {
+            client: Client::new(),
+            config,
+        }
+    }
+
+    /// 将模板别名解析为模板 ID；未知别名直接报错
+    fn resolve_template<'a>(&'a self, template: &'a SmsTemplate) -> FlareResult<(&'a str, &'a str)> {
+        let template_id = match template.template_code.as_deref() {
+            None => self.config.template_id.as_str(),
+            Some(name) => match self.config.template_alias(name) {
+                Some(id) => id,
+                None if name.chars().all(|c| c.is_ascii_digit()) => name,
+                None => return Err(FlareError::Config(format!("未知短信模板: {}", name))),
+            },
+        };
+        let sign_name = template.sign_name.as_deref().unwrap_or(&self.config.sign_name);
+        Ok((template_id, sign_name))
+    }
+
+    /// 向多个号码发送同一模板短信，超过单次上限时自动分批。
+    /// 请求级错误（鉴权、参数等）直接返回，号码级结果见返回值
+    pub async fn send_sms(
+        &self,
+        phones: &[&str],
+        params: &[String],
+        template: &SmsTemplate,
+    ) -> FlareResult<Vec<BatchSmsResult>> {
+        let (template_id, sign_name) = self.resolve_template(template)?;
+
+        let mut results = Vec::with_capacity(phones.len());
+        for chunk in phones.chunks(BATCH_LIMIT) {
+            let phone_set: Vec<String> = chunk.iter().map(|p| to_e164(p)).collect();
+            let body = json!({
+                "PhoneNumberSet": phone_set,
+                "SmsSdkAppId": self.config.sdk_app_id,
+                "SignName": sign_name,
+                "TemplateId": template_id,
+                "TemplateParamSet": params,
+            });
+            let resp = self.call("SendSms", &body).await?;
+            for status in resp.send_status_set {
+                let result = BatchSmsResult::from(status);
+                match &result.error {
+                    None => tracing::info!(
+                        "腾讯云短信已提交 phone={} template={} serial_no={}",
+                        result.phone,
+                        template_id,
+                        result.biz_id.as_deref().unwrap_or_default()
+                    ),
+                    Some((code, message)) => {
+                        tracing::warn!("腾讯云短信发送失败 phone={} code={} message={}", result.phone, code, message)
+                    }
+                }
+                results.push(result);
+            }
+        }
+        Ok(results)
+    }
+
+    /// 调用云 API 3.0：JSON 请求体，TC3-HMAC-SHA256 签名
+    async fn call(&self, action: &str, body: &Value) -> FlareResult<SendSmsResponse> {
+        let url = reqwest::Url::parse(&self.config.endpoint)
+            .map_err(|e| FlareError::Config(format!("无效的 TENCENT_SMS_ENDPOINT {}: {}", self.config.endpoint, e)))?;
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+        let payload = body.to_string();
+        let timestamp = chrono::Utc::now().timestamp();
+        let content_type = "application/json; charset=utf-8";
+
+        let authorization = Tc3Request {
+            service: "sms",
+            headers: &[("content-type", content_type), ("host", &host), ("x-tc-action", action)],
+            payload: payload.as_bytes(),
+            timestamp,
+        }
+        .authorization(&self.config.secret_id, &self.config.secret_key);
+
+        let resp = self
+            .client
+            .post(url)
+            .header("Authorization", authorization)
+            .header("Content-Type", content_type)
+            .header("X-TC-Action", action)
+            .header("X-TC-Version", API_VERSION)
+            .header("X-TC-Timestamp", timestamp.to_string())
+            .header("X-TC-Region", &self.config.region)
+            .body(payload)
+            .send()
+            .await?;
+
+        let status = resp.status();
+        let text = resp.text().await?;
+        tracing::debug!("Tencent sms response: {}", text);
+
+        #[derive(Deserialize)]
+        struct Envelope {
+            #[serde(rename = "Response")]
+            response: SendSmsResponse,
+        }
+        let resp = match serde_json::from_str::<Envelope>(&text) {
+            Ok(envelope) => envelope.response,
+            Err(_) => return Err(FlareError::HttpStatus { status: status.as_u16(), body: text }),
+        };
+        if let Some(error) = resp.error {
+            return Err(FlareError::Sms {
+                provider: "tencent",
+                retryable: is_retryable(&error.code),
+                code: error.code,
+                message: error.message,
+                request_id: resp.request_id,
+            });
+        }
+        Ok(resp)
+    }
+}
+
+#[async_trait]
+impl SmsGateway for TencentSmsSender {
+    /// param 为模板参数 JSON 数组，返回 SerialNo
+    async fn send_templated(&self, phone: &str, param: &str, template: &SmsTemplate) -> FlareResult<Option<String>> {
+        let params = parse_template_params(param)?;
+        let results = self.send_sms(&[phone], &params, template).await?;
+        match results.into_iter().next() {
+            Some(BatchSmsResult { error: Some((code, message)), retryable, .. }) => Err(FlareError::Sms {
+                provider: "tencent",
+                code,
+                message,
+                request_id: None,
+                retryable,
+            }),
+            Some(result) => Ok(result.biz_id),
+            None => Err(FlareError::String("腾讯云短信响应缺少 SendStatusSet".into())),
+        }
+    }
+}
+
+#[async_trait]
+impl Sender for TencentSmsSender {
+    async fn send(&self, notification: &Notification) -> FlareResult<()> {
+        // Notification.to 为手机号，body 为模板参数 JSON 数组，subject 可指定模板别名
+        let template = SmsTemplate::from_subject(&notification.subject);
+        self.send_templated(&notification.to, &notification.body, &template).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::MockServer;
+    use flare_common::ChannelType;
+
+    async fn build_mock_sender(response: Value) -> (MockServer, TencentSmsSender) {
+        let server = MockServer::start().await;
+        server.respond_json("/", response);
+        let cfg = TencentSmsConfig {
+            endpoint: server.url("/"),
+            region: "ap-guangzhou".into(),
+            secret_id: "AKIDtest".into(),
+            secret_key: "test-key".into(),
+            sdk_app_id: "1400006666".into(),
+            sign_name: "腾讯云".into(),
+            template_id: "449739".into(),
+            templates: [("order_shipped".to_string(), "1110".to_string())].into(),
+        };
+        (server, TencentSmsSender::new(cfg))
+    }
+
+    fn notification(to: &str, body: &str) -> Notification {
+        Notification {
+            from: String::new(),
+            to: to.into(),
+            subject: String::new(),
+            body: body.into(),
+            channel: ChannelType::Sms,
+        }
+    }
+
+    #[tokio::test]
+    async fn send_builds_signed_request() {
+        let (server, sender) = build_mock_sender(json!({ "Response": {
+            "SendStatusSet": [{ "SerialNo": "5000:1045710669157053657849499619", "PhoneNumber": "+8613800000000",
+                                "Fee": 1, "Code": "Ok", "Message": "send success", "IsoCode": "CN" }],
+            "RequestId": "a0aabda6-cf91-4f3e-a81f-9198114a2279"
+        }})).await;
+
+        sender.send(&notification("13800000000", r#"["1234", 5]"#)).await.unwrap();
+
+        let req = &server.requests()[0];
+        assert_eq!(req.header("x-tc-action"), Some("SendSms"));
+        assert_eq!(req.header("x-tc-version"), Some(API_VERSION));
+        assert_eq!(req.header("x-tc-region"), Some("ap-guangzhou"));
+        let body = req.json();
+        assert_eq!(body["PhoneNumberSet"], json!(["+8613800000000"]));
+        assert_eq!(body["SmsSdkAppId"], "1400006666");
+        assert_eq!(body["SignName"], "腾讯云");
+        assert_eq!(body["TemplateId"], "449739");
+        assert_eq!(body["TemplateParamSet"], json!(["1234", "5"]));
+
+        // 按实际收到的请求重新计算签名
+        let host = req.header("host").unwrap();
+        let timestamp: i64 = req.header("x-tc-timestamp").unwrap().parse().unwrap();
+        let expected = Tc3Request {
+            service: "sms",
+            headers: &[
+                ("content-type", req.header("content-type").unwrap()),
+                ("host", host),
+                ("x-tc-action", "SendSms"),
+            ],
+            payload: &req.body,
+            timestamp,
+        }
+        .authorization("AKIDtest", "test-key");
+        assert_eq!(req.header("authorization"), Some(expected.as_str()));
+    }
+
+    #[tokio::test]
+    async fn per_number_results() {
+        let (server, sender) = build_mock_sender(json!({ "Response": {
+            "SendStatusSet": [
+                { "SerialNo": "2028:1", "PhoneNumber": "+8613800000000", "Code": "Ok", "Message": "send success" },
+                { "SerialNo": "", "PhoneNumber": "+8613800000001",
+                  "Code": "LimitExceeded.PhoneNumberThirtySecondLimit", "Message": "30 秒内限制发送 1 条" },
+                { "SerialNo": "", "PhoneNumber": "+85291234567",
+                  "Code": "InvalidParameterValue.IncorrectPhoneNumber", "Message": "号码格式错误" }
+            ],
+            "RequestId": "req"
+        }})).await;
+
+        let template = SmsTemplate { template_code: Some("order_shipped".into()), sign_name: None };
+        let results = sender
+            .send_sms(&["13800000000", "13800000001", "+85291234567"], &[], &template)
+            .await
+            .unwrap();
+        assert!(results[0].is_success());
+        assert_eq!(results[0].biz_id.as_deref(), Some("2028:1"));
+        assert!(!results[1].is_success() && results[1].retryable);
+        assert!(!results[2].is_success() && !results[2].retryable);
+        assert_eq!(results[2].phone, "+85291234567");
+
+        let body = server.requests()[0].json();
+        assert_eq!(body["TemplateId"], "1110");
+        assert_eq!(body["PhoneNumberSet"][2], "+85291234567");
+    }
+
+    #[tokio::test]
+    async fn request_error_is_surfaced() {
+        let (_server, sender) = build_mock_sender(json!({ "Response": {
+            "Error": { "Code": "AuthFailure.SignatureFailure", "Message": "The provided credentials could not be validated." },
+            "RequestId": "req-err"
+        }})).await;
+
+        let err = sender.send(&notification("13800000000", "[]")).await.unwrap_err();
+        match &err {
+            FlareError::Sms { provider, code, request_id, .. } => {
+                assert_eq!(*provider, "tencent");
+                assert_eq!(code, "AuthFailure.SignatureFailure");
+                assert_eq!(request_id.as_deref(), Some("req-err"));
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+        assert!(!err.is_retryable());
+    }
+
+    #[test]
+    fn template_params_must_be_array() {
+        assert_eq!(parse_template_params("").unwrap(), Vec::<String>::new());
+        assert!(matches!(parse_template_params(r#"{"code":"1"}"#), Err(FlareError::Config(_))));
+    }
+}
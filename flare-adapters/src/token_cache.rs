@@ -0,0 +1,53 @@
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use tokio::sync::Mutex;
+
+use flare_common::FlareResult;
+
+/// 提前刷新的余量，避免令牌在请求途中过期
+const REFRESH_MARGIN: Duration = Duration::from_secs(300);
+
+struct CachedToken {
+    value: String,
+    expires_at: Instant,
+}
+
+/// 平台 access token 缓存：过期前复用，过期或被平台拒绝后重新获取。
+/// 获取过程持有锁，并发请求只会触发一次刷新。
+#[derive(Default)]
+pub(crate) struct TokenCache {
+    inner: Mutex<Option<CachedToken>>,
+}
+
+impl TokenCache {
+    /// 返回缓存中的令牌；缓存为空或即将过期时调用 `fetch` 获取，
+    /// `fetch` 返回 `(令牌, 有效期秒数)`
+    pub async fn get_or_fetch<F, Fut>(&self, fetch: F) -> FlareResult<String>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = FlareResult<(String, u64)>>,
+    {
+        let mut guard = self.inner.lock().await;
+        if let Some(cached) = guard.as_ref() {
+            if Instant::now() < cached.expires_at {
+                return Ok(cached.value.clone());
+            }
+        }
+
+        let (value, expires_in) = fetch().await?;
+        let ttl = Duration::from_secs(expires_in);
+        // 有效期很短时不扣余量，至少用到一半
+        let ttl = if ttl > REFRESH_MARGIN * 2 { ttl - REFRESH_MARGIN } else { ttl / 2 };
+        *guard = Some(CachedToken {
+            value: value.clone(),
+            expires_at: Instant::now() + ttl,
+        });
+        Ok(value)
+    }
+
+    /// 丢弃缓存的令牌，下次调用 `get_or_fetch` 时重新获取
+    pub async fn invalidate(&self) {
+        *self.inner.lock().await = None;
+    }
+}
@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use flare_common::{FlareError, FlareResult, WebhookConfig};
+use flare_core::{render_json_template, Notification, Sender};
+use reqwest::{Client, Method};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::signing;
+
+/// 签名时间戳（毫秒）请求头
+pub const WEBHOOK_TIMESTAMP_HEADER: &str = "X-Flare-Timestamp";
+/// 签名请求头：base64(HMAC-SHA256(secret, timestamp + "\n" + body))
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "X-Flare-Signature";
+
+/// 计算 webhook 签名，接收方按同样方式计算后比对
+pub fn webhook_signature(secret: &str, timestamp: i64, body: &str) -> String {
+    signing::hmac_sha256_base64(secret.as_bytes(), format!("{}\n{}", timestamp, body).as_bytes())
+}
+
+/// 校验收到的 webhook 签名
+pub fn verify_webhook_signature(secret: &str, timestamp: i64, body: &str, signature: &str) -> bool {
+    let expected = webhook_signature(secret, timestamp, body);
+    // 逐字节比较全部内容，避免按前缀长度泄露信息
+    expected.len() == signature.len()
+        && expected
+            .bytes()
+            .zip(signature.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// Notification.body 的结构化形式；不含 data 字段时整个 body 即为数据
+#[derive(Debug, Default, Deserialize)]
+struct WebhookRequest {
+    #[serde(default)]
+    method: Option<String>,
+    #[serde(default)]
+    headers: HashMap<String, String>,
+    #[serde(default)]
+    template: Option<String>,
+    #[serde(default)]
+    timeout_secs: Option<u64>,
+    data: Value,
+}
+
+impl WebhookRequest {
+    fn parse(body: &str) -> FlareResult<Self> {
+        let value: Value = serde_json::from_str(body)
+            .map_err(|e| FlareError::Config(format!("webhook 消息体不是合法 JSON: {}", e)))?;
+        if value.get("data").is_some() {
+            serde_json::from_value(value)
+                .map_err(|e| FlareError::Config(format!("webhook 消息体格式错误: {}", e)))
+        } else {
+            Ok(Self { data: value, ..Default::default() })
+        }
+    }
+}
+
+/// 通用 HTTP webhook：Notification.to 为目标地址（为空时使用配置的默认地址），
+/// body 为 JSON 数据，可按模板渲染后发送
+pub struct WebhookSender {
+    client: Client,
+    config: WebhookConfig,
+}
+
+impl WebhookSender {
+    pub fn new(config: WebhookConfig) -> Self {
+        Self { client: Client::new(), config }
+    }
+}
+
+#[async_trait]
+impl Sender for WebhookSender {
+    async fn send(&self, notification: &Notification) -> FlareResult<()> {
+        let url = if notification.to.is_empty() {
+            self.config
+                .url
+                .clone()
+                .ok_or_else(|| FlareError::Config("未指定 webhook 地址".into()))?
+        } else {
+            notification.to.clone()
+        };
+
+        let request = WebhookRequest::parse(&notification.body)?;
+        let method_name = request.method.as_deref().unwrap_or(&self.config.method);
+        let method = Method::from_bytes(method_name.to_ascii_uppercase().as_bytes())
+            .map_err(|_| FlareError::Config(format!("无效的请求方法: {}", method_name)))?;
+
+        let body = match request.template.as_ref().or(self.config.body_template.as_ref()) {
+            Some(template) => render_json_template(template, &request.data)?,
+            None => request.data.to_string(),
+        };
+
+        let timeout = Duration::from_secs(request.timeout_secs.unwrap_or(self.config.timeout_secs));
+        let mut builder = self
+            .client
+            .request(method, &url)
+            .timeout(timeout)
+            .header("Content-Type", "application/json");
+        // 消息中的请求头覆盖配置中的同名请求头（不区分大小写）
+        let mut headers: HashMap<String, &String> = HashMap::new();
+        for (name, value) in self.config.headers.iter().chain(request.headers.iter()) {
+            headers.insert(name.to_ascii_lowercase(), value);
+        }
+        for (name, value) in headers {
+            builder = builder.header(name, value.as_str());
+        }
+        if let Some(secret) = &self.config.secret {
+            let ts = chrono::Utc::now().timestamp_millis();
+            builder = builder
+                .header(WEBHOOK_TIMESTAMP_HEADER, ts.to_string())
+                .header(WEBHOOK_SIGNATURE_HEADER, webhook_signature(secret, ts, &body));
+        }
+
+        let response = builder.body(body).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(FlareError::HttpStatus { status: status.as_u16(), body: text });
+        }
+
+        tracing::debug!(url = %url, status = status.as_u16(), "webhook 发送成功");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::{MockResponse, MockServer};
+    use flare_common::ChannelType;
+    use serde_json::json;
+
+    fn config(url: Option<String>) -> WebhookConfig {
+        WebhookConfig {
+            url,
+            method: "POST".into(),
+            headers: [("X-Source".to_string(), "flare".to_string())].into(),
+            body_template: None,
+            timeout_secs: 5,
+            secret: None,
+        }
+    }
+
+    fn notification(to: &str, body: Value) -> Notification {
+        Notification {
+            from: String::new(),
+            to: to.into(),
+            subject: String::new(),
+            body: body.to_string(),
+            channel: ChannelType::Webhook,
+        }
+    }
+
+    #[test]
+    fn signature_round_trip() {
+        let sig = webhook_signature("s3cret", 1700000000000, r#"{"a":1}"#);
+        assert!(verify_webhook_signature("s3cret", 1700000000000, r#"{"a":1}"#, &sig));
+        assert!(!verify_webhook_signature("s3cret", 1700000000001, r#"{"a":1}"#, &sig));
+        assert!(!verify_webhook_signature("other", 1700000000000, r#"{"a":1}"#, &sig));
+    }
+
+    #[tokio::test]
+    async fn sends_data_to_default_url_with_signature() {
+        let server = MockServer::start().await;
+        server.respond_json("/hook", json!({ "ok": true }));
+        let mut cfg = config(Some(server.url("/hook")));
+        cfg.secret = Some("s3cret".into());
+        let sender = WebhookSender::new(cfg);
+
+        sender.send(&notification("", json!({ "event": "order.paid", "id": 42 }))).await.unwrap();
+
+        let req = &server.requests_to("/hook")[0];
+        assert_eq!(req.method, "POST");
+        assert_eq!(req.json(), json!({ "event": "order.paid", "id": 42 }));
+        assert_eq!(req.header("x-source"), Some("flare"));
+        let ts: i64 = req.header("x-flare-timestamp").unwrap().parse().unwrap();
+        let body = String::from_utf8(req.body.clone()).unwrap();
+        assert!(verify_webhook_signature("s3cret", ts, &body, req.header("x-flare-signature").unwrap()));
+    }
+
+    #[tokio::test]
+    async fn message_overrides_url_method_headers_and_template() {
+        let server = MockServer::start().await;
+        server.respond_json("/override", json!({}));
+        let sender = WebhookSender::new(config(Some(server.url("/default"))));
+
+        let body = json!({
+            "method": "put",
+            "headers": { "X-Source": "order-service", "Authorization": "Bearer t" },
+            "template": r#"{"title": "订单 {{id}}", "note": "{{note}}", "items": {{json items}}}"#,
+            "data": { "id": 42, "note": "含 \"引号\"", "items": [1, 2] }
+        });
+        sender.send(&notification(&server.url("/override"), body)).await.unwrap();
+
+        assert!(server.requests_to("/default").is_empty());
+        let req = &server.requests_to("/override")[0];
+        assert_eq!(req.method, "PUT");
+        assert_eq!(req.header("x-source"), Some("order-service"));
+        assert_eq!(req.header("authorization"), Some("Bearer t"));
+        assert_eq!(req.json(), json!({ "title": "订单 42", "note": "含 \"引号\"", "items": [1, 2] }));
+        assert!(req.header("x-flare-signature").is_none());
+    }
+
+    #[tokio::test]
+    async fn non_success_status_is_error() {
+        let server = MockServer::start().await;
+        server.respond("/hook", MockResponse::json(503, json!({ "error": "busy" })));
+        let sender = WebhookSender::new(config(None));
+
+        let err = sender.send(&notification(&server.url("/hook"), json!({ "a": 1 }))).await.unwrap_err();
+        assert!(matches!(err, FlareError::HttpStatus { status: 503, .. }));
+        assert!(err.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn missing_url_is_config_error() {
+        let sender = WebhookSender::new(config(None));
+        let err = sender.send(&notification("", json!({ "a": 1 }))).await.unwrap_err();
+        assert!(matches!(err, FlareError::Config(_)));
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+
+use axum::async_trait;
+use axum::extract::{FromRequestParts, Path, Query};
+use axum::http::request::Parts;
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+struct Claims {
+    sub: String,
+}
+
+/// 校验站内信接口的访问令牌：HS256 签名的 JWT，`sub` 为用户 ID，必须带 `exp`
+pub struct TokenVerifier {
+    key: DecodingKey,
+    validation: Validation,
+}
+
+impl TokenVerifier {
+    pub fn hs256(secret: &[u8]) -> Self {
+        Self {
+            key: DecodingKey::from_secret(secret),
+            validation: Validation::new(Algorithm::HS256),
+        }
+    }
+
+    /// 校验通过时返回用户 ID
+    pub fn verify(&self, token: &str) -> Option<String> {
+        jsonwebtoken::decode::<Claims>(token, &self.key, &self.validation)
+            .ok()
+            .map(|data| data.claims.sub)
+            .filter(|sub| !sub.is_empty())
+    }
+}
+
+/// 认证失败：缺少或无效的令牌返回 401，访问他人的站内信返回 403
+#[derive(Debug)]
+pub enum AuthError {
+    Unauthorized,
+    Forbidden,
+}
+
+impl IntoResponse for AuthError {
+    fn into_response(self) -> Response {
+        let (status, error) = match self {
+            AuthError::Unauthorized => (StatusCode::UNAUTHORIZED, "unauthorized"),
+            AuthError::Forbidden => (StatusCode::FORBIDDEN, "forbidden"),
+        };
+        (status, Json(json!({ "error": error }))).into_response()
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct AccessTokenQuery {
+    #[serde(default)]
+    access_token: Option<String>,
+}
+
+/// 已认证的用户 ID。令牌取自 `Authorization: Bearer <token>`；
+/// 浏览器的 EventSource、WebSocket 无法设置请求头，也可用 `access_token` 查询参数。
+/// 路径中带 `:user_id` 时必须与令牌中的用户一致
+pub struct AuthUser(pub String);
+
+#[async_trait]
+impl FromRequestParts<AppState> for AuthUser {
+    type Rejection = AuthError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let bearer = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .map(|v| v.trim().to_string());
+        let token = match bearer {
+            Some(token) => token,
+            None => Query::<AccessTokenQuery>::from_request_parts(parts, state)
+                .await
+                .ok()
+                .and_then(|Query(query)| query.access_token)
+                .ok_or(AuthError::Unauthorized)?,
+        };
+        let user_id = state.token_verifier.verify(&token).ok_or(AuthError::Unauthorized)?;
+
+        let params = Option::<Path<HashMap<String, String>>>::from_request_parts(parts, state)
+            .await
+            .unwrap_or_default();
+        if let Some(Path(params)) = params {
+            if params.get("user_id").is_some_and(|id| *id != user_id) {
+                return Err(AuthError::Forbidden);
+            }
+        }
+        Ok(AuthUser(user_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::body::Body;
+    use axum::http::Request;
+    use flare_storage::{MemoryDeliveryStore, MemoryInboxStore};
+    use jsonwebtoken::{EncodingKey, Header};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::{router, PushHub};
+
+    const SECRET: &[u8] = b"test-secret";
+
+    fn token_for(user_id: &str) -> String {
+        let exp = chrono::Utc::now().timestamp() + 600;
+        let claims = json!({ "sub": user_id, "exp": exp });
+        jsonwebtoken::encode(&Header::default(), &claims, &EncodingKey::from_secret(SECRET)).unwrap()
+    }
+
+    fn state() -> AppState {
+        AppState {
+            delivery_store: Arc::new(MemoryDeliveryStore::new()),
+            inbox_store: Arc::new(MemoryInboxStore::new()),
+            push_hub: Arc::new(PushHub::new()),
+            sms_report_token: None,
+            token_verifier: Arc::new(TokenVerifier::hs256(SECRET)),
+        }
+    }
+
+    async fn status(uri: &str, token: Option<&str>) -> StatusCode {
+        let mut request = Request::get(uri);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        let response = router(state()).oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+        response.status()
+    }
+
+    #[tokio::test]
+    async fn inbox_requires_token_of_the_same_user() {
+        let token = token_for("u1");
+        assert_eq!(status("/users/u1/inbox/unread", Some(&token)).await, StatusCode::OK);
+        assert_eq!(status(&format!("/users/u1/inbox?access_token={}", token), None).await, StatusCode::OK);
+
+        // 他人的站内信
+        assert_eq!(status("/users/u2/inbox/unread", Some(&token)).await, StatusCode::FORBIDDEN);
+        assert_eq!(status("/users/u2/inbox", Some(&token)).await, StatusCode::FORBIDDEN);
+        // 缺少、伪造的令牌
+        assert_eq!(status("/users/u1/inbox/unread", None).await, StatusCode::UNAUTHORIZED);
+        let forged = jsonwebtoken::encode(
+            &Header::default(),
+            &json!({ "sub": "u1", "exp": chrono::Utc::now().timestamp() + 600 }),
+            &EncodingKey::from_secret(b"other-secret"),
+        )
+        .unwrap();
+        assert_eq!(status("/users/u1/inbox/unread", Some(&forged)).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn push_sessions_require_token_of_the_same_user() {
+        let token = token_for("u1");
+        assert_eq!(status(&format!("/users/u1/inbox/events?access_token={}", token), None).await, StatusCode::OK);
+
+        assert_eq!(status(&format!("/users/u2/inbox/events?access_token={}", token), None).await, StatusCode::FORBIDDEN);
+        assert_eq!(status("/users/u2/inbox/ws", Some(&token)).await, StatusCode::FORBIDDEN);
+        assert_eq!(status("/users/u1/inbox/events", None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status("/users/u1/inbox/ws", None).await, StatusCode::UNAUTHORIZED);
+    }
+}
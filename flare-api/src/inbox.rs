@@ -0,0 +1,139 @@
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use flare_storage::{InboxMessage, InboxQuery, UnreadCounts};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{AppState, AuthUser};
+
+/// 存储访问失败统一返回 500
+pub struct InboxError(anyhow::Error);
+
+impl IntoResponse for InboxError {
+    fn into_response(self) -> Response {
+        tracing::error!("站内信操作失败: {:#}", self.0);
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "internal error" }))).into_response()
+    }
+}
+
+impl From<anyhow::Error> for InboxError {
+    fn from(e: anyhow::Error) -> Self {
+        Self(e)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MarkReadRequest {
+    pub ids: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct MarkAllReadRequest {
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+/// `GET /users/:user_id/inbox?category=&unread_only=&limit=&offset=`
+pub async fn list_inbox(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Query(query): Query<InboxQuery>,
+) -> Result<Json<Vec<InboxMessage>>, InboxError> {
+    Ok(Json(state.inbox_store.list(&user_id, &query).await?))
+}
+
+/// `GET /users/:user_id/inbox/unread`：未读数角标
+pub async fn unread_counts(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+) -> Result<Json<UnreadCounts>, InboxError> {
+    Ok(Json(state.inbox_store.unread_counts(&user_id).await?))
+}
+
+/// `POST /users/:user_id/inbox/read`，body 为 `{"ids": [...]}`
+pub async fn mark_read(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Json(req): Json<MarkReadRequest>,
+) -> Result<Json<serde_json::Value>, InboxError> {
+    let updated = state.inbox_store.mark_read(&user_id, &req.ids).await?;
+    Ok(Json(json!({ "updated": updated })))
+}
+
+/// `POST /users/:user_id/inbox/read-all`，可选 body `{"category": "..."}`
+pub async fn mark_all_read(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    req: Option<Json<MarkAllReadRequest>>,
+) -> Result<Json<serde_json::Value>, InboxError> {
+    let Json(req) = req.unwrap_or_default();
+    let updated = state.inbox_store.mark_all_read(&user_id, req.category.as_deref()).await?;
+    Ok(Json(json!({ "updated": updated })))
+}
+
+/// `DELETE /users/:user_id/inbox/:id`
+pub async fn delete_message(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Path((_, id)): Path<(String, String)>,
+) -> Result<StatusCode, InboxError> {
+    if state.inbox_store.delete(&user_id, &id).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Ok(StatusCode::NOT_FOUND)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use flare_storage::{InboxStore, MemoryDeliveryStore, MemoryInboxStore};
+
+    use super::*;
+    use crate::{PushHub, TokenVerifier};
+
+    async fn state_with(messages: &[InboxMessage]) -> AppState {
+        let inbox_store = Arc::new(MemoryInboxStore::new());
+        inbox_store.insert(messages).await.unwrap();
+        AppState {
+            delivery_store: Arc::new(MemoryDeliveryStore::new()),
+            inbox_store,
+            push_hub: Arc::new(PushHub::new()),
+            sms_report_token: None,
+            token_verifier: Arc::new(TokenVerifier::hs256(b"test-secret")),
+        }
+    }
+
+    #[tokio::test]
+    async fn inbox_endpoints_manage_read_state() {
+        let order = InboxMessage::new("u1", "order", "订单已发货", "");
+        let system = InboxMessage::new("u1", "system", "系统维护", "");
+        let state = state_with(&[order.clone(), system.clone()]).await;
+        let user = || AuthUser("u1".to_string());
+
+        let Json(counts) = unread_counts(State(state.clone()), user()).await.ok().unwrap();
+        assert_eq!(counts.total, 2);
+
+        let req = MarkReadRequest { ids: vec![order.id.clone()] };
+        let Json(body) = mark_read(State(state.clone()), user(), Json(req)).await.ok().unwrap();
+        assert_eq!(body["updated"], 1);
+
+        let query = InboxQuery { unread_only: true, ..Default::default() };
+        let Json(unread) = list_inbox(State(state.clone()), user(), Query(query)).await.ok().unwrap();
+        assert_eq!(unread.len(), 1);
+        assert_eq!(unread[0].id, system.id);
+
+        let Json(body) = mark_all_read(State(state.clone()), user(), None).await.ok().unwrap();
+        assert_eq!(body["updated"], 1);
+
+        let path = |id: &str| Path(("u1".to_string(), id.to_string()));
+        let status = delete_message(State(state.clone()), user(), path(&order.id)).await.ok().unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let other = AuthUser("u2".to_string());
+        let status = delete_message(State(state.clone()), other, path(&system.id)).await.ok().unwrap();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
@@ -1,3 +1,46 @@
+mod auth;
+mod inbox;
+mod push;
+mod sms_receipt;
+
+use std::sync::Arc;
+
+use axum::routing::{delete, get, post};
+use axum::Router;
+use flare_storage::{DeliveryStore, InboxStore};
+use tower_http::trace::TraceLayer;
+
+pub use auth::*;
+pub use inbox::*;
+pub use push::*;
+pub use sms_receipt::*;
+
+/// 各路由共享的状态
+#[derive(Clone)]
+pub struct AppState {
+    pub delivery_store: Arc<dyn DeliveryStore>,
+    pub inbox_store: Arc<dyn InboxStore>,
+    pub push_hub: Arc<PushHub>,
+    /// 短信状态报告推送地址上的 `token` 参数，未配置时拒绝所有推送
+    pub sms_report_token: Option<String>,
+    /// 校验站内信接口的访问令牌
+    pub token_verifier: Arc<TokenVerifier>,
+}
+
+pub fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/callbacks/aliyun/sms/report", post(aliyun_sms_report))
+        .route("/users/:user_id/inbox", get(list_inbox))
+        .route("/users/:user_id/inbox/unread", get(unread_counts))
+        .route("/users/:user_id/inbox/events", get(inbox_events))
+        .route("/users/:user_id/inbox/ws", get(inbox_ws))
+        .route("/users/:user_id/inbox/read", post(mark_read))
+        .route("/users/:user_id/inbox/read-all", post(mark_all_read))
+        .route("/users/:user_id/inbox/:id", delete(delete_message))
+        .layer(TraceLayer::new_for_http())
+        .with_state(state)
+}
+
 pub fn add(left: u64, right: u64) -> u64 {
     left + right
 }
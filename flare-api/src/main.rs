@@ -0,0 +1,57 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+
+use flare_api::{router, run_push_subscriber, AppState, PushHub, TokenVerifier};
+use flare_common::{DatabaseConfig, RedisConfig};
+use flare_core::init_logger;
+use flare_storage::{connect_inbox_store, PgDeliveryStore};
+use tracing::{info, warn};
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenvy::dotenv().ok();
+    init_logger().await;
+
+    let db_cfg = DatabaseConfig::from_env()?;
+    let delivery_store = PgDeliveryStore::connect(&db_cfg.url).await?;
+    delivery_store.migrate().await?;
+    // 配置 REDIS_URL 后缓存未读数
+    let redis_url = RedisConfig::from_env().ok().map(|cfg| cfg.url);
+    let inbox_store = connect_inbox_store(&db_cfg.url, redis_url.as_deref()).await?;
+
+    // 实时推送依赖 Redis pub/sub 在多实例间分发新消息
+    let push_hub = Arc::new(PushHub::new());
+    match &redis_url {
+        Some(url) => {
+            tokio::spawn(run_push_subscriber(push_hub.clone(), url.clone()));
+        }
+        None => warn!("未配置 REDIS_URL，站内信实时推送只补发重连前的消息"),
+    }
+
+    // 短信状态报告推送地址需带上 ?token=，防止伪造回执
+    let sms_report_token = std::env::var("SMS_REPORT_TOKEN").ok().filter(|v| !v.is_empty());
+    if sms_report_token.is_none() {
+        warn!("未配置 SMS_REPORT_TOKEN，拒绝所有短信状态报告推送");
+    }
+
+    // 站内信接口要求 HS256 签名的 JWT，sub 为用户 ID
+    let jwt_secret = std::env::var("API_JWT_SECRET")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .context("缺少 API_JWT_SECRET 配置")?;
+
+    let state = AppState {
+        delivery_store: Arc::new(delivery_store),
+        inbox_store,
+        push_hub,
+        sms_report_token,
+        token_verifier: Arc::new(TokenVerifier::hs256(jwt_secret.as_bytes())),
+    };
+
+    let addr = std::env::var("API_ADDR").unwrap_or_else(|_| "0.0.0.0:8080".to_string());
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    info!("API listening on {}", addr);
+    axum::serve(listener, router(state)).await?;
+    Ok(())
+}
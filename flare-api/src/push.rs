@@ -0,0 +1,243 @@
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::extract::ws::{Message as WsMessage, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, State};
+use axum::http::HeaderMap;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::Response;
+use futures_util::stream::{self, BoxStream};
+use futures_util::{Stream, StreamExt};
+use flare_storage::{subscribe_inbox, InboxMessage, InboxQuery};
+use serde::Deserialize;
+use tokio::sync::broadcast;
+
+use crate::{AppState, AuthUser, InboxError};
+
+/// 单个用户未发出的实时消息上限，超出后断开会话，由客户端重连补发
+const SESSION_BUFFER: usize = 64;
+
+/// 重连时最多补发的消息数，更早的消息由客户端刷新列表获取
+const REPLAY_LIMIT: i64 = InboxQuery::MAX_LIMIT;
+
+/// Redis 订阅断开后的重连间隔
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(3);
+
+/// WebSocket 心跳间隔，防止空闲连接被代理断开
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 本实例上的推送会话，按用户分发新站内信
+#[derive(Default)]
+pub struct PushHub {
+    users: Mutex<HashMap<String, broadcast::Sender<Arc<InboxMessage>>>>,
+}
+
+impl PushHub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe(&self, user_id: &str) -> broadcast::Receiver<Arc<InboxMessage>> {
+        let mut users = self.users.lock().unwrap();
+        // 顺带清理会话已全部断开的用户
+        users.retain(|_, tx| tx.receiver_count() > 0);
+        users
+            .entry(user_id.to_string())
+            .or_insert_with(|| broadcast::channel(SESSION_BUFFER).0)
+            .subscribe()
+    }
+
+    /// 分发给该用户在本实例上的会话，返回会话数
+    pub fn publish(&self, message: InboxMessage) -> usize {
+        let users = self.users.lock().unwrap();
+        match users.get(&message.user_id) {
+            Some(tx) => tx.send(Arc::new(message)).unwrap_or(0),
+            None => 0,
+        }
+    }
+}
+
+/// 订阅 Redis 中的新站内信并分发到本实例的会话，断开后自动重连
+pub async fn run_push_subscriber(hub: Arc<PushHub>, redis_url: String) {
+    loop {
+        match subscribe_inbox(&redis_url).await {
+            Ok(messages) => {
+                tracing::info!("已订阅站内信推送");
+                let mut messages = std::pin::pin!(messages);
+                while let Some(message) = messages.next().await {
+                    hub.publish(message);
+                }
+                tracing::warn!("站内信推送订阅断开，{:?} 后重连", RESUBSCRIBE_DELAY);
+            }
+            Err(e) => tracing::warn!("订阅站内信推送失败，{:?} 后重试: {:#}", RESUBSCRIBE_DELAY, e),
+        }
+        tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+    }
+}
+
+/// 会话消息流：先补发 last_event_id 之后的消息，再推送实时消息。
+/// 先订阅再补发，补发期间到达的消息按 ID 去重；实时消息积压溢出时结束，
+/// 客户端携带最后收到的消息 ID 重连即可补齐
+async fn session_messages(
+    state: &AppState,
+    user_id: &str,
+    last_event_id: Option<&str>,
+) -> Result<BoxStream<'static, Arc<InboxMessage>>, InboxError> {
+    let rx = state.push_hub.subscribe(user_id);
+    let replay = match last_event_id {
+        Some(last_id) => state.inbox_store.list_after(user_id, last_id, REPLAY_LIMIT).await?,
+        None => Vec::new(),
+    };
+    let replayed: HashSet<String> = replay.iter().map(|m| m.id.clone()).collect();
+
+    let live = stream::unfold(rx, |mut rx| async move {
+        match rx.recv().await {
+            Ok(message) => Some((message, rx)),
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                tracing::warn!("推送会话积压，丢弃 {} 条后断开", skipped);
+                None
+            }
+            Err(broadcast::error::RecvError::Closed) => None,
+        }
+    })
+    .filter(move |m| std::future::ready(!replayed.contains(&m.id)));
+
+    Ok(stream::iter(replay.into_iter().map(Arc::new)).chain(live).boxed())
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ResumeQuery {
+    /// 最后收到的消息 ID；SSE 也可通过 Last-Event-ID 头传递
+    #[serde(default)]
+    pub last_event_id: Option<String>,
+}
+
+/// `GET /users/:user_id/inbox/events`：SSE 推送，事件名为 message，
+/// 事件 ID 为消息 ID，浏览器重连时自动携带 Last-Event-ID 补发断线期间的消息。
+/// EventSource 无法设置请求头，令牌可通过 `access_token` 参数传递
+pub async fn inbox_events(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    Query(query): Query<ResumeQuery>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, InboxError> {
+    let last_event_id = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+        .or(query.last_event_id);
+    let messages = session_messages(&state, &user_id, last_event_id.as_deref()).await?;
+
+    let events = messages.map(|message| {
+        let data = serde_json::to_string(&*message).unwrap_or_default();
+        Ok(Event::default().id(&message.id).event("message").data(data))
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+/// `GET /users/:user_id/inbox/ws?last_event_id=&access_token=`：WebSocket 推送，每条消息一个 JSON 文本帧。
+/// 先校验令牌再升级连接
+pub async fn inbox_ws(
+    State(state): State<AppState>,
+    AuthUser(user_id): AuthUser,
+    ws: WebSocketUpgrade,
+    Query(query): Query<ResumeQuery>,
+) -> Result<Response, InboxError> {
+    let messages = session_messages(&state, &user_id, query.last_event_id.as_deref()).await?;
+    Ok(ws.on_upgrade(move |socket| forward_to_socket(socket, messages)))
+}
+
+async fn forward_to_socket(mut socket: WebSocket, mut messages: BoxStream<'static, Arc<InboxMessage>>) {
+    let mut ping = tokio::time::interval(PING_INTERVAL);
+    ping.tick().await;
+    loop {
+        tokio::select! {
+            message = messages.next() => {
+                let Some(message) = message else { break };
+                let text = serde_json::to_string(&*message).unwrap_or_default();
+                if socket.send(WsMessage::Text(text)).await.is_err() {
+                    return;
+                }
+            }
+            // 客户端只需接收；Ping 由 axum 自动回复
+            incoming = socket.recv() => match incoming {
+                Some(Ok(WsMessage::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+            _ = ping.tick() => {
+                if socket.send(WsMessage::Ping(Vec::new())).await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+    // 积压溢出，关闭连接让客户端重连补发
+    let _ = socket.send(WsMessage::Close(None)).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use flare_storage::{InboxStore, MemoryDeliveryStore, MemoryInboxStore};
+
+    use super::*;
+    use crate::TokenVerifier;
+
+    #[test]
+    fn hub_routes_by_user() {
+        let hub = PushHub::new();
+        let mut u1 = hub.subscribe("u1");
+        let _u2 = hub.subscribe("u2");
+
+        assert_eq!(hub.publish(InboxMessage::new("u1", "order", "已发货", "")), 1);
+        assert_eq!(hub.publish(InboxMessage::new("u3", "order", "已发货", "")), 0);
+        assert_eq!(u1.try_recv().unwrap().title, "已发货");
+
+        drop(u1);
+        hub.subscribe("u2");
+        assert_eq!(hub.users.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn session_replays_after_last_event_then_streams_live() {
+        let inbox_store = Arc::new(MemoryInboxStore::new());
+        let state = AppState {
+            delivery_store: Arc::new(MemoryDeliveryStore::new()),
+            inbox_store: inbox_store.clone(),
+            push_hub: Arc::new(PushHub::new()),
+            sms_report_token: None,
+            token_verifier: Arc::new(TokenVerifier::hs256(b"test-secret")),
+        };
+        let mut seen = InboxMessage::new("u1", "order", "已读过", "");
+        seen.created_at -= chrono::Duration::seconds(10);
+        let missed = InboxMessage::new("u1", "order", "断线期间", "");
+        inbox_store.insert(&[seen.clone(), missed.clone()]).await.unwrap();
+
+        let mut messages = session_messages(&state, "u1", Some(&seen.id)).await.ok().unwrap();
+        // 补发期间同时到达的消息不重复推送
+        state.push_hub.publish(missed.clone());
+        let live = InboxMessage::new("u1", "order", "实时", "");
+        state.push_hub.publish(live.clone());
+
+        assert_eq!(messages.next().await.unwrap().id, missed.id);
+        assert_eq!(messages.next().await.unwrap().id, live.id);
+    }
+
+    #[tokio::test]
+    async fn lagging_session_ends() {
+        let state = AppState {
+            delivery_store: Arc::new(MemoryDeliveryStore::new()),
+            inbox_store: Arc::new(MemoryInboxStore::new()),
+            push_hub: Arc::new(PushHub::new()),
+            sms_report_token: None,
+            token_verifier: Arc::new(TokenVerifier::hs256(b"test-secret")),
+        };
+        let mut messages = session_messages(&state, "u1", None).await.ok().unwrap();
+        for _ in 0..=SESSION_BUFFER {
+            state.push_hub.publish(InboxMessage::new("u1", "order", "刷屏", ""));
+        }
+
+        assert!(messages.next().await.is_none());
+    }
+}
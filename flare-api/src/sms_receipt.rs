@@ -0,0 +1,139 @@
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::Json;
+use flare_adapters::SmsReport;
+use flare_storage::DeliveryReceipt;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::AppState;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ReportAuth {
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+/// 按字节等长比较，避免通过响应时间逐位猜出 token
+fn token_matches(expected: &str, given: &str) -> bool {
+    expected.len() == given.len()
+        && expected.bytes().zip(given.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// 阿里云短信状态报告 HTTP 批量推送，地址需带上 `?token=<SMS_REPORT_TOKEN>`。
+/// 返回 `{"code":0}` 表示已接收，其他响应阿里云会稍后重推
+pub async fn aliyun_sms_report(
+    State(state): State<AppState>,
+    Query(auth): Query<ReportAuth>,
+    Json(reports): Json<Vec<SmsReport>>,
+) -> (StatusCode, Json<Value>) {
+    let authorized = match (&state.sms_report_token, &auth.token) {
+        (Some(expected), Some(given)) => token_matches(expected, given),
+        _ => false,
+    };
+    if !authorized {
+        tracing::warn!("拒绝未授权的短信状态报告推送，共 {} 条", reports.len());
+        return (StatusCode::UNAUTHORIZED, Json(json!({ "code": 1, "msg": "token 无效" })));
+    }
+
+    for report in &reports {
+        let receipt = DeliveryReceipt {
+            biz_id: report.biz_id.clone(),
+            phone: report.phone_number.clone(),
+            status: report.delivery_status(),
+            err_code: report.err_code.clone(),
+            err_msg: report.err_msg.clone(),
+        };
+        match state.delivery_store.apply_receipt(&receipt).await {
+            Ok(true) => tracing::info!(
+                "短信回执 biz_id={} phone={} status={}",
+                receipt.biz_id,
+                receipt.phone,
+                <&str>::from(receipt.status)
+            ),
+            // 未知或已是终态的记录无需重推
+            Ok(false) => tracing::debug!("忽略短信回执 biz_id={} phone={}", receipt.biz_id, receipt.phone),
+            Err(e) => {
+                tracing::error!("保存短信回执失败 biz_id={}: {:#}", receipt.biz_id, e);
+                return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "code": 1, "msg": "保存失败" })));
+            }
+        }
+    }
+    (StatusCode::OK, Json(json!({ "code": 0, "msg": "成功" })))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use flare_common::DeliveryStatus;
+    use flare_storage::{DeliveryRecord, DeliveryStore, MemoryDeliveryStore, MemoryInboxStore};
+
+    use super::*;
+    use crate::{PushHub, TokenVerifier};
+
+    async fn state_with_sent(phones: &[&str]) -> (AppState, Arc<MemoryDeliveryStore>) {
+        let store = Arc::new(MemoryDeliveryStore::new());
+        for phone in phones {
+            store.record_sent(&DeliveryRecord::sent("msg-1", "aliyun", phone, "biz-1")).await.unwrap();
+        }
+        let state = AppState {
+            delivery_store: store.clone(),
+            inbox_store: Arc::new(MemoryInboxStore::new()),
+            push_hub: Arc::new(PushHub::new()),
+            sms_report_token: Some("report-secret".into()),
+            token_verifier: Arc::new(TokenVerifier::hs256(b"test-secret")),
+        };
+        (state, store)
+    }
+
+    fn token(value: &str) -> Query<ReportAuth> {
+        Query(ReportAuth { token: Some(value.into()) })
+    }
+
+    #[tokio::test]
+    async fn report_updates_delivery_state() {
+        let (state, store) = state_with_sent(&["13800000000", "13800000001"]).await;
+
+        let reports = serde_json::from_value(json!([
+            { "phone_number": "13800000000", "success": true, "biz_id": "biz-1",
+              "err_code": "DELIVERED", "err_msg": "用户接收成功", "sms_size": "1" },
+            { "phone_number": "13800000001", "success": false, "biz_id": "biz-1",
+              "err_code": "MK:0001", "err_msg": "用户停机" },
+            { "phone_number": "13900000000", "success": true, "biz_id": "unknown" }
+        ]))
+        .unwrap();
+        let (status, Json(body)) = aliyun_sms_report(State(state), token("report-secret"), Json(reports)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["code"], 0);
+
+        let delivered = store.get("biz-1", "13800000000").await.unwrap().unwrap();
+        assert_eq!(delivered.status, DeliveryStatus::Delivered);
+        let failed = store.get("biz-1", "13800000001").await.unwrap().unwrap();
+        assert_eq!(failed.status, DeliveryStatus::Failed);
+        assert_eq!(failed.err_code.as_deref(), Some("MK:0001"));
+    }
+
+    #[tokio::test]
+    async fn report_without_valid_token_is_rejected() {
+        let (state, store) = state_with_sent(&["13800000000"]).await;
+        let forged = || {
+            serde_json::from_value::<Vec<SmsReport>>(json!([
+                { "phone_number": "13800000000", "success": true, "biz_id": "biz-1", "err_code": "DELIVERED" }
+            ]))
+            .unwrap()
+        };
+
+        for auth in [Query(ReportAuth::default()), token("guess"), token("report-secret-")] {
+            let (status, _) = aliyun_sms_report(State(state.clone()), auth, Json(forged())).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+        }
+        // 未配置 token 时拒绝所有推送
+        let unconfigured = AppState { sms_report_token: None, ..state };
+        let (status, _) = aliyun_sms_report(State(unconfigured), token("report-secret"), Json(forged())).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let record = store.get("biz-1", "13800000000").await.unwrap().unwrap();
+        assert_eq!(record.status, DeliveryStatus::Sent);
+    }
+}
@@ -8,6 +8,11 @@ pub enum ChannelType {
     ImFeishu,
     ImDingding,
     ImWechat,
+    ImSlack,
+    ImTelegram,
+    ImTeams,
+    ImDiscord,
     Push,
     SiteMessage,
+    Webhook,
 }
\ No newline at end of file
@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+/// 消息投递状态：提交成功后为 Sent，收到运营商回执后变为终态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DeliveryStatus {
+    /// 已提交服务商，等待回执
+    Sent,
+    /// 已送达终端
+    Delivered,
+    /// 投递失败，原因见 err_code
+    Failed,
+}
+
+impl DeliveryStatus {
+    /// 是否为终态，终态不再被回执覆盖
+    pub fn is_final(self) -> bool {
+        !matches!(self, DeliveryStatus::Sent)
+    }
+
+    /// 从存储中的字符串还原
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "SENT" => Some(DeliveryStatus::Sent),
+            "DELIVERED" => Some(DeliveryStatus::Delivered),
+            "FAILED" => Some(DeliveryStatus::Failed),
+            _ => None,
+        }
+    }
+}
+
+impl From<DeliveryStatus> for &'static str {
+    fn from(s: DeliveryStatus) -> Self {
+        match s {
+            DeliveryStatus::Sent => "SENT",
+            DeliveryStatus::Delivered => "DELIVERED",
+            DeliveryStatus::Failed => "FAILED",
+        }
+    }
+}
@@ -84,4 +84,173 @@ impl From<DingdingMessageType> for &'static str {
             DingdingMessageType::Video => "video",
         }
     }
-}
\ No newline at end of file
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WechatMessageType {
+    /// 文本消息
+    Text,
+    /// Markdown 消息
+    Markdown,
+    /// 图片（base64 + md5）
+    Image,
+    /// 图文消息
+    News,
+    /// 文件
+    File,
+    /// 模板卡片
+    TemplateCard,
+}
+
+impl From<WechatMessageType> for &'static str {
+    fn from(t: WechatMessageType) -> Self {
+        match t {
+            WechatMessageType::Text => "text",
+            WechatMessageType::Markdown => "markdown",
+            WechatMessageType::Image => "image",
+            WechatMessageType::News => "news",
+            WechatMessageType::File => "file",
+            WechatMessageType::TemplateCard => "template_card",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TeamsMessageType {
+    /// 文本（包装为只含 TextBlock 的 Adaptive Card）
+    Text,
+    /// Adaptive Card
+    AdaptiveCard,
+    /// 旧版 Office 365 Connector 的 MessageCard
+    MessageCard,
+}
+
+impl From<TeamsMessageType> for &'static str {
+    fn from(t: TeamsMessageType) -> Self {
+        match t {
+            TeamsMessageType::Text => "text",
+            TeamsMessageType::AdaptiveCard => "adaptive_card",
+            TeamsMessageType::MessageCard => "message_card",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscordMessageType {
+    /// 文本（content，支持 Discord Markdown）
+    Text,
+    /// Embed 卡片，可附带 content
+    Embed,
+}
+
+impl From<DiscordMessageType> for &'static str {
+    fn from(t: DiscordMessageType) -> Self {
+        match t {
+            DiscordMessageType::Text => "text",
+            DiscordMessageType::Embed => "embed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TelegramMessageType {
+    /// 文本消息（sendMessage）
+    Text,
+    /// 图片（sendPhoto）
+    Photo,
+    /// 文件（sendDocument）
+    Document,
+}
+
+impl From<TelegramMessageType> for &'static str {
+    fn from(t: TelegramMessageType) -> Self {
+        match t {
+            TelegramMessageType::Text => "text",
+            TelegramMessageType::Photo => "photo",
+            TelegramMessageType::Document => "document",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TelegramParseMode {
+    #[serde(rename = "MarkdownV2")]
+    MarkdownV2,
+    #[serde(rename = "HTML")]
+    Html,
+}
+
+impl TelegramParseMode {
+    /// 不区分大小写解析 MarkdownV2 / HTML
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "markdownv2" => Some(Self::MarkdownV2),
+            "html" => Some(Self::Html),
+            _ => None,
+        }
+    }
+}
+
+impl From<TelegramParseMode> for &'static str {
+    fn from(t: TelegramParseMode) -> Self {
+        match t {
+            TelegramParseMode::MarkdownV2 => "MarkdownV2",
+            TelegramParseMode::Html => "HTML",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PushPlatform {
+    /// Apple Push Notification service
+    Apns,
+    /// Firebase Cloud Messaging
+    Fcm,
+    /// 华为 Push Kit
+    Huawei,
+    /// 小米推送
+    Xiaomi,
+    /// OPPO 推送
+    Oppo,
+    /// vivo 推送
+    Vivo,
+    /// 荣耀推送
+    Honor,
+}
+
+impl PushPlatform {
+    pub const ALL: [PushPlatform; 7] = [
+        PushPlatform::Apns,
+        PushPlatform::Fcm,
+        PushPlatform::Huawei,
+        PushPlatform::Xiaomi,
+        PushPlatform::Oppo,
+        PushPlatform::Vivo,
+        PushPlatform::Honor,
+    ];
+
+    /// 不区分大小写解析平台名，如设备令牌中的厂商标签
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|p| <&'static str>::from(*p).eq_ignore_ascii_case(value))
+    }
+}
+
+impl From<PushPlatform> for &'static str {
+    fn from(p: PushPlatform) -> Self {
+        match p {
+            PushPlatform::Apns => "apns",
+            PushPlatform::Fcm => "fcm",
+            PushPlatform::Huawei => "huawei",
+            PushPlatform::Xiaomi => "xiaomi",
+            PushPlatform::Oppo => "oppo",
+            PushPlatform::Vivo => "vivo",
+            PushPlatform::Honor => "honor",
+        }
+    }
+}
@@ -1,5 +1,7 @@
 mod channel;
+mod delivery;
 mod message;
 
 pub use channel::*;
+pub use delivery::*;
 pub use message::*;
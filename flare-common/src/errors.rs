@@ -8,10 +8,41 @@ pub enum FlareError {
     Smtp(#[from] lettre::transport::smtp::Error),
     #[error("Email address error: {0}")]
     Address(#[from] lettre::address::AddressError),
+    /// 收件人、抄送等列表中的某个地址无效
+    #[error("Invalid email address '{address}': {source}")]
+    InvalidAddress {
+        address: String,
+        #[source]
+        source: lettre::address::AddressError,
+    },
     #[error("Email build error: {0}")]
     Lettre(#[from] lettre::error::Error),
     #[error("HTTP error: {0}")]
     Http(#[from] reqwest::Error),
+    #[error("HTTP status {status}: {body}")]
+    HttpStatus { status: u16, body: String },
+    /// 平台在响应体中返回的业务错误（如飞书 code、钉钉/企业微信 errcode）
+    #[error("{platform} error {code}: {message}")]
+    Platform {
+        platform: &'static str,
+        code: i64,
+        message: String,
+        /// 限流、系统繁忙等可重试；签名、关键词、参数错误等不可重试
+        retryable: bool,
+    },
+    /// 短信服务商返回的错误（如阿里云 Code 不为 OK）
+    #[error("{provider} SMS error {code}: {message}")]
+    Sms {
+        provider: &'static str,
+        code: String,
+        message: String,
+        request_id: Option<String>,
+        /// 流控、系统错误等可重试；签名、模板、号码错误等不可重试
+        retryable: bool,
+    },
+    /// 数据库、缓存等存储访问失败，通常可稍后重试
+    #[error("Storage error: {0}")]
+    Storage(String),
     #[error("String error: {0}")]
     String(String),
     #[error("Configuration error: {0}")]
@@ -20,4 +51,22 @@ pub enum FlareError {
     Unknown(String),
 }
 
+impl FlareError {
+    /// 失败是否值得稍后重试
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            FlareError::Platform { retryable, .. } | FlareError::Sms { retryable, .. } => *retryable,
+            FlareError::HttpStatus { status, .. } => *status == 429 || *status >= 500,
+            FlareError::Http(e) => {
+                e.is_timeout()
+                    || e.is_connect()
+                    || e.status().is_some_and(|s| s.as_u16() == 429 || s.is_server_error())
+            }
+            FlareError::Smtp(e) => e.is_transient(),
+            FlareError::Storage(_) => true,
+            _ => false,
+        }
+    }
+}
+
 pub type FlareResult<T> = Result<T, FlareError>;
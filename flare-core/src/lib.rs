@@ -1,6 +1,7 @@
 mod sender;
 mod notification;
 mod log;
+mod template;
 mod feishu;
 mod dingding;
 mod wechat;
@@ -8,4 +9,5 @@ mod wechat;
 pub use sender::*;
 pub use notification::*;
 pub use log::*;
+pub use template::*;
 
@@ -0,0 +1,46 @@
+use flare_common::{FlareError, FlareResult};
+use handlebars::{Context, Handlebars, Helper, HelperResult, Output, RenderContext};
+use serde_json::Value;
+
+/// `{{json value}}`：原样输出变量的 JSON 表示，用于嵌入对象、数组
+fn json_helper(
+    h: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> HelperResult {
+    let value = h.param(0).map(|p| p.value().clone()).unwrap_or(Value::Null);
+    out.write(&value.to_string())?;
+    Ok(())
+}
+
+/// 渲染 JSON 模板：`{{name}}` 按 JSON 字符串规则转义（放在引号内使用），
+/// `{{json data}}` 输出变量的完整 JSON。渲染结果必须是合法 JSON
+pub fn render_json_template(template: &str, data: &Value) -> FlareResult<String> {
+    let mut hb = Handlebars::new();
+    hb.register_escape_fn(|s| {
+        let quoted = Value::from(s).to_string();
+        quoted[1..quoted.len() - 1].to_string()
+    });
+    hb.register_helper("json", Box::new(json_helper));
+
+    let rendered = hb
+        .render_template(template, data)
+        .map_err(|e| FlareError::Config(format!("模板渲染失败: {}", e)))?;
+    serde_json::from_str::<Value>(&rendered)
+        .map_err(|e| FlareError::Config(format!("模板渲染结果不是合法 JSON: {}", e)))?;
+    Ok(rendered)
+}
+
+/// 渲染文本模板：`{{name}}` 代入的变量值经 `escape` 转义，模板本身的标记原样保留
+pub fn render_text_template(
+    template: &str,
+    data: &Value,
+    escape: impl Fn(&str) -> String + Send + Sync + 'static,
+) -> FlareResult<String> {
+    let mut hb = Handlebars::new();
+    hb.register_escape_fn(escape);
+    hb.render_template(template, data)
+        .map_err(|e| FlareError::Config(format!("模板渲染失败: {}", e)))
+}
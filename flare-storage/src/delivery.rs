@@ -0,0 +1,439 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use flare_common::DeliveryStatus;
+use sqlx::{PgPool, Row};
+
+/// 回执轮询的退避：首次间隔 2 分钟，之后每次翻倍，最长 2 小时
+const POLL_BACKOFF_BASE_SECS: i64 = 120;
+const POLL_BACKOFF_MAX_SECS: i64 = 7200;
+
+/// 第 `poll_count + 1` 次查询仍无终态后，到下次查询的间隔
+fn poll_backoff(poll_count: i32) -> chrono::Duration {
+    let secs = POLL_BACKOFF_BASE_SECS.saturating_mul(1 << poll_count.clamp(0, 16));
+    chrono::Duration::seconds(secs.min(POLL_BACKOFF_MAX_SECS))
+}
+
+/// 一条已提交短信的投递记录，以 (biz_id, phone) 唯一标识
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeliveryRecord {
+    /// 上游 Kafka 消息 ID
+    pub message_id: String,
+    /// 服务商，如 aliyun
+    pub provider: String,
+    pub phone: String,
+    /// 服务商返回的回执 ID
+    pub biz_id: String,
+    pub status: DeliveryStatus,
+    pub err_code: Option<String>,
+    pub err_msg: Option<String>,
+    pub sent_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// 已轮询查询的次数
+    pub poll_count: i32,
+    /// 下次轮询的时间，每次查询无终态后按次数退避
+    pub next_poll_at: DateTime<Utc>,
+}
+
+impl DeliveryRecord {
+    /// 新提交的记录，状态为 Sent
+    pub fn sent(message_id: &str, provider: &str, phone: &str, biz_id: &str) -> Self {
+        let now = Utc::now();
+        Self {
+            message_id: message_id.to_string(),
+            provider: provider.to_string(),
+            phone: phone.to_string(),
+            biz_id: biz_id.to_string(),
+            status: DeliveryStatus::Sent,
+            err_code: None,
+            err_msg: None,
+            sent_at: now,
+            updated_at: now,
+            poll_count: 0,
+            next_poll_at: now,
+        }
+    }
+
+    /// 提交即失败的记录，状态为 Failed，不参与轮询。
+    /// 服务商未返回回执 ID，以 `failed:<provider>:<message_id>` 占位，消息重投时不会重复记录
+    pub fn failed(message_id: &str, provider: &str, phone: &str, err_code: &str, err_msg: &str) -> Self {
+        let biz_id = format!("failed:{}:{}", provider, message_id);
+        Self {
+            status: DeliveryStatus::Failed,
+            err_code: Some(err_code.to_string()).filter(|code| !code.is_empty()),
+            err_msg: Some(err_msg.to_string()),
+            ..Self::sent(message_id, provider, phone, &biz_id)
+        }
+    }
+}
+
+/// 回执：来自状态报告推送或主动查询
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeliveryReceipt {
+    pub biz_id: String,
+    pub phone: String,
+    pub status: DeliveryStatus,
+    pub err_code: Option<String>,
+    pub err_msg: Option<String>,
+}
+
+/// 投递状态存储
+#[async_trait]
+pub trait DeliveryStore: Send + Sync {
+    /// 记录一条短信的投递状态，通常为已提交；(biz_id, phone) 已存在时保持不变
+    async fn record_sent(&self, record: &DeliveryRecord) -> Result<()>;
+
+    /// 应用回执，返回是否有记录被更新；已是终态的记录保持不变，
+    /// 推送与轮询重复到达时以先到者为准
+    async fn apply_receipt(&self, receipt: &DeliveryReceipt) -> Result<bool>;
+
+    /// 指定服务商在 `[sent_after, sent_before)` 之间提交、仍未收到回执且已到轮询时间的记录，
+    /// 按下次轮询时间排序，长期无终态的记录不会挡住新记录
+    async fn pending(
+        &self,
+        provider: &str,
+        sent_after: DateTime<Utc>,
+        sent_before: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<DeliveryRecord>>;
+
+    /// 记录一次未得到终态的轮询，按已查询次数推迟下次轮询
+    async fn mark_polled(&self, biz_id: &str, phone: &str) -> Result<()>;
+
+    async fn get(&self, biz_id: &str, phone: &str) -> Result<Option<DeliveryRecord>>;
+}
+
+/// Postgres 实现，表结构见 [`PgDeliveryStore::migrate`]
+pub struct PgDeliveryStore {
+    pool: PgPool,
+}
+
+impl PgDeliveryStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn connect(url: &str) -> Result<Self> {
+        let pool = PgPool::connect(url).await.context("连接数据库失败")?;
+        Ok(Self::new(pool))
+    }
+
+    /// 建表（幂等）
+    pub async fn migrate(&self) -> Result<()> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS sms_delivery (
+                biz_id      TEXT NOT NULL,
+                phone       TEXT NOT NULL,
+                message_id  TEXT NOT NULL,
+                provider    TEXT NOT NULL,
+                status      TEXT NOT NULL,
+                err_code    TEXT,
+                err_msg     TEXT,
+                sent_at     TIMESTAMPTZ NOT NULL,
+                updated_at  TIMESTAMPTZ NOT NULL,
+                PRIMARY KEY (biz_id, phone)
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+        // 早期版本的表没有轮询退避字段
+        sqlx::query(
+            "ALTER TABLE sms_delivery
+                ADD COLUMN IF NOT EXISTS poll_count   INT NOT NULL DEFAULT 0,
+                ADD COLUMN IF NOT EXISTS next_poll_at TIMESTAMPTZ NOT NULL DEFAULT now()",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query("DROP INDEX IF EXISTS sms_delivery_pending")
+            .execute(&self.pool)
+            .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS sms_delivery_next_poll
+             ON sms_delivery (provider, next_poll_at) WHERE status = 'SENT'",
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    fn from_row(row: &sqlx::postgres::PgRow) -> Result<DeliveryRecord> {
+        let status: String = row.try_get("status")?;
+        Ok(DeliveryRecord {
+            message_id: row.try_get("message_id")?,
+            provider: row.try_get("provider")?,
+            phone: row.try_get("phone")?,
+            biz_id: row.try_get("biz_id")?,
+            status: DeliveryStatus::parse(&status)
+                .with_context(|| format!("未知投递状态: {}", status))?,
+            err_code: row.try_get("err_code")?,
+            err_msg: row.try_get("err_msg")?,
+            sent_at: row.try_get("sent_at")?,
+            updated_at: row.try_get("updated_at")?,
+            poll_count: row.try_get("poll_count")?,
+            next_poll_at: row.try_get("next_poll_at")?,
+        })
+    }
+}
+
+#[async_trait]
+impl DeliveryStore for PgDeliveryStore {
+    async fn record_sent(&self, record: &DeliveryRecord) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO sms_delivery
+                (biz_id, phone, message_id, provider, status, err_code, err_msg, sent_at, updated_at,
+                 poll_count, next_poll_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+             ON CONFLICT (biz_id, phone) DO NOTHING",
+        )
+        .bind(&record.biz_id)
+        .bind(&record.phone)
+        .bind(&record.message_id)
+        .bind(&record.provider)
+        .bind(<&str>::from(record.status))
+        .bind(&record.err_code)
+        .bind(&record.err_msg)
+        .bind(record.sent_at)
+        .bind(record.updated_at)
+        .bind(record.poll_count)
+        .bind(record.next_poll_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn apply_receipt(&self, receipt: &DeliveryReceipt) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE sms_delivery
+             SET status = $3, err_code = $4, err_msg = $5, updated_at = now()
+             WHERE biz_id = $1 AND phone = $2 AND status = 'SENT'",
+        )
+        .bind(&receipt.biz_id)
+        .bind(&receipt.phone)
+        .bind(<&str>::from(receipt.status))
+        .bind(&receipt.err_code)
+        .bind(&receipt.err_msg)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn pending(
+        &self,
+        provider: &str,
+        sent_after: DateTime<Utc>,
+        sent_before: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<DeliveryRecord>> {
+        let rows = sqlx::query(
+            "SELECT * FROM sms_delivery
+             WHERE status = 'SENT' AND provider = $1 AND sent_at >= $2 AND sent_at < $3
+               AND next_poll_at <= now()
+             ORDER BY next_poll_at LIMIT $4",
+        )
+        .bind(provider)
+        .bind(sent_after)
+        .bind(sent_before)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(Self::from_row).collect()
+    }
+
+    async fn mark_polled(&self, biz_id: &str, phone: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE sms_delivery
+             SET next_poll_at = now() + LEAST($3 * power(2, LEAST(poll_count, 16)), $4) * interval '1 second',
+                 poll_count = poll_count + 1
+             WHERE biz_id = $1 AND phone = $2",
+        )
+        .bind(biz_id)
+        .bind(phone)
+        .bind(POLL_BACKOFF_BASE_SECS)
+        .bind(POLL_BACKOFF_MAX_SECS)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn get(&self, biz_id: &str, phone: &str) -> Result<Option<DeliveryRecord>> {
+        let row = sqlx::query("SELECT * FROM sms_delivery WHERE biz_id = $1 AND phone = $2")
+            .bind(biz_id)
+            .bind(phone)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.as_ref().map(Self::from_row).transpose()
+    }
+}
+
+/// 内存实现，用于测试和未配置数据库的单机部署
+#[derive(Default)]
+pub struct MemoryDeliveryStore {
+    records: Mutex<HashMap<(String, String), DeliveryRecord>>,
+}
+
+impl MemoryDeliveryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl DeliveryStore for MemoryDeliveryStore {
+    async fn record_sent(&self, record: &DeliveryRecord) -> Result<()> {
+        let key = (record.biz_id.clone(), record.phone.clone());
+        self.records.lock().unwrap().entry(key).or_insert_with(|| record.clone());
+        Ok(())
+    }
+
+    async fn apply_receipt(&self, receipt: &DeliveryReceipt) -> Result<bool> {
+        let mut records = self.records.lock().unwrap();
+        let key = (receipt.biz_id.clone(), receipt.phone.clone());
+        match records.get_mut(&key) {
+            Some(record) if !record.status.is_final() => {
+                record.status = receipt.status;
+                record.err_code = receipt.err_code.clone();
+                record.err_msg = receipt.err_msg.clone();
+                record.updated_at = Utc::now();
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn pending(
+        &self,
+        provider: &str,
+        sent_after: DateTime<Utc>,
+        sent_before: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<DeliveryRecord>> {
+        let records = self.records.lock().unwrap();
+        let mut pending: Vec<_> = records
+            .values()
+            .filter(|r| r.provider == provider && !r.status.is_final())
+            .filter(|r| r.sent_at >= sent_after && r.sent_at < sent_before)
+            .filter(|r| r.next_poll_at <= Utc::now())
+            .cloned()
+            .collect();
+        pending.sort_by_key(|r| r.next_poll_at);
+        pending.truncate(limit.max(0) as usize);
+        Ok(pending)
+    }
+
+    async fn mark_polled(&self, biz_id: &str, phone: &str) -> Result<()> {
+        let key = (biz_id.to_string(), phone.to_string());
+        if let Some(record) = self.records.lock().unwrap().get_mut(&key) {
+            record.next_poll_at = Utc::now() + poll_backoff(record.poll_count);
+            record.poll_count += 1;
+        }
+        Ok(())
+    }
+
+    async fn get(&self, biz_id: &str, phone: &str) -> Result<Option<DeliveryRecord>> {
+        let key = (biz_id.to_string(), phone.to_string());
+        Ok(self.records.lock().unwrap().get(&key).cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn receipt(status: DeliveryStatus, err_code: &str) -> DeliveryReceipt {
+        DeliveryReceipt {
+            biz_id: "biz-1".into(),
+            phone: "13800000000".into(),
+            status,
+            err_code: Some(err_code.into()),
+            err_msg: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn receipt_moves_record_to_final_state_once() {
+        let store = MemoryDeliveryStore::new();
+        store.record_sent(&DeliveryRecord::sent("msg-1", "aliyun", "13800000000", "biz-1")).await.unwrap();
+
+        assert!(store.apply_receipt(&receipt(DeliveryStatus::Failed, "MK:0001")).await.unwrap());
+        // 终态后重复或迟到的回执不再覆盖
+        assert!(!store.apply_receipt(&receipt(DeliveryStatus::Delivered, "DELIVERED")).await.unwrap());
+
+        let record = store.get("biz-1", "13800000000").await.unwrap().unwrap();
+        assert_eq!(record.status, DeliveryStatus::Failed);
+        assert_eq!(record.err_code.as_deref(), Some("MK:0001"));
+    }
+
+    #[tokio::test]
+    async fn failed_submission_is_final_and_not_polled() {
+        let store = MemoryDeliveryStore::new();
+        let record = DeliveryRecord::failed("msg-1", "aliyun", "13800000000", "isv.MOBILE_NUMBER_ILLEGAL", "非法手机号");
+        store.record_sent(&record).await.unwrap();
+
+        let now = Utc::now();
+        let pending = store.pending("aliyun", now - Duration::days(1), now + Duration::minutes(1), 10).await.unwrap();
+        assert!(pending.is_empty());
+        assert!(!store.apply_receipt(&DeliveryReceipt { biz_id: record.biz_id.clone(), ..receipt(DeliveryStatus::Delivered, "DELIVERED") }).await.unwrap());
+
+        let stored = store.get("failed:aliyun:msg-1", "13800000000").await.unwrap().unwrap();
+        assert_eq!(stored.status, DeliveryStatus::Failed);
+        assert_eq!(stored.err_code.as_deref(), Some("isv.MOBILE_NUMBER_ILLEGAL"));
+    }
+
+    #[tokio::test]
+    async fn receipt_for_unknown_record_is_ignored() {
+        let store = MemoryDeliveryStore::new();
+        assert!(!store.apply_receipt(&receipt(DeliveryStatus::Delivered, "DELIVERED")).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn pending_only_returns_unfinished_records_in_window() {
+        let store = MemoryDeliveryStore::new();
+        let mut old = DeliveryRecord::sent("msg-1", "aliyun", "13800000001", "biz-old");
+        old.sent_at = Utc::now() - Duration::days(5);
+        store.record_sent(&old).await.unwrap();
+        store.record_sent(&DeliveryRecord::sent("msg-2", "aliyun", "13800000000", "biz-1")).await.unwrap();
+        store.record_sent(&DeliveryRecord::sent("msg-3", "aliyun", "13800000002", "biz-2")).await.unwrap();
+        store.record_sent(&DeliveryRecord::sent("msg-4", "tencent", "13800000003", "2028:1")).await.unwrap();
+        store
+            .apply_receipt(&DeliveryReceipt { biz_id: "biz-2".into(), phone: "13800000002".into(), ..receipt(DeliveryStatus::Delivered, "DELIVERED") })
+            .await
+            .unwrap();
+
+        let now = Utc::now();
+        let pending = store.pending("aliyun", now - Duration::days(3), now + Duration::seconds(1), 10).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].biz_id, "biz-1");
+    }
+
+    #[tokio::test]
+    async fn polled_records_back_off_behind_newer_ones() {
+        let store = MemoryDeliveryStore::new();
+        let mut waiting = DeliveryRecord::sent("msg-1", "aliyun", "13800000001", "biz-waiting");
+        waiting.sent_at = Utc::now() - Duration::hours(1);
+        store.record_sent(&waiting).await.unwrap();
+        store.record_sent(&DeliveryRecord::sent("msg-2", "aliyun", "13800000002", "biz-new")).await.unwrap();
+
+        let now = Utc::now();
+        let window = (now - Duration::days(3), now + Duration::seconds(1));
+        // 批量上限为 1 时，仍在等待回执的旧记录轮询过后让位给新记录
+        assert_eq!(store.pending("aliyun", window.0, window.1, 1).await.unwrap()[0].biz_id, "biz-waiting");
+        store.mark_polled("biz-waiting", "13800000001").await.unwrap();
+        assert_eq!(store.pending("aliyun", window.0, window.1, 1).await.unwrap()[0].biz_id, "biz-new");
+
+        let record = store.get("biz-waiting", "13800000001").await.unwrap().unwrap();
+        assert_eq!(record.poll_count, 1);
+        assert!(record.next_poll_at > now + Duration::seconds(100));
+    }
+
+    #[test]
+    fn poll_backoff_doubles_up_to_cap() {
+        assert_eq!(poll_backoff(0), Duration::minutes(2));
+        assert_eq!(poll_backoff(1), Duration::minutes(4));
+        assert_eq!(poll_backoff(10), Duration::hours(2));
+        assert_eq!(poll_backoff(i32::MAX), Duration::hours(2));
+    }
+}
@@ -0,0 +1,540 @@
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+
+/// 未指定分类时使用的默认分类
+pub const DEFAULT_INBOX_CATEGORY: &str = "default";
+
+/// 站内信，每个接收用户一条
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InboxMessage {
+    pub id: String,
+    pub user_id: String,
+    /// 分类，如 system、order、activity
+    pub category: String,
+    pub title: String,
+    pub content: String,
+    /// 业务附加数据，如跳转链接
+    pub data: Option<serde_json::Value>,
+    /// 未读时为 None
+    pub read_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    /// 过期后不再展示、不计入未读
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl InboxMessage {
+    /// 新的未读消息，ID 随机生成
+    pub fn new(user_id: &str, category: &str, title: &str, content: &str) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            category: category.to_string(),
+            title: title.to_string(),
+            content: content.to_string(),
+            data: None,
+            read_at: None,
+            created_at: Utc::now(),
+            expires_at: None,
+        }
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|t| t <= now)
+    }
+}
+
+/// 列表查询条件，按创建时间倒序分页
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct InboxQuery {
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub unread_only: bool,
+    #[serde(default)]
+    pub limit: Option<i64>,
+    #[serde(default)]
+    pub offset: Option<i64>,
+}
+
+impl InboxQuery {
+    /// 单页最多条数
+    pub const MAX_LIMIT: i64 = 100;
+
+    fn limit(&self) -> i64 {
+        self.limit.unwrap_or(20).clamp(1, Self::MAX_LIMIT)
+    }
+
+    fn offset(&self) -> i64 {
+        self.offset.unwrap_or(0).max(0)
+    }
+}
+
+/// 未读数：总数及各分类
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnreadCounts {
+    pub total: i64,
+    pub categories: BTreeMap<String, i64>,
+}
+
+impl UnreadCounts {
+    pub fn from_categories(categories: BTreeMap<String, i64>) -> Self {
+        Self { total: categories.values().sum(), categories }
+    }
+}
+
+/// 站内信存储；除写入外的操作都限定在 user_id 范围内，防止越权访问
+#[async_trait]
+pub trait InboxStore: Send + Sync {
+    /// 写入消息，已存在的 ID 跳过（Kafka 重投），返回实际写入的消息 ID
+    async fn insert(&self, messages: &[InboxMessage]) -> Result<Vec<String>>;
+
+    /// 未过期的消息，按创建时间倒序
+    async fn list(&self, user_id: &str, query: &InboxQuery) -> Result<Vec<InboxMessage>>;
+
+    /// 指定消息之后创建的未过期消息，按创建时间正序，用于断线重连后补发；
+    /// 指定消息不存在时返回空
+    async fn list_after(&self, user_id: &str, last_id: &str, limit: i64) -> Result<Vec<InboxMessage>>;
+
+    /// 标记已读，返回实际由未读变为已读的条数
+    async fn mark_read(&self, user_id: &str, ids: &[String]) -> Result<u64>;
+
+    /// 未过期的消息全部标记已读，可限定分类，返回条数与未读数的减少一致
+    async fn mark_all_read(&self, user_id: &str, category: Option<&str>) -> Result<u64>;
+
+    /// 删除一条消息，返回是否存在
+    async fn delete(&self, user_id: &str, id: &str) -> Result<bool>;
+
+    /// 未过期的未读消息数
+    async fn unread_counts(&self, user_id: &str) -> Result<UnreadCounts>;
+
+    /// 清理已过期的消息，返回删除条数
+    async fn purge_expired(&self) -> Result<u64>;
+}
+
+/// Postgres 实现，表结构见 [`PgInboxStore::migrate`]
+pub struct PgInboxStore {
+    pool: PgPool,
+}
+
+impl PgInboxStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn connect(url: &str) -> Result<Self> {
+        let pool = PgPool::connect(url).await.context("连接数据库失败")?;
+        Ok(Self::new(pool))
+    }
+
+    /// 建表（幂等）
+    pub async fn migrate(&self) -> Result<()> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS inbox_message (
+                id          TEXT PRIMARY KEY,
+                user_id     TEXT NOT NULL,
+                category    TEXT NOT NULL,
+                title       TEXT NOT NULL,
+                content     TEXT NOT NULL,
+                data        JSONB,
+                read_at     TIMESTAMPTZ,
+                created_at  TIMESTAMPTZ NOT NULL,
+                expires_at  TIMESTAMPTZ
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS inbox_message_user ON inbox_message (user_id, created_at DESC)")
+            .execute(&self.pool)
+            .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS inbox_message_unread ON inbox_message (user_id, category) WHERE read_at IS NULL",
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    fn from_row(row: &sqlx::postgres::PgRow) -> Result<InboxMessage> {
+        Ok(InboxMessage {
+            id: row.try_get("id")?,
+            user_id: row.try_get("user_id")?,
+            category: row.try_get("category")?,
+            title: row.try_get("title")?,
+            content: row.try_get("content")?,
+            data: row.try_get("data")?,
+            read_at: row.try_get("read_at")?,
+            created_at: row.try_get("created_at")?,
+            expires_at: row.try_get("expires_at")?,
+        })
+    }
+}
+
+#[async_trait]
+impl InboxStore for PgInboxStore {
+    async fn insert(&self, messages: &[InboxMessage]) -> Result<Vec<String>> {
+        let mut tx = self.pool.begin().await?;
+        let mut inserted = Vec::with_capacity(messages.len());
+        for message in messages {
+            let row = sqlx::query(
+                "INSERT INTO inbox_message
+                    (id, user_id, category, title, content, data, read_at, created_at, expires_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                 ON CONFLICT (id) DO NOTHING
+                 RETURNING id",
+            )
+            .bind(&message.id)
+            .bind(&message.user_id)
+            .bind(&message.category)
+            .bind(&message.title)
+            .bind(&message.content)
+            .bind(&message.data)
+            .bind(message.read_at)
+            .bind(message.created_at)
+            .bind(message.expires_at)
+            .fetch_optional(&mut *tx)
+            .await?;
+            if let Some(row) = row {
+                inserted.push(row.try_get("id")?);
+            }
+        }
+        tx.commit().await?;
+        Ok(inserted)
+    }
+
+    async fn list(&self, user_id: &str, query: &InboxQuery) -> Result<Vec<InboxMessage>> {
+        let rows = sqlx::query(
+            "SELECT * FROM inbox_message
+             WHERE user_id = $1
+               AND ($2::TEXT IS NULL OR category = $2)
+               AND (NOT $3 OR read_at IS NULL)
+               AND (expires_at IS NULL OR expires_at > now())
+             ORDER BY created_at DESC, id
+             LIMIT $4 OFFSET $5",
+        )
+        .bind(user_id)
+        .bind(&query.category)
+        .bind(query.unread_only)
+        .bind(query.limit())
+        .bind(query.offset())
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(Self::from_row).collect()
+    }
+
+    async fn list_after(&self, user_id: &str, last_id: &str, limit: i64) -> Result<Vec<InboxMessage>> {
+        let rows = sqlx::query(
+            "SELECT m.* FROM inbox_message m
+             JOIN inbox_message last ON last.user_id = $1 AND last.id = $2
+             WHERE m.user_id = $1
+               AND (m.created_at, m.id) > (last.created_at, last.id)
+               AND (m.expires_at IS NULL OR m.expires_at > now())
+             ORDER BY m.created_at, m.id
+             LIMIT $3",
+        )
+        .bind(user_id)
+        .bind(last_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter().map(Self::from_row).collect()
+    }
+
+    async fn mark_read(&self, user_id: &str, ids: &[String]) -> Result<u64> {
+        let result = sqlx::query(
+            "UPDATE inbox_message SET read_at = now()
+             WHERE user_id = $1 AND id = ANY($2) AND read_at IS NULL",
+        )
+        .bind(user_id)
+        .bind(ids)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn mark_all_read(&self, user_id: &str, category: Option<&str>) -> Result<u64> {
+        let result = sqlx::query(
+            "UPDATE inbox_message SET read_at = now()
+             WHERE user_id = $1 AND ($2::TEXT IS NULL OR category = $2) AND read_at IS NULL
+               AND (expires_at IS NULL OR expires_at > now())",
+        )
+        .bind(user_id)
+        .bind(category)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn delete(&self, user_id: &str, id: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM inbox_message WHERE user_id = $1 AND id = $2")
+            .bind(user_id)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn unread_counts(&self, user_id: &str) -> Result<UnreadCounts> {
+        let rows = sqlx::query(
+            "SELECT category, COUNT(*) AS unread FROM inbox_message
+             WHERE user_id = $1 AND read_at IS NULL AND (expires_at IS NULL OR expires_at > now())
+             GROUP BY category",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        let categories = rows
+            .iter()
+            .map(|row| Ok((row.try_get("category")?, row.try_get("unread")?)))
+            .collect::<Result<_>>()?;
+        Ok(UnreadCounts::from_categories(categories))
+    }
+
+    async fn purge_expired(&self) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM inbox_message WHERE expires_at <= now()")
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+/// 内存实现，用于测试和未配置数据库的单机部署
+#[derive(Default)]
+pub struct MemoryInboxStore {
+    messages: Mutex<HashMap<String, InboxMessage>>,
+}
+
+impl MemoryInboxStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl InboxStore for MemoryInboxStore {
+    async fn insert(&self, messages: &[InboxMessage]) -> Result<Vec<String>> {
+        let mut stored = self.messages.lock().unwrap();
+        let mut inserted = Vec::with_capacity(messages.len());
+        for message in messages {
+            if let Entry::Vacant(entry) = stored.entry(message.id.clone()) {
+                entry.insert(message.clone());
+                inserted.push(message.id.clone());
+            }
+        }
+        Ok(inserted)
+    }
+
+    async fn list(&self, user_id: &str, query: &InboxQuery) -> Result<Vec<InboxMessage>> {
+        let now = Utc::now();
+        let stored = self.messages.lock().unwrap();
+        let mut messages: Vec<_> = stored
+            .values()
+            .filter(|m| m.user_id == user_id && !m.is_expired(now))
+            .filter(|m| query.category.as_ref().map_or(true, |c| &m.category == c))
+            .filter(|m| !query.unread_only || m.read_at.is_none())
+            .cloned()
+            .collect();
+        messages.sort_by(|a, b| b.created_at.cmp(&a.created_at).then_with(|| a.id.cmp(&b.id)));
+        Ok(messages
+            .into_iter()
+            .skip(query.offset() as usize)
+            .take(query.limit() as usize)
+            .collect())
+    }
+
+    async fn list_after(&self, user_id: &str, last_id: &str, limit: i64) -> Result<Vec<InboxMessage>> {
+        let now = Utc::now();
+        let stored = self.messages.lock().unwrap();
+        let Some(last) = stored.get(last_id).filter(|m| m.user_id == user_id) else {
+            return Ok(Vec::new());
+        };
+        let position = |m: &InboxMessage| (m.created_at, m.id.clone());
+        let after = position(last);
+        let mut messages: Vec<_> = stored
+            .values()
+            .filter(|m| m.user_id == user_id && !m.is_expired(now) && position(m) > after)
+            .cloned()
+            .collect();
+        messages.sort_by_key(position);
+        messages.truncate(limit.max(0) as usize);
+        Ok(messages)
+    }
+
+    async fn mark_read(&self, user_id: &str, ids: &[String]) -> Result<u64> {
+        let now = Utc::now();
+        let mut stored = self.messages.lock().unwrap();
+        let mut updated = 0;
+        for id in ids {
+            if let Some(m) = stored.get_mut(id) {
+                if m.user_id == user_id && m.read_at.is_none() {
+                    m.read_at = Some(now);
+                    updated += 1;
+                }
+            }
+        }
+        Ok(updated)
+    }
+
+    async fn mark_all_read(&self, user_id: &str, category: Option<&str>) -> Result<u64> {
+        let now = Utc::now();
+        let mut stored = self.messages.lock().unwrap();
+        let mut updated = 0;
+        for m in stored.values_mut() {
+            if m.user_id == user_id
+                && m.read_at.is_none()
+                && !m.is_expired(now)
+                && category.map_or(true, |c| m.category == c)
+            {
+                m.read_at = Some(now);
+                updated += 1;
+            }
+        }
+        Ok(updated)
+    }
+
+    async fn delete(&self, user_id: &str, id: &str) -> Result<bool> {
+        let mut stored = self.messages.lock().unwrap();
+        match stored.get(id) {
+            Some(m) if m.user_id == user_id => {
+                stored.remove(id);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn unread_counts(&self, user_id: &str) -> Result<UnreadCounts> {
+        let now = Utc::now();
+        let stored = self.messages.lock().unwrap();
+        let mut categories = BTreeMap::new();
+        for m in stored.values() {
+            if m.user_id == user_id && m.read_at.is_none() && !m.is_expired(now) {
+                *categories.entry(m.category.clone()).or_insert(0) += 1;
+            }
+        }
+        Ok(UnreadCounts::from_categories(categories))
+    }
+
+    async fn purge_expired(&self) -> Result<u64> {
+        let now = Utc::now();
+        let mut stored = self.messages.lock().unwrap();
+        let before = stored.len();
+        stored.retain(|_, m| !m.is_expired(now));
+        Ok((before - stored.len()) as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn message(user_id: &str, category: &str, title: &str, age_secs: i64) -> InboxMessage {
+        let mut m = InboxMessage::new(user_id, category, title, "content");
+        m.created_at = Utc::now() - Duration::seconds(age_secs);
+        m
+    }
+
+    #[tokio::test]
+    async fn list_filters_and_pages_newest_first() {
+        let store = MemoryInboxStore::new();
+        let mut expired = message("u1", "system", "expired", 1);
+        expired.expires_at = Some(Utc::now() - Duration::seconds(1));
+        store
+            .insert(&[
+                message("u1", "order", "oldest", 30),
+                message("u1", "system", "middle", 20),
+                message("u1", "order", "newest", 10),
+                message("u2", "order", "other user", 5),
+                expired,
+            ])
+            .await
+            .unwrap();
+
+        let titles = |messages: Vec<InboxMessage>| messages.into_iter().map(|m| m.title).collect::<Vec<_>>();
+        let all = store.list("u1", &InboxQuery::default()).await.unwrap();
+        assert_eq!(titles(all), ["newest", "middle", "oldest"]);
+
+        let orders = InboxQuery { category: Some("order".into()), limit: Some(1), offset: Some(1), ..Default::default() };
+        assert_eq!(titles(store.list("u1", &orders).await.unwrap()), ["oldest"]);
+    }
+
+    #[tokio::test]
+    async fn list_after_returns_newer_messages_oldest_first() {
+        let store = MemoryInboxStore::new();
+        let messages = [
+            message("u1", "order", "a", 30),
+            message("u1", "order", "b", 20),
+            message("u1", "order", "c", 10),
+            message("u2", "order", "other user", 5),
+        ];
+        store.insert(&messages).await.unwrap();
+
+        let titles = |messages: Vec<InboxMessage>| messages.into_iter().map(|m| m.title).collect::<Vec<_>>();
+        assert_eq!(titles(store.list_after("u1", &messages[0].id, 10).await.unwrap()), ["b", "c"]);
+        assert_eq!(titles(store.list_after("u1", &messages[0].id, 1).await.unwrap()), ["b"]);
+        assert!(store.list_after("u1", &messages[2].id, 10).await.unwrap().is_empty());
+        // 其他用户的消息不能作为补发起点
+        assert!(store.list_after("u2", &messages[0].id, 10).await.unwrap().is_empty());
+        assert!(store.list_after("u1", "missing", 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn read_state_updates_unread_counts() {
+        let store = MemoryInboxStore::new();
+        let messages = [
+            message("u1", "order", "a", 3),
+            message("u1", "order", "b", 2),
+            message("u1", "system", "c", 1),
+        ];
+        store.insert(&messages).await.unwrap();
+
+        let counts = store.unread_counts("u1").await.unwrap();
+        assert_eq!(counts.total, 3);
+        assert_eq!(counts.categories["order"], 2);
+
+        // 其他用户无法标记、删除
+        assert_eq!(store.mark_read("u2", &[messages[0].id.clone()]).await.unwrap(), 0);
+        assert!(!store.delete("u2", &messages[0].id).await.unwrap());
+
+        assert_eq!(store.mark_read("u1", &[messages[0].id.clone(), messages[0].id.clone()]).await.unwrap(), 1);
+        assert_eq!(store.mark_all_read("u1", Some("order")).await.unwrap(), 1);
+        let counts = store.unread_counts("u1").await.unwrap();
+        assert_eq!(counts, UnreadCounts::from_categories([("system".to_string(), 1)].into()));
+
+        let unread = InboxQuery { unread_only: true, ..Default::default() };
+        assert_eq!(store.list("u1", &unread).await.unwrap().len(), 1);
+        assert!(store.delete("u1", &messages[2].id).await.unwrap());
+        assert_eq!(store.unread_counts("u1").await.unwrap().total, 0);
+    }
+
+    #[tokio::test]
+    async fn purge_removes_only_expired() {
+        let store = MemoryInboxStore::new();
+        let mut expired = message("u1", "system", "expired", 1);
+        expired.expires_at = Some(Utc::now() - Duration::seconds(1));
+        let mut alive = message("u1", "system", "alive", 1);
+        alive.expires_at = Some(Utc::now() + Duration::hours(1));
+        store.insert(&[expired, alive]).await.unwrap();
+
+        assert_eq!(store.purge_expired().await.unwrap(), 1);
+        assert_eq!(store.list("u1", &InboxQuery::default()).await.unwrap()[0].title, "alive");
+    }
+
+    #[tokio::test]
+    async fn mark_all_read_skips_expired() {
+        let store = MemoryInboxStore::new();
+        let mut expired = message("u1", "system", "expired", 1);
+        expired.expires_at = Some(Utc::now() - Duration::seconds(1));
+        store.insert(&[expired, message("u1", "system", "alive", 1)]).await.unwrap();
+
+        assert_eq!(store.unread_counts("u1").await.unwrap().total, 1);
+        assert_eq!(store.mark_all_read("u1", None).await.unwrap(), 1);
+    }
+}
@@ -0,0 +1,187 @@
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use futures_util::{Stream, StreamExt};
+use redis::aio::MultiplexedConnection;
+
+use crate::{InboxMessage, InboxQuery, InboxStore, UnreadCounts};
+
+/// 新站内信的 pub/sub 频道，消息体为 [`InboxMessage`] 的 JSON；
+/// 每个 API 实例订阅该频道，再分发给本实例上对应用户的推送会话
+pub const INBOX_PUSH_CHANNEL: &str = "flare:inbox:push";
+
+/// 新站内信发布器
+#[async_trait]
+pub trait InboxPublisher: Send + Sync {
+    async fn publish(&self, messages: &[InboxMessage]) -> Result<()>;
+}
+
+/// Redis pub/sub 实现
+pub struct RedisInboxPublisher {
+    conn: MultiplexedConnection,
+}
+
+impl RedisInboxPublisher {
+    pub async fn connect(url: &str) -> Result<Self> {
+        let client = redis::Client::open(url).context("Redis 地址无效")?;
+        let conn = client
+            .get_multiplexed_tokio_connection()
+            .await
+            .context("连接 Redis 失败")?;
+        Ok(Self { conn })
+    }
+}
+
+#[async_trait]
+impl InboxPublisher for RedisInboxPublisher {
+    async fn publish(&self, messages: &[InboxMessage]) -> Result<()> {
+        let mut pipe = redis::pipe();
+        for message in messages {
+            pipe.publish(INBOX_PUSH_CHANNEL, serde_json::to_string(message)?).ignore();
+        }
+        let mut conn = self.conn.clone();
+        pipe.query_async::<_, ()>(&mut conn).await?;
+        Ok(())
+    }
+}
+
+/// 订阅新站内信；连接断开时流结束，由调用方重新订阅
+pub async fn subscribe_inbox(url: &str) -> Result<impl Stream<Item = InboxMessage>> {
+    let client = redis::Client::open(url).context("Redis 地址无效")?;
+    let mut pubsub = client.get_async_pubsub().await.context("连接 Redis 失败")?;
+    pubsub.subscribe(INBOX_PUSH_CHANNEL).await.context("订阅站内信频道失败")?;
+
+    Ok(pubsub.into_on_message().filter_map(|msg| async move {
+        let payload: String = match msg.get_payload() {
+            Ok(payload) => payload,
+            Err(e) => {
+                tracing::warn!("站内信推送消息读取失败: {}", e);
+                return None;
+            }
+        };
+        match serde_json::from_str(&payload) {
+            Ok(message) => Some(message),
+            Err(e) => {
+                tracing::warn!("站内信推送消息解析失败: {} payload={}", e, payload);
+                None
+            }
+        }
+    }))
+}
+
+/// 写入后发布新消息的站内信存储，只发布实际写入的消息，重投的消息不会重复推送。
+/// 发布失败只记录日志：消息已落库，客户端重连或刷新列表时仍能看到
+pub struct PublishingInboxStore {
+    inner: Arc<dyn InboxStore>,
+    publisher: Arc<dyn InboxPublisher>,
+}
+
+impl PublishingInboxStore {
+    pub fn new(inner: Arc<dyn InboxStore>, publisher: Arc<dyn InboxPublisher>) -> Self {
+        Self { inner, publisher }
+    }
+}
+
+#[async_trait]
+impl InboxStore for PublishingInboxStore {
+    async fn insert(&self, messages: &[InboxMessage]) -> Result<Vec<String>> {
+        let inserted = self.inner.insert(messages).await?;
+        let fresh: Vec<InboxMessage> = messages.iter().filter(|m| inserted.contains(&m.id)).cloned().collect();
+        if !fresh.is_empty() {
+            if let Err(e) = self.publisher.publish(&fresh).await {
+                tracing::warn!("发布站内信推送失败: {:#}", e);
+            }
+        }
+        Ok(inserted)
+    }
+
+    async fn list(&self, user_id: &str, query: &InboxQuery) -> Result<Vec<InboxMessage>> {
+        self.inner.list(user_id, query).await
+    }
+
+    async fn list_after(&self, user_id: &str, last_id: &str, limit: i64) -> Result<Vec<InboxMessage>> {
+        self.inner.list_after(user_id, last_id, limit).await
+    }
+
+    async fn mark_read(&self, user_id: &str, ids: &[String]) -> Result<u64> {
+        self.inner.mark_read(user_id, ids).await
+    }
+
+    async fn mark_all_read(&self, user_id: &str, category: Option<&str>) -> Result<u64> {
+        self.inner.mark_all_read(user_id, category).await
+    }
+
+    async fn delete(&self, user_id: &str, id: &str) -> Result<bool> {
+        self.inner.delete(user_id, id).await
+    }
+
+    async fn unread_counts(&self, user_id: &str) -> Result<UnreadCounts> {
+        self.inner.unread_counts(user_id).await
+    }
+
+    async fn purge_expired(&self) -> Result<u64> {
+        self.inner.purge_expired().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::MemoryInboxStore;
+
+    #[derive(Default)]
+    struct RecordingPublisher {
+        published: Mutex<Vec<String>>,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl InboxPublisher for RecordingPublisher {
+        async fn publish(&self, messages: &[InboxMessage]) -> Result<()> {
+            if self.fail {
+                anyhow::bail!("redis down");
+            }
+            self.published.lock().unwrap().extend(messages.iter().map(|m| m.id.clone()));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn insert_publishes_stored_messages() {
+        let publisher = Arc::new(RecordingPublisher::default());
+        let store = PublishingInboxStore::new(Arc::new(MemoryInboxStore::new()), publisher.clone());
+        let messages = [InboxMessage::new("u1", "order", "已发货", ""), InboxMessage::new("u2", "order", "已发货", "")];
+
+        store.insert(&messages).await.unwrap();
+
+        let ids: Vec<_> = messages.iter().map(|m| m.id.clone()).collect();
+        assert_eq!(*publisher.published.lock().unwrap(), ids);
+    }
+
+    #[tokio::test]
+    async fn redelivered_messages_are_not_published_again() {
+        let publisher = Arc::new(RecordingPublisher::default());
+        let store = PublishingInboxStore::new(Arc::new(MemoryInboxStore::new()), publisher.clone());
+        let first = InboxMessage::new("u1", "order", "已发货", "");
+        let second = InboxMessage::new("u2", "order", "已发货", "");
+
+        store.insert(std::slice::from_ref(&first)).await.unwrap();
+        let inserted = store.insert(&[first.clone(), second.clone()]).await.unwrap();
+
+        assert_eq!(inserted, vec![second.id.clone()]);
+        assert_eq!(*publisher.published.lock().unwrap(), [first.id, second.id]);
+    }
+
+    #[tokio::test]
+    async fn publish_failure_keeps_message() {
+        let publisher = Arc::new(RecordingPublisher { fail: true, ..Default::default() });
+        let store = PublishingInboxStore::new(Arc::new(MemoryInboxStore::new()), publisher);
+
+        store.insert(&[InboxMessage::new("u1", "order", "已发货", "")]).await.unwrap();
+
+        assert_eq!(store.unread_counts("u1").await.unwrap().total, 1);
+    }
+}
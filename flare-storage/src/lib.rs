@@ -1,3 +1,13 @@
+mod delivery;
+mod inbox;
+mod inbox_push;
+mod unread_cache;
+
+pub use delivery::*;
+pub use inbox::*;
+pub use inbox_push::*;
+pub use unread_cache::*;
+
 pub fn add(left: u64, right: u64) -> u64 {
     left + right
 }
@@ -0,0 +1,331 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use redis::aio::MultiplexedConnection;
+use redis::AsyncCommands;
+
+use crate::{
+    InboxMessage, InboxQuery, InboxStore, PgInboxStore, PublishingInboxStore, RedisInboxPublisher, UnreadCounts,
+};
+
+/// 缓存中保存总数的字段，其余字段为各分类的未读数；
+/// 该字段总是存在，用于区分“未缓存”和“没有未读”
+const TOTAL_FIELD: &str = "__total";
+
+/// 回填缓存的 Lua 脚本：版本与查询前一致时才写入，
+/// KEYS = [缓存 key, 版本 key]，ARGV = [版本, TTL 秒, 字段, 值, ...]
+const SET_IF_VERSION_SCRIPT: &str = r"
+if (redis.call('GET', KEYS[2]) or '') ~= ARGV[1] then
+    return 0
+end
+redis.call('DEL', KEYS[1])
+redis.call('HSET', KEYS[1], unpack(ARGV, 3))
+redis.call('EXPIRE', KEYS[1], ARGV[2])
+return 1
+";
+
+/// 未读数缓存。回填带上查询数据库前读到的版本，每次失效都会更换版本，
+/// 查询期间有变更时放弃回填，避免把变更前的旧值写回缓存
+#[async_trait]
+pub trait UnreadCache: Send + Sync {
+    async fn get(&self, user_id: &str) -> Result<Option<UnreadCounts>>;
+
+    /// 当前版本，从未失效过时为空串
+    async fn version(&self, user_id: &str) -> Result<String>;
+
+    /// 版本仍为 `version` 时写入，返回是否写入
+    async fn set(&self, user_id: &str, counts: &UnreadCounts, version: &str) -> Result<bool>;
+
+    /// 删除缓存并更换版本
+    async fn invalidate(&self, user_id: &str) -> Result<()>;
+}
+
+/// Redis 实现，每个用户一个 hash：`flare:inbox:unread:{user_id}`
+pub struct RedisUnreadCache {
+    conn: MultiplexedConnection,
+    /// 消息过期不会主动失效缓存，由 TTL 兜底
+    ttl: Duration,
+}
+
+impl RedisUnreadCache {
+    pub async fn connect(url: &str) -> Result<Self> {
+        let client = redis::Client::open(url).context("Redis 地址无效")?;
+        let conn = client
+            .get_multiplexed_tokio_connection()
+            .await
+            .context("连接 Redis 失败")?;
+        Ok(Self { conn, ttl: Duration::from_secs(300) })
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    fn key(user_id: &str) -> String {
+        format!("flare:inbox:unread:{}", user_id)
+    }
+
+    fn version_key(user_id: &str) -> String {
+        format!("flare:inbox:unread-version:{}", user_id)
+    }
+}
+
+#[async_trait]
+impl UnreadCache for RedisUnreadCache {
+    async fn get(&self, user_id: &str) -> Result<Option<UnreadCounts>> {
+        let mut conn = self.conn.clone();
+        let mut fields: HashMap<String, i64> = conn.hgetall(Self::key(user_id)).await?;
+        let Some(total) = fields.remove(TOTAL_FIELD) else {
+            return Ok(None);
+        };
+        Ok(Some(UnreadCounts { total, categories: fields.into_iter().collect() }))
+    }
+
+    async fn version(&self, user_id: &str) -> Result<String> {
+        let mut conn = self.conn.clone();
+        let version: Option<String> = conn.get(Self::version_key(user_id)).await?;
+        Ok(version.unwrap_or_default())
+    }
+
+    async fn set(&self, user_id: &str, counts: &UnreadCounts, version: &str) -> Result<bool> {
+        let script = redis::Script::new(SET_IF_VERSION_SCRIPT);
+        let mut invocation = script.prepare_invoke();
+        invocation
+            .key(Self::key(user_id))
+            .key(Self::version_key(user_id))
+            .arg(version)
+            .arg(self.ttl.as_secs())
+            .arg(TOTAL_FIELD)
+            .arg(counts.total);
+        for (category, count) in &counts.categories {
+            invocation.arg(category).arg(*count);
+        }
+
+        let mut conn = self.conn.clone();
+        let written: i64 = invocation.invoke_async(&mut conn).await?;
+        Ok(written == 1)
+    }
+
+    async fn invalidate(&self, user_id: &str) -> Result<()> {
+        // 版本取随机值，过期后重新生成也不会与查询前读到的版本相同
+        let mut pipe = redis::pipe();
+        pipe.atomic()
+            .set_ex(Self::version_key(user_id), uuid::Uuid::new_v4().to_string(), self.ttl.as_secs())
+            .ignore()
+            .del(Self::key(user_id))
+            .ignore();
+        let mut conn = self.conn.clone();
+        pipe.query_async::<_, ()>(&mut conn).await?;
+        Ok(())
+    }
+}
+
+/// 带未读数缓存的站内信存储：未读数优先读缓存，变更后失效对应用户的缓存。
+/// 缓存不可用时记录日志并直接访问底层存储
+pub struct CachedInboxStore {
+    inner: Arc<dyn InboxStore>,
+    cache: Arc<dyn UnreadCache>,
+}
+
+impl CachedInboxStore {
+    pub fn new(inner: Arc<dyn InboxStore>, cache: Arc<dyn UnreadCache>) -> Self {
+        Self { inner, cache }
+    }
+
+    async fn invalidate(&self, user_id: &str) {
+        if let Err(e) = self.cache.invalidate(user_id).await {
+            tracing::warn!("清除未读数缓存失败 user_id={}: {:#}", user_id, e);
+        }
+    }
+}
+
+#[async_trait]
+impl InboxStore for CachedInboxStore {
+    async fn insert(&self, messages: &[InboxMessage]) -> Result<Vec<String>> {
+        let inserted = self.inner.insert(messages).await?;
+        let users: HashSet<_> = messages.iter().map(|m| m.user_id.as_str()).collect();
+        for user_id in users {
+            self.invalidate(user_id).await;
+        }
+        Ok(inserted)
+    }
+
+    async fn list(&self, user_id: &str, query: &InboxQuery) -> Result<Vec<InboxMessage>> {
+        self.inner.list(user_id, query).await
+    }
+
+    async fn list_after(&self, user_id: &str, last_id: &str, limit: i64) -> Result<Vec<InboxMessage>> {
+        self.inner.list_after(user_id, last_id, limit).await
+    }
+
+    async fn mark_read(&self, user_id: &str, ids: &[String]) -> Result<u64> {
+        let updated = self.inner.mark_read(user_id, ids).await?;
+        if updated > 0 {
+            self.invalidate(user_id).await;
+        }
+        Ok(updated)
+    }
+
+    async fn mark_all_read(&self, user_id: &str, category: Option<&str>) -> Result<u64> {
+        let updated = self.inner.mark_all_read(user_id, category).await?;
+        if updated > 0 {
+            self.invalidate(user_id).await;
+        }
+        Ok(updated)
+    }
+
+    async fn delete(&self, user_id: &str, id: &str) -> Result<bool> {
+        let deleted = self.inner.delete(user_id, id).await?;
+        if deleted {
+            self.invalidate(user_id).await;
+        }
+        Ok(deleted)
+    }
+
+    async fn unread_counts(&self, user_id: &str) -> Result<UnreadCounts> {
+        match self.cache.get(user_id).await {
+            Ok(Some(counts)) => return Ok(counts),
+            Ok(None) => {}
+            Err(e) => tracing::warn!("读取未读数缓存失败 user_id={}: {:#}", user_id, e),
+        }
+
+        // 先取版本再查询，查询期间的变更会更换版本，旧值不会写入缓存
+        let version = match self.cache.version(user_id).await {
+            Ok(version) => Some(version),
+            Err(e) => {
+                tracing::warn!("读取未读数缓存版本失败 user_id={}: {:#}", user_id, e);
+                None
+            }
+        };
+        let counts = self.inner.unread_counts(user_id).await?;
+        if let Some(version) = version {
+            if let Err(e) = self.cache.set(user_id, &counts, &version).await {
+                tracing::warn!("写入未读数缓存失败 user_id={}: {:#}", user_id, e);
+            }
+        }
+        Ok(counts)
+    }
+
+    async fn purge_expired(&self) -> Result<u64> {
+        self.inner.purge_expired().await
+    }
+}
+
+/// 连接 Postgres 站内信存储并建表；提供 Redis 地址时套上未读数缓存，
+/// 并在写入后发布实时推送。Redis 连接失败只记录日志，不影响站内信功能
+pub async fn connect_inbox_store(database_url: &str, redis_url: Option<&str>) -> Result<Arc<dyn InboxStore>> {
+    let store = PgInboxStore::connect(database_url).await?;
+    store.migrate().await?;
+    let mut store: Arc<dyn InboxStore> = Arc::new(store);
+
+    let Some(redis_url) = redis_url else {
+        return Ok(store);
+    };
+    match RedisUnreadCache::connect(redis_url).await {
+        Ok(cache) => store = Arc::new(CachedInboxStore::new(store, Arc::new(cache))),
+        Err(e) => tracing::warn!("未读数缓存不可用，直接查询数据库: {:#}", e),
+    }
+    // 在缓存之外发布，客户端收到推送后查询到的未读数已是最新
+    match RedisInboxPublisher::connect(redis_url).await {
+        Ok(publisher) => store = Arc::new(PublishingInboxStore::new(store, Arc::new(publisher))),
+        Err(e) => tracing::warn!("站内信实时推送不可用: {:#}", e),
+    }
+    Ok(store)
+}
+
+/// 内存实现，用于测试
+#[derive(Default)]
+pub struct MemoryUnreadCache {
+    counts: std::sync::Mutex<HashMap<String, UnreadCounts>>,
+    versions: std::sync::Mutex<HashMap<String, u64>>,
+}
+
+impl MemoryUnreadCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl UnreadCache for MemoryUnreadCache {
+    async fn get(&self, user_id: &str) -> Result<Option<UnreadCounts>> {
+        Ok(self.counts.lock().unwrap().get(user_id).cloned())
+    }
+
+    async fn version(&self, user_id: &str) -> Result<String> {
+        Ok(self.versions.lock().unwrap().get(user_id).map(u64::to_string).unwrap_or_default())
+    }
+
+    async fn set(&self, user_id: &str, counts: &UnreadCounts, version: &str) -> Result<bool> {
+        let versions = self.versions.lock().unwrap();
+        let current = versions.get(user_id).map(u64::to_string).unwrap_or_default();
+        if current != version {
+            return Ok(false);
+        }
+        self.counts.lock().unwrap().insert(user_id.to_string(), counts.clone());
+        Ok(true)
+    }
+
+    async fn invalidate(&self, user_id: &str) -> Result<()> {
+        let mut versions = self.versions.lock().unwrap();
+        *versions.entry(user_id.to_string()).or_default() += 1;
+        self.counts.lock().unwrap().remove(user_id);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MemoryInboxStore;
+
+    #[tokio::test]
+    async fn counts_are_cached_until_inbox_changes() {
+        let inner = Arc::new(MemoryInboxStore::new());
+        let cache = Arc::new(MemoryUnreadCache::new());
+        let store = CachedInboxStore::new(inner.clone(), cache.clone());
+
+        let message = InboxMessage::new("u1", "order", "已发货", "您的订单已发货");
+        store.insert(std::slice::from_ref(&message)).await.unwrap();
+        assert_eq!(store.unread_counts("u1").await.unwrap().total, 1);
+        assert_eq!(cache.get("u1").await.unwrap().unwrap().total, 1);
+
+        // 绕过缓存写入底层存储，缓存未失效前仍返回旧值
+        inner.insert(&[InboxMessage::new("u1", "order", "已签收", "")]).await.unwrap();
+        assert_eq!(store.unread_counts("u1").await.unwrap().total, 1);
+
+        store.mark_read("u1", std::slice::from_ref(&message.id)).await.unwrap();
+        assert!(cache.get("u1").await.unwrap().is_none());
+        assert_eq!(store.unread_counts("u1").await.unwrap().total, 1);
+    }
+
+    #[tokio::test]
+    async fn stale_counts_are_not_written_back_after_invalidation() {
+        let cache = MemoryUnreadCache::new();
+        // 查询数据库前取得版本，查询期间有新消息写入并失效缓存
+        let version = cache.version("u1").await.unwrap();
+        let stale = UnreadCounts::default();
+        cache.invalidate("u1").await.unwrap();
+
+        assert!(!cache.set("u1", &stale, &version).await.unwrap());
+        assert!(cache.get("u1").await.unwrap().is_none());
+
+        let version = cache.version("u1").await.unwrap();
+        assert!(cache.set("u1", &UnreadCounts { total: 1, ..Default::default() }, &version).await.unwrap());
+        assert_eq!(cache.get("u1").await.unwrap().unwrap().total, 1);
+    }
+
+    #[tokio::test]
+    async fn unchanged_mutation_keeps_cache() {
+        let cache = Arc::new(MemoryUnreadCache::new());
+        let store = CachedInboxStore::new(Arc::new(MemoryInboxStore::new()), cache.clone());
+
+        assert_eq!(store.unread_counts("u1").await.unwrap(), UnreadCounts::default());
+        assert!(!store.delete("u1", "missing").await.unwrap());
+        assert_eq!(cache.get("u1").await.unwrap(), Some(UnreadCounts::default()));
+    }
+}
@@ -1,8 +1,14 @@
-use flare_common::{ChannelType, EmailConfig, FlareError, FlareResult};
+use flare_common::{ChannelType, EmailConfig, FlareError, FlareResult, PushPlatform};
 use flare_core::Notification;
 use flare_core::Sender;
-use flare_adapters::{EmailSender, SmsSender, FeishuSender, DingdingSender};
+use flare_adapters::{
+    EmailSender, SmsSender, SmsTemplate, BatchSmsItem, BatchSmsResult, TencentSmsSender, SmsRouter, FeishuSender, FeishuAppSender, DingdingSender, DingdingWorkNoticeSender,
+    WechatSender, WechatAppSender, SlackSender, SlackAppSender, TelegramSender, TeamsSender, DiscordSender,
+    WebhookSender, SiteMessageSender, PushPayload, PushRouter,
+};
+use flare_storage::{DeliveryRecord, DeliveryStore};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Message {
@@ -16,8 +22,36 @@ pub struct Message {
 pub struct HandlerContext {
     pub email_sender: EmailSender,
     pub sms_sender: SmsSender,
+    /// 未配置腾讯云短信时为 None
+    pub tencent_sms_sender: Option<TencentSmsSender>,
+    /// 配置 SMS_ROUTES 后，未指定服务商的单号码短信经路由发送
+    pub sms_router: Option<SmsRouter>,
     pub feishu_sender: FeishuSender,
+    /// 未配置飞书应用时为 None
+    pub feishu_app_sender: Option<Arc<FeishuAppSender>>,
     pub dingding_sender: DingdingSender,
+    /// 未配置钉钉企业内部应用时为 None
+    pub dingding_work_sender: Option<DingdingWorkNoticeSender>,
+    pub wechat_sender: WechatSender,
+    /// 未配置企业微信自建应用时为 None
+    pub wechat_app_sender: Option<WechatAppSender>,
+    /// 未配置 Slack Incoming Webhook 时为 None
+    pub slack_sender: Option<SlackSender>,
+    /// 未配置 Slack Bot Token 时为 None
+    pub slack_app_sender: Option<SlackAppSender>,
+    /// 未配置 Telegram Bot Token 时为 None
+    pub telegram_sender: Option<TelegramSender>,
+    /// 未配置 Teams webhook 时为 None
+    pub teams_sender: Option<TeamsSender>,
+    /// 未配置 Discord webhook 时为 None
+    pub discord_sender: Option<DiscordSender>,
+    pub webhook_sender: WebhookSender,
+    /// 未配置数据库时为 None
+    pub site_message_sender: Option<SiteMessageSender>,
+    /// APNs、FCM 及国内厂商推送，只包含已配置的通道
+    pub push_router: PushRouter,
+    /// 未配置数据库时为 None，不记录短信投递状态
+    pub delivery_store: Option<Arc<dyn DeliveryStore>>,
 }
 
 pub async fn dispatch(ctx: &HandlerContext, msg: Message) {
@@ -26,7 +60,14 @@ pub async fn dispatch(ctx: &HandlerContext, msg: Message) {
         ChannelType::Sms => handle_sms(ctx, msg).await,
         ChannelType::ImFeishu => handle_im_feishu(ctx, msg).await,
         ChannelType::ImDingding => handle_im_dingding(ctx, msg).await,
-        _ => Err(FlareError::Config("Unsupported message type".into())),
+        ChannelType::ImWechat => handle_im_wechat(ctx, msg).await,
+        ChannelType::ImSlack => handle_im_slack(ctx, msg).await,
+        ChannelType::ImTelegram => handle_im_telegram(ctx, msg).await,
+        ChannelType::ImTeams => handle_im_teams(ctx, msg).await,
+        ChannelType::ImDiscord => handle_im_discord(ctx, msg).await,
+        ChannelType::Webhook => handle_webhook(ctx, msg).await,
+        ChannelType::SiteMessage => handle_site_message(ctx, msg).await,
+        ChannelType::Push => handle_push(ctx, msg).await,
     };
 
     if let Err(e) = result {
@@ -36,13 +77,35 @@ pub async fn dispatch(ctx: &HandlerContext, msg: Message) {
 
 async fn handle_email(ctx: &HandlerContext, msg: Message) -> FlareResult<()> {
     // 从环境加载 Email 配置仅用于发件人默认值
-    let EmailConfig { smtp_user, .. } = EmailConfig::from_env()
+    let email_cfg = EmailConfig::from_env()
         .map_err(|e| FlareError::Config(format!("email config error: {}", e)))?;
+    let from = email_cfg
+        .sender()
+        .ok_or_else(|| FlareError::Config("未配置发件人 SMTP_FROM 或 SMTP_USER".into()))?
+        .to_string();
 
-    let from = smtp_user;
-    let to = require_str(&msg.payload, "to")?;
+    // to 为数组或以 , / ; 分隔的多个地址，可带显示名如 "张三 <zhangsan@example.com>"
+    let to = match &msg.payload["to"] {
+        serde_json::Value::Array(list) => list.iter().filter_map(|v| v.as_str()).collect::<Vec<_>>().join(", "),
+        _ => require_str(&msg.payload, "to")?,
+    };
     let subject = require_str(&msg.payload, "subject")?;
-    let body = require_str(&msg.payload, "body")?;
+    // 指定 html、附件、抄送等字段时按 EmailContent 组装，body 作为纯文本正文
+    const CONTENT_KEYS: [&str; 5] = ["html", "attachments", "cc", "bcc", "reply_to"];
+    let body = if CONTENT_KEYS.iter().any(|k| msg.payload.get(k).is_some()) {
+        let mut content = serde_json::Map::new();
+        if let Some(text) = msg.payload.get("text").or_else(|| msg.payload.get("body")) {
+            content.insert("text".into(), text.clone());
+        }
+        for key in CONTENT_KEYS {
+            if let Some(value) = msg.payload.get(key).filter(|v| !v.is_null()) {
+                content.insert(key.into(), value.clone());
+            }
+        }
+        serde_json::Value::Object(content).to_string()
+    } else {
+        require_str(&msg.payload, "body").or_else(|_| require_str(&msg.payload, "text"))?
+    };
 
     let notification = Notification {
         from,
@@ -56,51 +119,457 @@ async fn handle_email(ctx: &HandlerContext, msg: Message) -> FlareResult<()> {
 }
 
 async fn handle_sms(ctx: &HandlerContext, msg: Message) -> FlareResult<()> {
+    // 可选：template_code 为模板别名或模板 Code，sign_name 覆盖默认签名
+    let template = SmsTemplate {
+        template_code: require_str(&msg.payload, "template_code").ok(),
+        sign_name: require_str(&msg.payload, "sign_name").ok(),
+    };
+
+    // to 为数组或逗号分隔的多个号码
+    let to = msg.payload.get("to");
+    let multiple = to.is_some_and(|v| v.is_array() || v.as_str().is_some_and(|s| s.contains(',')));
+
+    // provider 指定服务商；未指定时单号码短信优先经路由发送，否则使用阿里云
+    match (require_str(&msg.payload, "provider").ok().as_deref(), &ctx.sms_router) {
+        (Some("tencent"), _) => return handle_sms_tencent(ctx, &msg, &template).await,
+        (None, Some(router)) if !multiple => return handle_sms_routed(ctx, router, &msg, &template).await,
+        (None | Some("aliyun"), _) => {}
+        (Some(other), _) => return Err(FlareError::Config(format!("unsupported SMS provider '{}'", other))),
+    }
+
+    // 多个号码时走批量发送
+    if multiple {
+        return handle_sms_batch(ctx, &msg, &template).await;
+    }
+
     // to: 手机号, body: 模板参数JSON字符串
     let to = require_str(&msg.payload, "to")?;
     let param = require_str(&msg.payload, "param").or_else(|_| require_str(&msg.payload, "body"))?;
 
-    let notification = Notification {
-        from: String::new(),
-        to,
-        subject: String::new(),
-        body: param,
-        channel: ChannelType::Sms,
+    let result = ctx.sms_sender.send_sms_with(&to, &param, &template).await?;
+    if let Some(biz_id) = &result.biz_id {
+        record_delivery(ctx, &[DeliveryRecord::sent(&msg.id, "aliyun", &to, biz_id)]).await;
+    }
+    Ok(())
+}
+
+/// 经路由发送：按号码与权重选择服务商，可重试的失败自动切换到下一家。
+/// 模板别名与签名交给每个服务商；失败的尝试记为 FAILED，最终发出的服务商记为 SENT
+async fn handle_sms_routed(
+    ctx: &HandlerContext,
+    router: &SmsRouter,
+    msg: &Message,
+    template: &SmsTemplate,
+) -> FlareResult<()> {
+    let to = require_str(&msg.payload, "to")?;
+    let param = match msg.payload.get("param").or_else(|| msg.payload.get("body")) {
+        Some(serde_json::Value::String(s)) => s.clone(),
+        // 按服务商分组的参数可直接写成对象
+        Some(other) => other.to_string(),
+        None => return Err(FlareError::Config("missing or invalid 'param'".into())),
     };
 
-    ctx.sms_sender.send(&notification).await
+    let dispatch = router.dispatch_with(&to, &param, template).await;
+    let mut records = Vec::with_capacity(dispatch.attempts.len());
+    for attempt in &dispatch.attempts {
+        match &attempt.error {
+            None => {
+                tracing::info!("短信 {} 经 {} 发送成功", msg.id, attempt.provider);
+                if let Some(biz_id) = dispatch.biz_id() {
+                    records.push(DeliveryRecord::sent(&msg.id, &attempt.provider, &to, biz_id));
+                }
+            }
+            Some(error) => {
+                tracing::warn!(
+                    "短信 {} 经 {} 发送失败 retryable={}: {}",
+                    msg.id,
+                    attempt.provider,
+                    attempt.retryable,
+                    error
+                );
+                let code = attempt.err_code.as_deref().unwrap_or_default();
+                records.push(DeliveryRecord::failed(&msg.id, &attempt.provider, &to, code, error));
+            }
+        }
+    }
+    record_delivery(ctx, &records).await;
+    dispatch.into_result()
+}
+
+/// 写入短信投递记录：已提交的等待状态报告推送或轮询更新终态，失败的直接为终态
+async fn record_delivery(ctx: &HandlerContext, records: &[DeliveryRecord]) {
+    let Some(store) = &ctx.delivery_store else {
+        return;
+    };
+    for record in records {
+        if let Err(e) = store.record_sent(record).await {
+            tracing::warn!("记录短信投递状态失败 ({} {}): {:#}", record.provider, record.phone, e);
+        }
+    }
+}
+
+/// 批量短信：to 的元素可以是手机号，也可以是 `{"phone","sign_name","param"}` 对象；
+/// 未单独指定 param 的号码使用 payload.param
+async fn handle_sms_batch(ctx: &HandlerContext, msg: &Message, template: &SmsTemplate) -> FlareResult<()> {
+    let payload = &msg.payload;
+    let param = require_str(payload, "param").or_else(|_| require_str(payload, "body")).ok();
+    let entries: Vec<serde_json::Value> = match &payload["to"] {
+        serde_json::Value::Array(list) => list.clone(),
+        other => other
+            .as_str()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|phone| !phone.is_empty())
+            .map(serde_json::Value::from)
+            .collect(),
+    };
+
+    let items = entries
+        .into_iter()
+        .map(|entry| {
+            let mut item = match entry {
+                serde_json::Value::String(phone) => BatchSmsItem { phone, ..Default::default() },
+                other => serde_json::from_value::<BatchSmsItem>(other)
+                    .map_err(|e| FlareError::Config(format!("invalid 'to' entry: {}", e)))?,
+            };
+            if item.template_param.is_none() {
+                item.template_param = param.clone();
+            }
+            Ok(item)
+        })
+        .collect::<FlareResult<Vec<_>>>()?;
+
+    let results = ctx.sms_sender.send_batch_sms(&items, template).await?;
+    report_sms_results(ctx, &msg.id, "aliyun", &results).await
+}
+
+/// 腾讯云短信：to 为一个或多个号码，param 为模板参数 JSON 数组
+async fn handle_sms_tencent(ctx: &HandlerContext, msg: &Message, template: &SmsTemplate) -> FlareResult<()> {
+    let sender = ctx
+        .tencent_sms_sender
+        .as_ref()
+        .ok_or_else(|| FlareError::Config("未配置腾讯云短信".into()))?;
+    let phones: Vec<String> = match &msg.payload["to"] {
+        serde_json::Value::Array(list) => list.iter().filter_map(|v| v.as_str()).map(str::to_string).collect(),
+        other => other
+            .as_str()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|phone| !phone.is_empty())
+            .map(str::to_string)
+            .collect(),
+    };
+    if phones.is_empty() {
+        return Err(FlareError::Config("missing or invalid 'to'".into()));
+    }
+    let param = require_str(&msg.payload, "param").or_else(|_| require_str(&msg.payload, "body")).unwrap_or_default();
+    let params = flare_adapters::parse_template_params(&param)?;
+
+    let phones: Vec<&str> = phones.iter().map(String::as_str).collect();
+    // 腾讯云以 SerialNo 作为回执 ID
+    let results = sender.send_sms(&phones, &params, template).await?;
+    report_sms_results(ctx, &msg.id, "tencent", &results).await
+}
+
+/// 记录各号码的发送结果：提交成功的记为 SENT 等待回执，失败的记为 FAILED；
+/// 全部号码失败时该消息视为失败
+async fn report_sms_results(
+    ctx: &HandlerContext,
+    message_id: &str,
+    provider: &str,
+    results: &[BatchSmsResult],
+) -> FlareResult<()> {
+    let mut records = Vec::with_capacity(results.len());
+    let mut failed = 0;
+    for result in results {
+        match (&result.error, &result.biz_id) {
+            (None, Some(biz_id)) => records.push(DeliveryRecord::sent(message_id, provider, &result.phone, biz_id)),
+            (None, None) => {}
+            (Some((code, message)), _) => {
+                failed += 1;
+                tracing::warn!("短信 {} 经 {} 发送到 {} 失败: {} {}", message_id, provider, result.phone, code, message);
+                records.push(DeliveryRecord::failed(message_id, provider, &result.phone, code, message));
+            }
+        }
+    }
+    record_delivery(ctx, &records).await;
+    if !results.is_empty() && failed == results.len() {
+        return Err(FlareError::String(format!("batch SMS failed for all {} numbers", results.len())));
+    }
+    Ok(())
 }
 
 async fn handle_im_feishu(ctx: &HandlerContext, msg: Message) -> FlareResult<()> {
     // 支持 payload.text 或 payload.body 作为文本
     let text = require_str(&msg.payload, "text")
         .or_else(|_| require_str(&msg.payload, "body"))?;
+    // 带 payload.to 时走应用机器人发给指定用户/群聊，否则走自定义机器人 webhook
+    let to = require_str(&msg.payload, "to").ok();
 
     let notification = Notification {
         from: String::new(),
-        to: String::new(),
+        to: to.clone().unwrap_or_default(),
         subject: String::new(),
         body: text,
         channel: ChannelType::ImFeishu,
     };
 
-    ctx.feishu_sender.send(&notification).await
+    match (to, &ctx.feishu_app_sender) {
+        (Some(_), Some(app_sender)) => app_sender.send(&notification).await,
+        (Some(_), None) => Err(FlareError::Config("未配置飞书应用，无法按接收人发送".into())),
+        (None, _) => ctx.feishu_sender.send(&notification).await,
+    }
 }
 
 async fn handle_im_dingding(ctx: &HandlerContext, msg: Message) -> FlareResult<()> {
     // 支持 payload.text 或 payload.body 作为消息内容
     let content = require_str(&msg.payload, "text")
         .or_else(|_| require_str(&msg.payload, "body"))?;
+    // 带 payload.to 时走工作通知发给指定员工/部门，否则走机器人 webhook
+    let to = require_str(&msg.payload, "to").ok();
 
     let notification = Notification {
         from: String::new(),
-        to: String::new(),
+        to: to.clone().unwrap_or_default(),
         subject: String::new(),
         body: content,
         channel: ChannelType::ImDingding,
     };
 
-    ctx.dingding_sender.send(&notification).await
+    match (to, &ctx.dingding_work_sender) {
+        (Some(_), Some(work_sender)) => work_sender.send(&notification).await,
+        (Some(_), None) => Err(FlareError::Config("未配置钉钉企业内部应用，无法按接收人发送".into())),
+        (None, _) => ctx.dingding_sender.send(&notification).await,
+    }
+}
+
+async fn handle_im_wechat(ctx: &HandlerContext, msg: Message) -> FlareResult<()> {
+    // 支持 payload.text 或 payload.body 作为消息内容
+    let content = require_str(&msg.payload, "text")
+        .or_else(|_| require_str(&msg.payload, "body"))?;
+    // 带 payload.to 时走自建应用发给指定成员/部门/标签，否则走群机器人
+    let to = require_str(&msg.payload, "to").ok();
+
+    let notification = Notification {
+        from: String::new(),
+        to: to.clone().unwrap_or_default(),
+        subject: String::new(),
+        body: content,
+        channel: ChannelType::ImWechat,
+    };
+
+    match (to, &ctx.wechat_app_sender) {
+        (Some(_), Some(app_sender)) => app_sender.send(&notification).await,
+        (Some(_), None) => Err(FlareError::Config("未配置企业微信自建应用，无法按接收人发送".into())),
+        (None, _) => ctx.wechat_sender.send(&notification).await,
+    }
+}
+
+async fn handle_im_slack(ctx: &HandlerContext, msg: Message) -> FlareResult<()> {
+    // 带 blocks / thread_ts 时整个 payload（除 to 外）作为消息体，否则取 payload.text 或 payload.body
+    let to = require_str(&msg.payload, "to").ok();
+    let body = if msg.payload.get("blocks").is_some() || msg.payload.get("thread_ts").is_some() {
+        let mut payload = msg.payload.clone();
+        if let Some(obj) = payload.as_object_mut() {
+            obj.remove("to");
+        }
+        payload.to_string()
+    } else {
+        require_str(&msg.payload, "text").or_else(|_| require_str(&msg.payload, "body"))?
+    };
+
+    let notification = Notification {
+        from: String::new(),
+        to: to.clone().unwrap_or_default(),
+        subject: String::new(),
+        body,
+        channel: ChannelType::ImSlack,
+    };
+
+    // 带 payload.to 时走 chat.postMessage 发到指定频道，否则走 Incoming Webhook
+    match (to, &ctx.slack_app_sender, &ctx.slack_sender) {
+        (Some(_), Some(app_sender), _) => app_sender.send(&notification).await,
+        (Some(_), None, _) => Err(FlareError::Config("未配置 Slack Bot Token，无法按频道发送".into())),
+        (None, _, Some(sender)) => sender.send(&notification).await,
+        (None, _, None) => Err(FlareError::Config("未配置 Slack Incoming Webhook".into())),
+    }
+}
+
+async fn handle_im_telegram(ctx: &HandlerContext, msg: Message) -> FlareResult<()> {
+    let sender = ctx
+        .telegram_sender
+        .as_ref()
+        .ok_or_else(|| FlareError::Config("未配置 Telegram Bot Token".into()))?;
+    // payload.chat_id（或 payload.to）缺省时使用 TELEGRAM_CHAT_ID
+    let chat_id = require_str(&msg.payload, "chat_id")
+        .or_else(|_| require_str(&msg.payload, "to"))
+        .unwrap_or_default();
+    // 带 msg_type / photo / document / params 时整个 payload 作为消息体，否则取 payload.text 或 payload.body
+    let structured = ["msg_type", "photo", "document", "params"]
+        .iter()
+        .any(|key| msg.payload.get(key).is_some());
+    let body = if structured {
+        let mut payload = msg.payload.clone();
+        if let Some(obj) = payload.as_object_mut() {
+            obj.remove("to");
+            obj.remove("chat_id");
+        }
+        payload.to_string()
+    } else {
+        require_str(&msg.payload, "text").or_else(|_| require_str(&msg.payload, "body"))?
+    };
+
+    let notification = Notification {
+        from: String::new(),
+        to: chat_id,
+        subject: String::new(),
+        body,
+        channel: ChannelType::ImTelegram,
+    };
+
+    sender.send(&notification).await
+}
+
+async fn handle_im_teams(ctx: &HandlerContext, msg: Message) -> FlareResult<()> {
+    let sender = ctx
+        .teams_sender
+        .as_ref()
+        .ok_or_else(|| FlareError::Config("未配置 Teams webhook".into()))?;
+    // 支持 payload.text 或 payload.body 作为消息内容，payload.to 可指定其他 webhook 地址
+    let content = require_str(&msg.payload, "text")
+        .or_else(|_| require_str(&msg.payload, "body"))?;
+
+    let notification = Notification {
+        from: String::new(),
+        to: require_str(&msg.payload, "to").unwrap_or_default(),
+        subject: String::new(),
+        body: content,
+        channel: ChannelType::ImTeams,
+    };
+
+    sender.send(&notification).await
+}
+
+async fn handle_im_discord(ctx: &HandlerContext, msg: Message) -> FlareResult<()> {
+    let sender = ctx
+        .discord_sender
+        .as_ref()
+        .ok_or_else(|| FlareError::Config("未配置 Discord webhook".into()))?;
+    // 支持 payload.text 或 payload.body 作为消息内容，payload.to 可指定其他 webhook 地址
+    let content = require_str(&msg.payload, "text")
+        .or_else(|_| require_str(&msg.payload, "body"))?;
+
+    let notification = Notification {
+        from: String::new(),
+        to: require_str(&msg.payload, "to").unwrap_or_default(),
+        subject: String::new(),
+        body: content,
+        channel: ChannelType::ImDiscord,
+    };
+
+    sender.send(&notification).await
+}
+
+async fn handle_webhook(ctx: &HandlerContext, msg: Message) -> FlareResult<()> {
+    // payload.url 缺省时使用 WEBHOOK_URL；带 data 时 method/headers/template/timeout_secs
+    // 覆盖默认配置，否则其余字段整体作为数据发送
+    let url = require_str(&msg.payload, "url").unwrap_or_default();
+    let mut request = msg.payload;
+    if let Some(obj) = request.as_object_mut() {
+        obj.remove("url");
+    }
+
+    let notification = Notification {
+        from: String::new(),
+        to: url,
+        subject: String::new(),
+        body: request.to_string(),
+        channel: ChannelType::Webhook,
+    };
+
+    ctx.webhook_sender.send(&notification).await
+}
+
+async fn handle_site_message(ctx: &HandlerContext, msg: Message) -> FlareResult<()> {
+    let sender = ctx
+        .site_message_sender
+        .as_ref()
+        .ok_or_else(|| FlareError::Config("未配置数据库，无法发送站内信".into()))?;
+    // payload.to 为用户 ID，可以是数组或以 , 分隔的字符串
+    let to = match &msg.payload["to"] {
+        serde_json::Value::Array(users) => users
+            .iter()
+            .filter_map(|v| v.as_str())
+            .collect::<Vec<_>>()
+            .join(","),
+        _ => require_str(&msg.payload, "to")?,
+    };
+    let title = require_str(&msg.payload, "title").or_else(|_| require_str(&msg.payload, "subject"))?;
+    let content = require_str(&msg.payload, "content")
+        .or_else(|_| require_str(&msg.payload, "text"))
+        .or_else(|_| require_str(&msg.payload, "body"))?;
+    let body = serde_json::json!({
+        "content": content,
+        "category": msg.payload.get("category"),
+        "data": msg.payload.get("data"),
+        "expires_at": msg.payload.get("expires_at"),
+        "ttl_secs": msg.payload.get("ttl_secs"),
+        // 同一条 Kafka 消息重投时写入相同的站内信 ID，不会重复
+        "message_id": msg.id,
+    });
+
+    let notification = Notification {
+        from: msg.source.clone(),
+        to,
+        subject: title,
+        body: body.to_string(),
+        channel: ChannelType::SiteMessage,
+    };
+
+    sender.send(&notification).await
+}
+
+async fn handle_push(ctx: &HandlerContext, msg: Message) -> FlareResult<()> {
+    if ctx.push_router.is_empty() {
+        return Err(FlareError::Config("未配置推送通道".into()));
+    }
+    // payload.platform 为不带厂商标签的设备令牌所用的通道，缺省时使用唯一配置的通道
+    let platform = match msg.payload.get("platform") {
+        Some(v) => Some(
+            serde_json::from_value::<PushPlatform>(v.clone())
+                .map_err(|_| FlareError::Config(format!("不支持的推送平台: {}", v)))?,
+        ),
+        None => None,
+    };
+    // payload.tokens 为设备令牌数组，也可以用 payload.to 传以 , 分隔的字符串；
+    // 令牌可带厂商标签，如 huawei:xxx、xiaomi:xxx
+    let to = match &msg.payload["tokens"] {
+        serde_json::Value::Array(tokens) => tokens
+            .iter()
+            .filter_map(|v| v.as_str())
+            .collect::<Vec<_>>()
+            .join(","),
+        _ => require_str(&msg.payload, "to")?,
+    };
+    // 其余字段（title、body、badge、sound、data 等）作为推送内容
+    let mut body = msg.payload;
+    if let Some(obj) = body.as_object_mut() {
+        obj.remove("platform");
+        obj.remove("tokens");
+        obj.remove("to");
+    }
+    let payload = PushPayload::parse(&body.to_string(), "");
+    let tokens: Vec<&str> = to.split(',').map(str::trim).filter(|t| !t.is_empty()).collect();
+
+    let report = ctx.push_router.push(&tokens, &payload, platform).await?;
+    if report.invalid_tokens.is_empty() {
+        tracing::info!("推送 {} 完成: {} 个设备", msg.id, report.sent);
+    } else {
+        tracing::info!("推送 {} 完成: {} 个设备，{} 个令牌已失效", msg.id, report.sent, report.invalid_tokens.len());
+    }
+    Ok(())
 }
 
 fn require_str(payload: &serde_json::Value, key: &str) -> FlareResult<String> {
@@ -0,0 +1,36 @@
+use std::time::Duration;
+
+use flare_adapters::InvalidTokenHandler;
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::ClientConfig;
+use serde_json::json;
+use tracing::warn;
+
+/// 把推送中发现的失效设备令牌发布到 Kafka，由业务方订阅后清理设备记录。
+/// 消息为 `{"platform": "huawei", "tokens": ["huawei:xxx"]}`，令牌保持调用方传入的写法（含厂商标签），
+/// 以平台名为 key
+pub struct KafkaInvalidTokenPublisher {
+    producer: FutureProducer,
+    topic: String,
+}
+
+impl KafkaInvalidTokenPublisher {
+    pub fn new(bootstrap_servers: &str, topic: String) -> anyhow::Result<Self> {
+        let producer = ClientConfig::new()
+            .set("bootstrap.servers", bootstrap_servers)
+            .set("message.timeout.ms", "5000")
+            .create()?;
+        Ok(Self { producer, topic })
+    }
+}
+
+#[async_trait::async_trait]
+impl InvalidTokenHandler for KafkaInvalidTokenPublisher {
+    async fn handle(&self, platform: &'static str, tokens: &[String]) {
+        let payload = json!({ "platform": platform, "tokens": tokens }).to_string();
+        let record = FutureRecord::to(&self.topic).key(platform).payload(&payload);
+        if let Err((e, _)) = self.producer.send(record, Duration::from_secs(5)).await {
+            warn!("发布失效设备令牌失败 ({} 个 {} 令牌): {}", tokens.len(), platform, e);
+        }
+    }
+}
@@ -1,16 +1,32 @@
+use anyhow::Context;
 use flare_core::init_logger;
 use rdkafka::{consumer::{Consumer, StreamConsumer}, ClientConfig, Message};
 use futures_util::StreamExt;
 use tracing::info;
-use flare_common::{EmailConfig, SmsConfig, FeishuConfig, DingdingConfig};
-use flare_adapters::{EmailSender, SmsSender, FeishuSender, DingdingSender};
+use flare_common::{
+    DatabaseConfig, RedisConfig, EmailConfig, SmsConfig, FeishuConfig, FeishuAppConfig, DingdingConfig, DingdingAppConfig, WechatConfig,
+    WechatAppConfig, TencentSmsConfig, SmsRouterConfig, WebhookConfig, SlackConfig, SlackAppConfig, TelegramConfig,
+    TeamsConfig, DiscordConfig, ApnsConfig, FcmConfig, HuaweiPushConfig, HonorPushConfig, XiaomiPushConfig,
+    OppoPushConfig, VivoPushConfig, PushPlatform, optional_config,
+};
+use flare_adapters::{
+    EmailSender, SmsSender, FeishuSender, FeishuAppSender, DingdingSender, DingdingWorkNoticeSender,
+    WechatSender, WechatAppSender, TencentSmsSender, SmsRouter, SmsProvider, WebhookSender, SlackSender, SlackAppSender, TelegramSender,
+    TeamsSender, DiscordSender, SiteMessageSender, ApnsSender, FcmSender, HuaweiPushSender, HonorPushSender,
+    XiaomiPushSender, OppoPushSender, VivoPushSender, PushRouter,
+};
+use flare_storage::{connect_inbox_store, DeliveryStore, InboxStore, PgDeliveryStore};
 use crate::handlers::HandlerContext;
+use std::sync::Arc;
+use std::time::Duration;
 
 
 mod handlers;
+mod invalid_tokens;
+mod receipt;
 
 #[tokio::main]
-async fn main() {
+async fn main() -> anyhow::Result<()> {
     // 加载环境变量并从配置构建 EmailConfig
     dotenvy::dotenv().ok();
     // 初始化日志
@@ -42,11 +58,88 @@ async fn main() {
     let sms_cfg = SmsConfig::from_env().expect("加载短信配置失败");
     let feishu_cfg = FeishuConfig::from_env().expect("加载飞书配置失败");
     let dingding_cfg = DingdingConfig::from_env().expect("加载钉钉配置失败");
+    let wechat_cfg = WechatConfig::from_env().expect("加载企业微信配置失败");
+    let webhook_cfg = WebhookConfig::from_env().expect("加载 webhook 配置失败");
+    // 腾讯云短信为可选配置
+    let tencent_sms_cfg = optional_config("TENCENT_SMS_SECRET_ID", TencentSmsConfig::from_env)
+        .context("加载腾讯云短信配置失败")?;
+    // 短信路由为可选配置，路由中的服务商缺少配置时启动失败
+    let sms_router = optional_config("SMS_ROUTES", SmsRouterConfig::from_env)
+        .context("加载短信路由配置失败")?
+        .map(|cfg| build_sms_router(&cfg, &sms_cfg, tencent_sms_cfg.as_ref()))
+        .transpose()?;
+    // 飞书应用机器人为可选配置，配置后同时为 webhook 消息提供媒体上传
+    let feishu_app_sender = optional_config("FEISHU_APP_ID", FeishuAppConfig::from_env)
+        .context("加载飞书应用配置失败")?
+        .map(|cfg| Arc::new(FeishuAppSender::new(cfg)));
+    let feishu_sender = match &feishu_app_sender {
+        Some(app_sender) => FeishuSender::new(feishu_cfg).with_uploader(app_sender.clone()),
+        None => FeishuSender::new(feishu_cfg),
+    };
+    // 配置数据库后记录短信投递状态，并轮询回执作为推送的兜底
+    let delivery_store = connect_delivery_store().await;
+    if let Some(store) = &delivery_store {
+        let interval = std::env::var("SMS_RECEIPT_POLL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(60);
+        tokio::spawn(receipt::poll_sms_receipts(
+            SmsSender::new(sms_cfg.clone()),
+            store.clone(),
+            Duration::from_secs(interval),
+        ));
+    }
+    // 配置数据库后启用站内信，并定期清理过期消息
+    let inbox_store = connect_inbox().await;
+    if let Some(store) = &inbox_store {
+        tokio::spawn(purge_expired_inbox(store.clone(), Duration::from_secs(3600)));
+    }
+    // 配置 PUSH_INVALID_TOKEN_TOPIC 后把失效的设备令牌发布到该 topic，否则只记录日志
+    let mut push_router = build_push_router()?;
+    if let Ok(topic) = std::env::var("PUSH_INVALID_TOKEN_TOPIC") {
+        let publisher = invalid_tokens::KafkaInvalidTokenPublisher::new(&kafka_servers, topic)
+            .context("创建失效令牌 Kafka producer 失败")?;
+        push_router = push_router.with_invalid_token_handler(Arc::new(publisher));
+    }
     let ctx = HandlerContext {
-        email_sender: EmailSender::new(&email_cfg),
-        sms_sender: SmsSender::new(sms_cfg),
-        feishu_sender: FeishuSender::new(feishu_cfg),
+        email_sender: EmailSender::new(&email_cfg).context("初始化 SMTP 发送器失败")?,
+        sms_sender: SmsSender::new(sms_cfg.clone()),
+        tencent_sms_sender: tencent_sms_cfg.map(TencentSmsSender::new),
+        sms_router,
+        feishu_sender,
+        feishu_app_sender,
         dingding_sender: DingdingSender::new(dingding_cfg),
+        // 钉钉企业内部应用为可选配置
+        dingding_work_sender: optional_config("DINGDING_APP_KEY", DingdingAppConfig::from_env)
+            .context("加载钉钉企业内部应用配置失败")?
+            .map(DingdingWorkNoticeSender::new),
+        wechat_sender: WechatSender::new(wechat_cfg),
+        // 自建应用为可选配置
+        wechat_app_sender: optional_config("WECHAT_CORP_ID", WechatAppConfig::from_env)
+            .context("加载企业微信自建应用配置失败")?
+            .map(WechatAppSender::new),
+        // Slack 为可选配置
+        slack_sender: optional_config("SLACK_WEBHOOK", SlackConfig::from_env)
+            .context("加载 Slack webhook 配置失败")?
+            .map(SlackSender::new),
+        slack_app_sender: optional_config("SLACK_BOT_TOKEN", SlackAppConfig::from_env)
+            .context("加载 Slack Bot 配置失败")?
+            .map(SlackAppSender::new),
+        // Telegram 为可选配置
+        telegram_sender: optional_config("TELEGRAM_BOT_TOKEN", TelegramConfig::from_env)
+            .context("加载 Telegram 配置失败")?
+            .map(TelegramSender::new),
+        // Teams、Discord 为可选配置
+        teams_sender: optional_config("TEAMS_WEBHOOK", TeamsConfig::from_env)
+            .context("加载 Teams 配置失败")?
+            .map(TeamsSender::new),
+        discord_sender: optional_config("DISCORD_WEBHOOK", DiscordConfig::from_env)
+            .context("加载 Discord 配置失败")?
+            .map(DiscordSender::new),
+        webhook_sender: WebhookSender::new(webhook_cfg),
+        site_message_sender: inbox_store.map(SiteMessageSender::new),
+        push_router,
+        delivery_store,
     };
 
     let mut stream = consumer.stream();
@@ -70,4 +163,104 @@ async fn main() {
             Err(e) => eprintln!("Kafka error: {}", e),
         }
     }
+    Ok(())
+}
+
+async fn connect_delivery_store() -> Option<Arc<dyn DeliveryStore>> {
+    let db_cfg = DatabaseConfig::from_env().ok()?;
+    let store = match PgDeliveryStore::connect(&db_cfg.url).await {
+        Ok(store) => store,
+        Err(e) => {
+            eprintln!("Delivery store disabled: {:#}", e);
+            return None;
+        }
+    };
+    if let Err(e) = store.migrate().await {
+        eprintln!("Delivery store disabled: {:#}", e);
+        return None;
+    }
+    Some(Arc::new(store))
+}
+
+async fn connect_inbox() -> Option<Arc<dyn InboxStore>> {
+    let db_cfg = DatabaseConfig::from_env().ok()?;
+    // 配置 REDIS_URL 后缓存未读数
+    let redis_url = RedisConfig::from_env().ok().map(|cfg| cfg.url);
+    match connect_inbox_store(&db_cfg.url, redis_url.as_deref()).await {
+        Ok(store) => Some(store),
+        Err(e) => {
+            eprintln!("Inbox store disabled: {:#}", e);
+            None
+        }
+    }
+}
+
+async fn purge_expired_inbox(store: Arc<dyn InboxStore>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        match store.purge_expired().await {
+            Ok(0) => {}
+            Ok(purged) => info!("清理过期站内信 {} 条", purged),
+            Err(e) => tracing::warn!("清理过期站内信失败: {:#}", e),
+        }
+    }
+}
+
+/// 按 SMS_ROUTES 组装短信路由，路由中用到的服务商必须已配置
+fn build_sms_router(
+    cfg: &SmsRouterConfig,
+    sms_cfg: &SmsConfig,
+    tencent_cfg: Option<&TencentSmsConfig>,
+) -> anyhow::Result<SmsRouter> {
+    if cfg.routes.is_empty() {
+        anyhow::bail!("SMS_ROUTES 未包含任何短信服务商");
+    }
+    let mut router = SmsRouter::new();
+    for route in &cfg.routes {
+        let provider = match route.provider.as_str() {
+            "aliyun" => SmsProvider::new("aliyun", SmsSender::new(sms_cfg.clone())),
+            "tencent" => {
+                let tencent_cfg = tencent_cfg.context("SMS_ROUTES 使用了 tencent，但未配置腾讯云短信 (TENCENT_SMS_SECRET_ID)")?;
+                SmsProvider::new("tencent", TencentSmsSender::new(tencent_cfg.clone()))
+            }
+            other => anyhow::bail!("SMS_ROUTES 中不支持的短信服务商: {}", other),
+        };
+        router = router.with_provider(provider.weight(route.weight).prefixes(route.prefixes.clone()));
+    }
+    Ok(router)
+}
+
+/// 按已配置的推送通道组装路由，配置了但不完整或密钥无效时直接退出
+fn build_push_router() -> anyhow::Result<PushRouter> {
+    let mut router = PushRouter::new();
+    if let Some(cfg) = optional_config("APNS_TEAM_ID", ApnsConfig::from_env).context("加载 APNs 配置失败")? {
+        let sender = ApnsSender::new(cfg).context("初始化 APNs 推送失败")?;
+        router = router.with_provider(PushPlatform::Apns, Arc::new(sender));
+    }
+    // 服务账号可以直接配置内容，也可以配置文件路径
+    let fcm_cfg = match optional_config("FCM_SERVICE_ACCOUNT", FcmConfig::from_env).context("加载 FCM 配置失败")? {
+        Some(cfg) => Some(cfg),
+        None => optional_config("FCM_SERVICE_ACCOUNT_PATH", FcmConfig::from_env).context("加载 FCM 配置失败")?,
+    };
+    if let Some(cfg) = fcm_cfg {
+        let sender = FcmSender::new(cfg).context("初始化 FCM 推送失败")?;
+        router = router.with_provider(PushPlatform::Fcm, Arc::new(sender));
+    }
+    if let Some(cfg) = optional_config("HUAWEI_PUSH_APP_ID", HuaweiPushConfig::from_env).context("加载华为推送配置失败")? {
+        router = router.with_provider(PushPlatform::Huawei, Arc::new(HuaweiPushSender::new(cfg)));
+    }
+    if let Some(cfg) = optional_config("HONOR_PUSH_APP_ID", HonorPushConfig::from_env).context("加载荣耀推送配置失败")? {
+        router = router.with_provider(PushPlatform::Honor, Arc::new(HonorPushSender::new(cfg)));
+    }
+    if let Some(cfg) = optional_config("XIAOMI_PUSH_APP_SECRET", XiaomiPushConfig::from_env).context("加载小米推送配置失败")? {
+        router = router.with_provider(PushPlatform::Xiaomi, Arc::new(XiaomiPushSender::new(cfg)));
+    }
+    if let Some(cfg) = optional_config("OPPO_PUSH_APP_KEY", OppoPushConfig::from_env).context("加载 OPPO 推送配置失败")? {
+        router = router.with_provider(PushPlatform::Oppo, Arc::new(OppoPushSender::new(cfg)));
+    }
+    if let Some(cfg) = optional_config("VIVO_PUSH_APP_ID", VivoPushConfig::from_env).context("加载 vivo 推送配置失败")? {
+        router = router.with_provider(PushPlatform::Vivo, Arc::new(VivoPushSender::new(cfg)));
+    }
+    Ok(router)
 }
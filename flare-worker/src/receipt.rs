@@ -0,0 +1,70 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{FixedOffset, Utc};
+use flare_adapters::SmsSender;
+use flare_storage::{DeliveryReceipt, DeliveryRecord, DeliveryStore};
+use tracing::{info, warn};
+
+/// 每轮最多查询的记录数
+const POLL_BATCH: i64 = 100;
+
+/// 轮询尚未收到回执的阿里云短信，作为状态报告推送的兜底。
+/// 刚提交的记录先等推送；超过 3 天仍无回执的不再查询
+pub async fn poll_sms_receipts(sender: SmsSender, store: Arc<dyn DeliveryStore>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        match poll_once(&sender, store.as_ref()).await {
+            Ok(0) => {}
+            Ok(updated) => info!("短信回执轮询更新 {} 条记录", updated),
+            Err(e) => warn!("短信回执轮询失败: {:#}", e),
+        }
+    }
+}
+
+async fn poll_once(sender: &SmsSender, store: &dyn DeliveryStore) -> anyhow::Result<usize> {
+    let now = Utc::now();
+    let records = store
+        .pending("aliyun", now - chrono::Duration::days(3), now - chrono::Duration::minutes(2), POLL_BATCH)
+        .await?;
+
+    let mut updated = 0;
+    for record in records {
+        let Some(receipt) = query_receipt(sender, &record).await else {
+            // 仍无终态，退避后再查，避免挡住后续记录
+            store.mark_polled(&record.biz_id, &record.phone).await?;
+            continue;
+        };
+        if store.apply_receipt(&receipt).await? {
+            updated += 1;
+        }
+    }
+    Ok(updated)
+}
+
+/// 查询单条记录的回执，仍在等待回执或查询失败时返回 None
+async fn query_receipt(sender: &SmsSender, record: &DeliveryRecord) -> Option<DeliveryReceipt> {
+    // SendDate 按北京时间计算
+    let beijing = FixedOffset::east_opt(8 * 3600).expect("valid offset");
+    let send_date = record.sent_at.with_timezone(&beijing).format("%Y%m%d").to_string();
+
+    let details = match sender.query_send_details(&record.phone, &send_date, Some(&record.biz_id)).await {
+        Ok(details) => details,
+        Err(e) => {
+            warn!("查询短信回执失败 biz_id={} phone={}: {}", record.biz_id, record.phone, e);
+            return None;
+        }
+    };
+    let detail = details
+        .into_iter()
+        .find(|d| d.phone_num == record.phone && d.delivery_status().is_final())?;
+
+    Some(DeliveryReceipt {
+        biz_id: record.biz_id.clone(),
+        phone: record.phone.clone(),
+        status: detail.delivery_status(),
+        err_code: detail.err_code,
+        err_msg: None,
+    })
+}